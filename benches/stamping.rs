@@ -0,0 +1,71 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::sync::Barrier;
+use std::thread;
+use std::time::{Duration, Instant};
+use uhlc::HLC;
+
+fn single_thread(c: &mut Criterion) {
+    let hlc = HLC::default();
+    c.bench_function("new_timestamp", |b| {
+        b.iter(|| black_box(hlc.new_timestamp()))
+    });
+
+    let other = HLC::default();
+    c.bench_function("update_with_timestamp", |b| {
+        b.iter_batched(
+            || other.new_timestamp(),
+            |ts| black_box(hlc.update_with_timestamp(&ts)),
+            criterion::BatchSize::SmallInput,
+        )
+    });
+}
+
+fn contended(c: &mut Criterion) {
+    let mut group = c.benchmark_group("new_timestamp_contended");
+    for threads in [2, 4, 8] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(threads),
+            &threads,
+            |b, &threads| {
+                // measures the time for each thread to generate `iters` timestamps concurrently
+                b.iter_custom(|iters| {
+                    let hlc = HLC::default();
+                    let barrier = Barrier::new(threads);
+                    thread::scope(|s| {
+                        let handles: Vec<_> = (0..threads)
+                            .map(|_| {
+                                s.spawn(|| {
+                                    barrier.wait();
+                                    let start = Instant::now();
+                                    for _ in 0..iters {
+                                        black_box(hlc.new_timestamp());
+                                    }
+                                    start.elapsed()
+                                })
+                            })
+                            .collect();
+                        handles
+                            .into_iter()
+                            .map(|h| h.join().unwrap())
+                            .max()
+                            .unwrap_or(Duration::ZERO)
+                    })
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, single_thread, contended);
+criterion_main!(benches);
@@ -0,0 +1,185 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{Timestamp, ID, NTP64};
+use alloc::{format, sync::Arc, vec::Vec};
+use arrow_array::{Array, ArrayRef, FixedSizeBinaryArray, StructArray, TimestampNanosecondArray};
+use arrow_schema::{ArrowError, DataType, Field, Fields, TimeUnit};
+use core::{convert::TryFrom, time::Duration};
+
+/// The name of the time column in a [`StructArray`] of [`Timestamp`]s.
+pub const ARROW_TIME_FIELD: &str = "time";
+/// The name of the ID column in a [`StructArray`] of [`Timestamp`]s.
+pub const ARROW_ID_FIELD: &str = "id";
+// The timezone of the time column (the NTP64 time is assumed to be relative to UNIX_EPOCH)
+const ARROW_TIMEZONE: &str = "UTC";
+
+/// Returns the Arrow [`Fields`] of a [`StructArray`] of [`Timestamp`]s:
+///  * `"time"`: a non-nullable `timestamp[ns, tz=UTC]`
+///  * `"id"`: a non-nullable `fixed_size_binary[16]` holding the le-encoded [`ID`]
+pub fn timestamp_arrow_fields() -> Fields {
+    Fields::from(vec![
+        Field::new(
+            ARROW_TIME_FIELD,
+            DataType::Timestamp(TimeUnit::Nanosecond, Some(ARROW_TIMEZONE.into())),
+            false,
+        ),
+        Field::new(
+            ARROW_ID_FIELD,
+            DataType::FixedSizeBinary(ID::MAX_SIZE as i32),
+            false,
+        ),
+    ])
+}
+
+/// Convert a slice of [`Timestamp`]s into an Arrow [`StructArray`] (see [`timestamp_arrow_fields()`] for its layout).
+///
+/// **NOTE: such conversion is lossy.** The time is converted to nanoseconds (see [`NTP64::as_nanos()`]),
+/// losing the sub-nanosecond precision of the NTP64 Fraction part, including the HLC logical counter.
+///
+/// # Examples
+///
+/// ```
+/// use uhlc::{timestamps_from_arrow, timestamps_to_arrow, HLC};
+///
+/// let hlc = HLC::default();
+/// let stamps = vec![hlc.new_timestamp(), hlc.new_timestamp()];
+/// let array = timestamps_to_arrow(&stamps);
+/// let decoded = timestamps_from_arrow(&array).unwrap();
+/// assert_eq!(decoded.len(), 2);
+/// assert_eq!(decoded[0].get_id(), stamps[0].get_id());
+/// ```
+pub fn timestamps_to_arrow(timestamps: &[Timestamp]) -> StructArray {
+    let times = TimestampNanosecondArray::from(
+        timestamps
+            .iter()
+            .map(|ts| ts.get_time().as_nanos() as i64)
+            .collect::<Vec<i64>>(),
+    )
+    .with_timezone(ARROW_TIMEZONE);
+    // The FixedSizeBinaryArray is built from the values buffer directly, since
+    // `try_from_iter()` rejects an empty iterator.
+    let ids = FixedSizeBinaryArray::new(
+        ID::MAX_SIZE as i32,
+        timestamps
+            .iter()
+            .flat_map(|ts| ts.get_id().to_le_bytes())
+            .collect::<Vec<u8>>()
+            .into(),
+        None,
+    );
+    StructArray::new(
+        timestamp_arrow_fields(),
+        vec![Arc::new(times) as ArrayRef, Arc::new(ids) as ArrayRef],
+        None,
+    )
+}
+
+/// Convert an Arrow [`StructArray`] (see [`timestamp_arrow_fields()`] for its expected layout) into [`Timestamp`]s.
+///
+/// An [`ArrowError`] is returned if a column is missing or has an unexpected type,
+/// or if any value is null, negative, after the end of the [`NTP64`] range (2106-02-07T06:28:15Z) or is a zero ID.
+pub fn timestamps_from_arrow(array: &StructArray) -> Result<Vec<Timestamp>, ArrowError> {
+    let times = array
+        .column_by_name(ARROW_TIME_FIELD)
+        .and_then(|c| c.as_any().downcast_ref::<TimestampNanosecondArray>())
+        .ok_or_else(|| {
+            ArrowError::SchemaError(format!(
+                "missing '{ARROW_TIME_FIELD}' column of type timestamp[ns]"
+            ))
+        })?;
+    let ids = array
+        .column_by_name(ARROW_ID_FIELD)
+        .and_then(|c| c.as_any().downcast_ref::<FixedSizeBinaryArray>())
+        .filter(|c| c.value_length() == ID::MAX_SIZE as i32)
+        .ok_or_else(|| {
+            ArrowError::SchemaError(format!(
+                "missing '{ARROW_ID_FIELD}' column of type fixed_size_binary[{}]",
+                ID::MAX_SIZE
+            ))
+        })?;
+    if times.null_count() > 0 || ids.null_count() > 0 {
+        return Err(ArrowError::InvalidArgumentError(
+            "null values are not valid Timestamps".into(),
+        ));
+    }
+
+    times
+        .values()
+        .iter()
+        .zip(ids.iter().flatten())
+        .map(|(nanos, id)| {
+            let nanos = u64::try_from(*nanos).map_err(|_| {
+                ArrowError::InvalidArgumentError(format!("negative time is not valid: {nanos}"))
+            })?;
+            let id = ID::try_from(id)
+                .map_err(|e| ArrowError::InvalidArgumentError(format!("invalid ID: {e}")))?;
+            let time = NTP64::try_from_duration(Duration::from_nanos(nanos)).map_err(|e| {
+                ArrowError::InvalidArgumentError(format!("invalid time {nanos}: {e}"))
+            })?;
+            Ok(Timestamp::new(time, id))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use arrow_array::{Array, ArrayRef, Int64Array, StructArray, TimestampNanosecondArray};
+    use arrow_schema::{DataType, Field};
+    use std::sync::Arc;
+
+    #[test]
+    fn arrow_round_trip() {
+        let hlc = HLC::default();
+        let stamps: Vec<Timestamp> = (0..100).map(|_| hlc.new_timestamp()).collect();
+
+        let array = timestamps_to_arrow(&stamps);
+        assert_eq!(array.len(), stamps.len());
+        assert_eq!(array.fields(), &timestamp_arrow_fields());
+
+        let decoded = timestamps_from_arrow(&array).unwrap();
+        assert_eq!(decoded.len(), stamps.len());
+        for (ts, d) in stamps.iter().zip(decoded.iter()) {
+            assert_eq!(ts.get_id(), d.get_id());
+            assert_eq!(ts.get_time().as_nanos(), d.get_time().as_nanos());
+        }
+
+        assert!(timestamps_from_arrow(&timestamps_to_arrow(&[]))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn arrow_invalid_layout() {
+        let array = StructArray::from(vec![(
+            Arc::new(Field::new("time", DataType::Int64, false)),
+            Arc::new(Int64Array::from(vec![1i64])) as ArrayRef,
+        )]);
+        assert!(timestamps_from_arrow(&array).is_err());
+    }
+
+    #[test]
+    fn arrow_out_of_range() {
+        // a valid Arrow time (year 2262) after the end of the NTP64 range
+        let stamps = [HLC::default().new_timestamp()];
+        let array = timestamps_to_arrow(&stamps);
+        let times = TimestampNanosecondArray::from(vec![i64::MAX]).with_timezone("UTC");
+        let array = StructArray::new(
+            timestamp_arrow_fields(),
+            vec![Arc::new(times) as ArrayRef, array.column(1).clone()],
+            None,
+        );
+        assert!(matches!(
+            timestamps_from_arrow(&array),
+            Err(arrow_schema::ArrowError::InvalidArgumentError(_))
+        ));
+    }
+}
@@ -0,0 +1,154 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::NTP64;
+use core::time::Duration;
+
+/// The result of [`calibrate()`]: how a clock `b` deviates from a reference clock `a`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CalibrationReport {
+    /// The number of samples the report is computed from.
+    pub samples: usize,
+    /// The mean offset in nanoseconds of `b` relatively to `a` over the calibration (positive if `b` is ahead).
+    pub offset_ns: i64,
+    /// The drift rate of `b` relatively to `a`, in parts per million (positive if `b` runs faster),
+    /// i.e. the number of microseconds `b` gains on `a` per second.
+    pub drift_ppm: f64,
+    /// The standard deviation in nanoseconds of the offsets around the drift line,
+    /// including the time to read the clocks.
+    pub jitter_ns: f64,
+}
+
+// The signed difference `b - a` in nanoseconds
+fn signed_nanos(b: NTP64, a: NTP64) -> f64 {
+    if b >= a {
+        (b - a).as_nanos() as f64
+    } else {
+        -((a - b).as_nanos() as f64)
+    }
+}
+
+/// Compare the clock `b` with the reference clock `a` (e.g. [`crate::system_time_clock()`]),
+/// taking `samples` readings `interval` apart. This allows to quantify how far a clock source
+/// deviates from the system clock before switching an [`crate::HLC`] over to it
+/// (see [`crate::HLCBuilder::with_clock()`]).
+///
+/// Each sample reads `a`, `b` and `a` again, `b` being compared to the middle of the two readings of `a`.
+/// The drift rate is the slope of the least squares line of the offsets over the time of `a`,
+/// thus it's only meaningful with at least 2 samples over a long enough period.
+///
+/// Note that this function blocks the calling thread for `(samples - 1) × interval`.
+///
+/// # Panics
+///
+/// If `samples` is 0.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use uhlc::{calibrate, system_time_clock, NTP64};
+///
+/// fn ahead_clock() -> NTP64 {
+///     system_time_clock() + NTP64::from(Duration::from_millis(250))
+/// }
+///
+/// let report = calibrate(system_time_clock, ahead_clock, 10, Duration::from_millis(1));
+/// assert_eq!(report.samples, 10);
+/// assert!((report.offset_ns - 250_000_000).abs() < 10_000_000);
+/// ```
+pub fn calibrate(
+    a: fn() -> NTP64,
+    b: fn() -> NTP64,
+    samples: usize,
+    interval: Duration,
+) -> CalibrationReport {
+    assert!(samples > 0, "calibrate() requires at least 1 sample");
+    // (time of `a` since the 1st sample in seconds, offset of `b` in nanoseconds)
+    let mut points = Vec::with_capacity(samples);
+    let mut origin = None;
+    for i in 0..samples {
+        if i > 0 {
+            std::thread::sleep(interval);
+        }
+        let before = a();
+        let time_b = b();
+        let after = a();
+        let time_a = if after > before {
+            before + NTP64((after - before).0 / 2)
+        } else {
+            before
+        };
+        let origin = *origin.get_or_insert(time_a);
+        points.push((
+            signed_nanos(time_a, origin) / 1e9,
+            signed_nanos(time_b, time_a),
+        ));
+    }
+
+    let n = samples as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let (sxx, sxy) = points.iter().fold((0.0, 0.0), |(sxx, sxy), (x, y)| {
+        (
+            sxx + (x - mean_x) * (x - mean_x),
+            sxy + (x - mean_x) * (y - mean_y),
+        )
+    });
+    // the slope in nanoseconds per second
+    let slope = if sxx > 0.0 { sxy / sxx } else { 0.0 };
+    let variance = points
+        .iter()
+        .map(|(x, y)| {
+            let residual = y - (mean_y + slope * (x - mean_x));
+            residual * residual
+        })
+        .sum::<f64>()
+        / n;
+    CalibrationReport {
+        samples,
+        offset_ns: mean_y.round() as i64,
+        drift_ppm: slope / 1e3,
+        jitter_ns: variance.sqrt(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use core::sync::atomic::{AtomicU64, Ordering};
+    use core::time::Duration;
+
+    #[test]
+    fn calibration() {
+        // a reference clock ticking 1s at each reading, and a clock 1s ahead and drifting by 1000 ppm
+        static TICKS: AtomicU64 = AtomicU64::new(0);
+        fn reference() -> NTP64 {
+            NTP64::from(Duration::from_secs(
+                TICKS.fetch_add(1, Ordering::SeqCst) + 1,
+            ))
+        }
+        fn drifting() -> NTP64 {
+            let secs = TICKS.load(Ordering::SeqCst) as f64 + 0.5;
+            NTP64::from(Duration::from_secs_f64(secs * 1.001 + 1.0))
+        }
+        let report = calibrate(reference, drifting, 10, Duration::ZERO);
+        assert_eq!(report.samples, 10);
+        assert!((report.drift_ppm - 1000.0).abs() < 0.1);
+        assert!(report.jitter_ns < 10.0);
+        // the mean reference time is 10.5s
+        assert!((report.offset_ns - 1_010_500_000).abs() < 10);
+
+        let report = calibrate(system_time_clock, system_time_clock, 1, Duration::ZERO);
+        assert_eq!(report.drift_ppm, 0.0);
+        assert_eq!(report.jitter_ns, 0.0);
+        assert!(report.offset_ns.abs() < 1_000_000_000);
+    }
+}
@@ -0,0 +1,79 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{Timestamp, ID, NTP64};
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+/// The evidence that a [`Timestamp`] was issued by an [`crate::HLC`],
+/// returned by [`crate::HLC::certify()`].
+///
+/// It records the invariants that were checked at certification time:
+///  * the [`Timestamp`]'s [`ID`] is the `issuer`'s one (i.e. the certifying HLC's one)
+///  * the [`Timestamp`]'s time is not after the `frontier` (i.e. the latest time of the certifying HLC)
+///
+/// It is serializable in order to be stored in audit logs.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct StampCertificate {
+    /// The certified [`Timestamp`].
+    pub timestamp: Timestamp,
+    /// The [`ID`] of the certifying HLC.
+    pub issuer: ID,
+    /// The latest time of the certifying HLC at certification time.
+    pub frontier: NTP64,
+}
+
+impl StampCertificate {
+    /// Check that the invariants recorded in this certificate hold.
+    pub fn verify(&self) -> Result<(), CertifyError> {
+        if *self.timestamp.get_id() != self.issuer {
+            Err(CertifyError::ForeignId {
+                issuer: self.issuer,
+                found: *self.timestamp.get_id(),
+            })
+        } else if *self.timestamp.get_time() > self.frontier {
+            Err(CertifyError::AfterFrontier {
+                time: *self.timestamp.get_time(),
+                frontier: self.frontier,
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// The error returned by [`crate::HLC::certify()`] when a [`Timestamp`] cannot have been issued by the HLC.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CertifyError {
+    /// The [`Timestamp`] was issued by another HLC.
+    ForeignId { issuer: ID, found: ID },
+    /// The [`Timestamp`]'s time is after the latest time of the HLC.
+    AfterFrontier { time: NTP64, frontier: NTP64 },
+}
+
+impl fmt::Display for CertifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CertifyError::ForeignId { issuer, found } => {
+                write!(f, "Timestamp issued by {found}, not by {issuer}")
+            }
+            CertifyError::AfterFrontier { time, frontier } => {
+                write!(
+                    f,
+                    "Timestamp time {time} is after the HLC frontier {frontier}"
+                )
+            }
+        }
+    }
+}
+
+impl core::error::Error for CertifyError {}
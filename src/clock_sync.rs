@@ -0,0 +1,152 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{Timestamp, UpdateError, HLC, ID, LMASK, NTP64};
+use core::time::Duration;
+use serde::{Deserialize, Serialize};
+
+/// A message gossiping the state of an [`HLC`] to its peers, allowing to detect the members
+/// of a cluster with a skewed clock.
+///
+/// It's created with [`HLC::clock_sync()`] and handled by the peers with [`HLC::apply_clock_sync()`].
+///
+/// # Examples
+///
+/// ```
+/// use uhlc::HLC;
+///
+/// let (hlc1, hlc2) = (HLC::default(), HLC::default());
+/// let msg = hlc1.clock_sync();
+/// let report = hlc2.apply_clock_sync(&msg).unwrap();
+/// assert_eq!(&report.id, hlc1.get_id());
+/// assert!(report.offset_ns.abs() < 1_000_000_000);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ClockSync {
+    /// A new [`Timestamp`] of the sender (bearing its [`ID`]).
+    pub timestamp: Timestamp,
+    /// The time elapsed since the sender's [`HLC`] was built, according to its physical clock.
+    pub uptime: Duration,
+    /// How far the latest time of the sender's [`HLC`] is ahead of its physical clock.
+    pub logical_lead: NTP64,
+    /// The maximum delta of the sender's [`HLC`].
+    pub max_delta: NTP64,
+}
+
+impl ClockSync {
+    /// Returns the [`ID`] of the sender.
+    #[inline]
+    pub fn get_id(&self) -> &ID {
+        self.timestamp.get_id()
+    }
+}
+
+/// The result of [`HLC::apply_clock_sync()`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ClockSyncReport {
+    /// The [`ID`] of the sender.
+    pub id: ID,
+    /// The offset in nanoseconds of the sender's [`Timestamp`] relatively to the current time
+    /// of this [`HLC`] when received (positive if the sender is ahead).
+    ///
+    /// This offset includes the transmission delay of the message.
+    pub offset_ns: i64,
+    /// How far the latest time of the sender's [`HLC`] was ahead of its physical clock.
+    pub logical_lead: NTP64,
+}
+
+impl HLC {
+    /// Returns a [`ClockSync`] message describing the current state of this [`HLC`],
+    /// to be gossiped to its peers.
+    pub fn clock_sync(&self) -> ClockSync {
+        let clock_time = (self.clock)();
+        let timestamp = self.new_timestamp_at(clock_time);
+        let now = NTP64(clock_time.0 & LMASK);
+        let logical_lead = if *timestamp.get_time() > now {
+            *timestamp.get_time() - now
+        } else {
+            NTP64(0)
+        };
+        let uptime = if clock_time > self.started {
+            (clock_time - self.started).to_duration()
+        } else {
+            Duration::ZERO
+        };
+        ClockSync {
+            timestamp,
+            uptime,
+            logical_lead,
+            max_delta: self.delta,
+        }
+    }
+
+    /// Handle a [`ClockSync`] message received from a peer: this [`HLC`] is updated with
+    /// the peer's [`Timestamp`] (see [`HLC::update_with_timestamp()`]), and a report
+    /// on the peer's clock is returned.
+    ///
+    /// If the peer's [`Timestamp`] is rejected (e.g. because its clock is too far ahead),
+    /// an [`UpdateError`] is returned.
+    pub fn apply_clock_sync(&self, msg: &ClockSync) -> Result<ClockSyncReport, UpdateError> {
+        let local = self.current_time();
+        let peer = *msg.timestamp.get_time();
+        let offset_ns = if peer >= local {
+            (peer - local).to_duration().as_nanos() as i64
+        } else {
+            -((local - peer).to_duration().as_nanos() as i64)
+        };
+        self.update_with_timestamp(&msg.timestamp)?;
+        Ok(ClockSyncReport {
+            id: *msg.get_id(),
+            offset_ns,
+            logical_lead: msg.logical_lead,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use core::time::Duration;
+
+    #[test]
+    fn clock_sync() {
+        let ahead = HLCBuilder::new()
+            .with_clock(|| NTP64::from(Duration::from_secs(10)))
+            .build();
+        let behind = HLCBuilder::new()
+            .with_clock(|| NTP64::from(Duration::from_secs(9)))
+            .with_max_delta(Duration::from_secs(2))
+            .build();
+        let msg = ahead.clock_sync();
+        assert_eq!(msg.uptime, Duration::ZERO);
+        assert_eq!(msg.logical_lead, NTP64(0));
+        let json = serde_json::to_string(&msg).unwrap();
+        assert_eq!(serde_json::from_str::<ClockSync>(&json).unwrap(), msg);
+
+        let report = behind.apply_clock_sync(&msg).unwrap();
+        assert_eq!(&report.id, ahead.get_id());
+        assert_eq!((report.offset_ns as f64 / 1e9).round(), 1.0);
+
+        // after the update, the HLC which was behind is logically ahead of its physical clock
+        let msg = behind.clock_sync();
+        assert!(msg.logical_lead.to_duration() > Duration::from_millis(999));
+        let report = ahead.apply_clock_sync(&msg).unwrap();
+        assert!(report.offset_ns >= 0);
+
+        // a clock too far ahead is rejected
+        let strict = HLCBuilder::new()
+            .with_clock(zero_clock)
+            .with_max_delta(Duration::from_secs(1))
+            .build();
+        assert!(strict.apply_clock_sync(&ahead.clock_sync()).is_err());
+    }
+}
@@ -0,0 +1,147 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{Timestamp, HLC, NTP64};
+use core::time::Duration;
+
+#[cfg(feature = "async")]
+use core::future::Future;
+
+impl HLC {
+    /// Returns how long to wait until the physical time of this [`HLC`] passes the time of `timestamp`
+    /// plus `uncertainty` (e.g. the maximum clock offset between the nodes), or [`Duration::ZERO`] if it already did.
+    ///
+    /// The time of `timestamp` includes its logical counter: once passed, all the [`Timestamp`]s
+    /// generated by this [`HLC`] are greater than the time of `timestamp` plus `uncertainty`,
+    /// whatever their logical counters.
+    ///
+    /// Returns [`Duration::MAX`] if the time of `timestamp` plus `uncertainty` is after the end
+    /// of the [`NTP64`] range (e.g. a remote `timestamp` near the end of the range), as it's never passed.
+    pub fn wait_time(&self, timestamp: &Timestamp, uncertainty: Duration) -> Duration {
+        let target = match NTP64::try_from_duration(uncertainty)
+            .ok()
+            .and_then(|uncertainty| timestamp.get_time().0.checked_add(uncertainty.0))
+        {
+            Some(target) => NTP64(target),
+            None => return Duration::MAX,
+        };
+        let now = self.physical_time(self.read_clock());
+        if now > target {
+            Duration::ZERO
+        } else {
+            // +1ns to pass the target, not only reach it
+            (target - now).to_duration() + Duration::from_nanos(1)
+        }
+    }
+
+    /// Block the current thread until the physical time of this [`HLC`] passes the time of `timestamp`
+    /// plus `uncertainty` (see [`HLC::wait_time()`]).
+    ///
+    /// This is the "commit-wait" of Spanner: waiting before acknowledging a write stamped with `timestamp`
+    /// guarantees that any later read on any node whose clock is within `uncertainty` of this one
+    /// is stamped after the write, i.e. sees it.
+    ///
+    /// This never returns if the physical clock of this [`HLC`] doesn't advance (e.g. [`crate::zero_clock()`]).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use uhlc::{system_time_clock, HLCBuilder, NTP64};
+    ///
+    /// let hlc = HLCBuilder::new().with_clock(system_time_clock).build();
+    /// let write = hlc.new_timestamp();
+    /// hlc.wait_until(&write, Duration::from_millis(5));
+    /// let read = hlc.new_timestamp();
+    /// assert!(*read.get_time() > *write.get_time() + NTP64::from(Duration::from_millis(5)));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn wait_until(&self, timestamp: &Timestamp, uncertainty: Duration) {
+        loop {
+            let wait = self.wait_time(timestamp, uncertainty);
+            if wait.is_zero() {
+                return;
+            }
+            std::thread::sleep(wait);
+        }
+    }
+
+    /// Asynchronously wait until the physical time of this [`HLC`] passes the time of `timestamp`
+    /// plus `uncertainty`, as [`HLC::wait_until()`] does, calling `sleep` (e.g. `tokio::time::sleep`)
+    /// to wait without blocking the runtime.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use uhlc::{system_time_clock, HLCBuilder};
+    ///
+    /// let hlc = HLCBuilder::new().with_clock(system_time_clock).build();
+    /// let write = hlc.new_timestamp();
+    /// async_std::task::block_on(hlc.wait_until_async(
+    ///     &write,
+    ///     Duration::from_millis(5),
+    ///     async_std::task::sleep,
+    /// ));
+    /// assert_eq!(hlc.wait_time(&write, Duration::from_millis(5)), Duration::ZERO);
+    /// ```
+    #[cfg(feature = "async")]
+    pub async fn wait_until_async<F, Fut>(
+        &self,
+        timestamp: &Timestamp,
+        uncertainty: Duration,
+        mut sleep: F,
+    ) where
+        F: FnMut(Duration) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        loop {
+            let wait = self.wait_time(timestamp, uncertainty);
+            if wait.is_zero() {
+                return;
+            }
+            sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use crate::*;
+    use core::time::Duration;
+    use std::time::Instant;
+
+    #[test]
+    fn commit_wait() {
+        let hlc = HLCBuilder::new().with_clock(system_time_clock).build();
+        let uncertainty = Duration::from_millis(20);
+        let write = hlc.new_timestamp();
+        assert!(hlc.wait_time(&write, uncertainty) > Duration::ZERO);
+        assert!(hlc.wait_time(&write, uncertainty) <= uncertainty + Duration::from_nanos(1));
+
+        let start = Instant::now();
+        hlc.wait_until(&write, uncertainty);
+        assert!(start.elapsed() >= Duration::from_millis(10));
+        assert_eq!(hlc.wait_time(&write, uncertainty), Duration::ZERO);
+        // whatever the counter of the awaited stamp
+        let read = hlc.new_timestamp();
+        assert!(*read.get_time() > *write.get_time() + NTP64::from(uncertainty));
+
+        // a stamp with the maximum counter
+        let stamp = Timestamp::new(NTP64(read.get_time().0 | CMASK), *read.get_id());
+        hlc.wait_until(&stamp, Duration::ZERO);
+        assert!(hlc.new_timestamp() > stamp);
+
+        // a target beyond the end of the NTP64 range is never passed
+        let remote = Timestamp::new(NTP64(u64::MAX - 1), *read.get_id());
+        assert_eq!(hlc.wait_time(&remote, uncertainty), Duration::MAX);
+        assert_eq!(hlc.wait_time(&write, Duration::MAX), Duration::MAX);
+    }
+}
@@ -0,0 +1,273 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{ClockKind, ConfigError, HLCBuilder, Limit, CSIZE, ID, NTP64};
+use core::time::Duration;
+use serde::{Deserialize, Serialize};
+
+/// The exact configuration of an [`crate::HLC`], returned by [`HLCBuilder::to_config()`]
+/// and re-applied with [`HLCBuilder::with_config()`], e.g. by orchestration tooling across
+/// the restarts of a node.
+///
+/// Only a custom physical clock (see [`HLCBuilder::with_clock()`]) and a recorder (with the `record` feature)
+/// can't be captured.
+/// The optional settings default to `None` when deserialized.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use uhlc::{ClockKind, HLCBuilder, HLCConfig};
+///
+/// let config = HLCBuilder::new()
+///     .with_max_delta(Duration::from_secs(1))
+///     .with_clock_kind(ClockKind::Monotonic)
+///     .to_config();
+/// let json = serde_json::to_string(&config).unwrap();
+///
+/// let restored: HLCConfig = serde_json::from_str(&json).unwrap();
+/// let hlc = HLCBuilder::new().with_config(&restored).unwrap().build();
+/// assert_eq!(hlc.get_id(), &config.id);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct HLCConfig {
+    /// The [`ID`] of the [`crate::HLC`].
+    pub id: ID,
+    /// The maximum delta (see [`HLCBuilder::with_max_delta()`]).
+    pub max_delta: Duration,
+    /// The size of the logical counter in bits (see [`CSIZE`]).
+    pub counter_bits: u8,
+    /// The physical clock, or `None` for the default one or a custom one
+    /// (see [`HLCBuilder::with_clock_kind()`]).
+    pub clock: Option<ClockKind>,
+    /// The maximum number of updates per peer (see [`HLCBuilder::with_update_rate_limit()`]).
+    #[serde(default)]
+    pub update_rate_limit: Option<LimitConfig<u32>>,
+    /// The forward drift budget per peer (see [`HLCBuilder::with_drift_budget()`]).
+    #[serde(default)]
+    pub drift_budget: Option<LimitConfig<Duration>>,
+    /// The maximum number of rejected updates before a peer is quarantined (see [`HLCBuilder::with_quarantine()`]).
+    #[serde(default)]
+    pub quarantine: Option<LimitConfig<u32>>,
+    /// The granularity of the physical time (see [`HLCBuilder::with_time_quantization()`]).
+    #[serde(default)]
+    pub time_quantization: Option<Duration>,
+    /// The bound of the jitter of the physical time (see [`HLCBuilder::with_time_jitter()`]).
+    #[serde(default)]
+    pub time_jitter: Option<Duration>,
+    /// The maximum lead over the physical clock (see [`HLCBuilder::with_max_logical_lead()`]).
+    #[serde(default)]
+    pub max_logical_lead: Option<Duration>,
+}
+
+/// A limit of `max` per `period` of the physical clock, in an [`HLCConfig`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct LimitConfig<T> {
+    /// The maximum per period.
+    pub max: T,
+    /// The period.
+    pub period: Duration,
+}
+
+impl HLCBuilder {
+    ///
+    /// Returns the configuration of the HLC to be created.
+    ///
+    /// Its `clock` is only set if configured with [`Self::with_clock_kind()`] (or from the environment):
+    /// a custom clock configured with [`Self::with_clock()`] can't be captured.
+    ///
+    pub fn to_config(&self) -> HLCConfig {
+        let guard = &self.hlc.guard;
+        HLCConfig {
+            id: self.hlc.id,
+            max_delta: self.hlc.delta.to_duration(),
+            counter_bits: CSIZE,
+            clock: self.clock_kind,
+            update_rate_limit: guard.rate_limit.map(|limit| LimitConfig {
+                max: limit.max,
+                period: limit.period.to_duration(),
+            }),
+            drift_budget: guard.drift_budget.map(|limit| LimitConfig {
+                max: limit.max.to_duration(),
+                period: limit.period.to_duration(),
+            }),
+            quarantine: guard.quarantine.map(|limit| LimitConfig {
+                max: limit.max,
+                period: limit.period.to_duration(),
+            }),
+            time_quantization: self.hlc.quantum.map(NTP64::to_duration),
+            time_jitter: self.hlc.jitter.map(NTP64::to_duration),
+            max_logical_lead: self.hlc.max_lead.map(NTP64::to_duration),
+        }
+    }
+
+    ///
+    /// Apply an [`HLCConfig`] to the HLC to be created. If its `clock` is `None`, the clock is unchanged,
+    /// while the other optional settings that are `None` are unset.
+    ///
+    /// Returns [`ConfigError::CounterBits`] if its `counter_bits` differs from [`CSIZE`] in this build,
+    /// or [`ConfigError::OutOfRange`] if one of its durations exceeds the range of a [`NTP64`].
+    ///
+    pub fn with_config(mut self, config: &HLCConfig) -> Result<HLCBuilder, ConfigError> {
+        fn ntp64(duration: Duration, setting: &'static str) -> Result<NTP64, ConfigError> {
+            NTP64::try_from_duration(duration).map_err(|_| ConfigError::OutOfRange(setting))
+        }
+        fn limit<T: Copy>(
+            config: Option<LimitConfig<T>>,
+            setting: &'static str,
+        ) -> Result<Option<Limit<T>>, ConfigError> {
+            config
+                .map(|limit| {
+                    Ok(Limit {
+                        max: limit.max,
+                        period: ntp64(limit.period, setting)?,
+                    })
+                })
+                .transpose()
+        }
+
+        if config.counter_bits != CSIZE {
+            return Err(ConfigError::CounterBits(config.counter_bits));
+        }
+        let delta = ntp64(config.max_delta, "maximum delta")?;
+        let rate_limit = limit(config.update_rate_limit, "update rate limit")?;
+        let drift_budget = limit(config.drift_budget, "drift budget")?
+            .map(|limit| {
+                Ok(Limit {
+                    max: ntp64(limit.max, "drift budget")?,
+                    period: limit.period,
+                })
+            })
+            .transpose()?;
+        let quarantine = limit(config.quarantine, "quarantine")?;
+        let quantum = config
+            .time_quantization
+            .map(|q| ntp64(q, "time quantization"))
+            .transpose()?;
+        let jitter = config
+            .time_jitter
+            .map(|j| ntp64(j, "time jitter"))
+            .transpose()?;
+        let max_lead = config
+            .max_logical_lead
+            .map(|l| ntp64(l, "maximum logical lead"))
+            .transpose()?;
+
+        self = self.with_id(config.id);
+        self.hlc.delta = delta;
+        if let Some(kind) = config.clock {
+            self = self.with_clock_kind(kind);
+        }
+        let guard = self.guard_mut();
+        guard.rate_limit = rate_limit;
+        guard.drift_budget = drift_budget;
+        guard.quarantine = quarantine;
+        self.hlc.quantum = quantum;
+        self.hlc.jitter = jitter;
+        self.hlc.max_lead = max_lead;
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use core::time::Duration;
+
+    #[test]
+    fn config_roundtrip() {
+        let builder = HLCBuilder::new()
+            .with_max_delta(Duration::from_millis(250))
+            .with_clock_kind(ClockKind::System);
+        let config = builder.to_config();
+        assert_eq!(config.max_delta, Duration::from_millis(250));
+        assert_eq!(config.counter_bits, CSIZE);
+        assert_eq!(config.clock, Some(ClockKind::System));
+        let json = serde_json::to_string(&config).unwrap();
+        assert!(json.contains(r#""clock":"system""#));
+        let restored: HLCConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, config);
+        assert_eq!(
+            HLCBuilder::new()
+                .with_config(&restored)
+                .unwrap()
+                .to_config(),
+            config
+        );
+
+        // a custom clock isn't captured
+        assert_eq!(builder.with_clock(zero_clock).to_config().clock, None);
+        let other_bits = HLCConfig {
+            counter_bits: CSIZE + 1,
+            ..config
+        };
+        assert_eq!(
+            HLCBuilder::new().with_config(&other_bits).err(),
+            Some(ConfigError::CounterBits(CSIZE + 1))
+        );
+    }
+
+    #[test]
+    fn config_all_settings() {
+        let builder = HLCBuilder::new()
+            .with_update_rate_limit(10, Duration::from_secs(1))
+            .with_drift_budget(Duration::from_millis(100), Duration::from_secs(60))
+            .with_quarantine(3, Duration::from_secs(600))
+            .with_time_quantization(Duration::from_millis(10))
+            .with_time_jitter(Duration::from_millis(5))
+            .with_max_logical_lead(Duration::from_secs(2));
+        let config = builder.to_config();
+        assert_eq!(
+            config.drift_budget,
+            Some(LimitConfig {
+                max: Duration::from_millis(100),
+                period: Duration::from_secs(60)
+            })
+        );
+        assert_eq!(config.max_logical_lead, Some(Duration::from_secs(2)));
+        let json = serde_json::to_string(&config).unwrap();
+        let restored: HLCConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            HLCBuilder::new()
+                .with_config(&restored)
+                .unwrap()
+                .to_config(),
+            config
+        );
+
+        // the settings missing from a serialized configuration are unset
+        let mut json = serde_json::to_value(config).unwrap();
+        json.as_object_mut()
+            .unwrap()
+            .retain(|key, _| ["id", "max_delta", "counter_bits", "clock"].contains(&key.as_str()));
+        let restored: HLCConfig = serde_json::from_value(json).unwrap();
+        let config = builder.with_config(&restored).unwrap().to_config();
+        assert_eq!(config.update_rate_limit, None);
+        assert_eq!(config.time_jitter, None);
+
+        // a duration beyond the NTP64 range is an error, not a panic
+        let huge = HLCConfig {
+            max_delta: Duration::from_secs(1 << 32),
+            ..config
+        };
+        assert_eq!(
+            HLCBuilder::new().with_config(&huge).err(),
+            Some(ConfigError::OutOfRange("maximum delta"))
+        );
+        let huge = HLCConfig {
+            drift_budget: Some(LimitConfig {
+                max: Duration::from_secs(1 << 32),
+                period: Duration::from_secs(1),
+            }),
+            ..config
+        };
+        assert!(HLCBuilder::new().with_config(&huge).is_err());
+    }
+}
@@ -0,0 +1,116 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{Timestamp, HLC, NTP64};
+use core::time::Duration;
+use std::time::Instant;
+
+// How far an Instant deadline is set when its timeout overflows the Instant: as tokio's far future, ~30 years
+const FAR_FUTURE: Duration = Duration::from_secs(86400 * 365 * 30);
+
+impl HLC {
+    /// Returns matching deadlines `timeout` after now: a [`Timestamp`] bearing the [`crate::ID`] of this [`HLC`]
+    /// and an [`Instant`], both read at once.
+    ///
+    /// The [`Timestamp`] is a deadline to exchange with the peers, that can be compared with their [`Timestamp`]s,
+    /// while the [`Instant`] is a deadline for the local timers (e.g. `tokio::time::sleep_until()`).
+    /// Note that the returned [`Timestamp`] is not issued by this [`HLC`].
+    ///
+    /// The deadlines saturate instead of overflowing: the [`Timestamp`] to the end of the [`NTP64`] range,
+    /// and the [`Instant`] to ~30 years from now.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use uhlc::HLC;
+    ///
+    /// let hlc = HLC::default();
+    /// let (deadline_ts, deadline) = hlc.deadline_after(Duration::from_secs(1));
+    /// let ts = hlc.new_timestamp();
+    /// assert!(ts < deadline_ts);
+    /// assert!(!hlc.is_past_deadline(&ts, deadline));
+    /// ```
+    pub fn deadline_after(&self, timeout: Duration) -> (Timestamp, Instant) {
+        let instant = Instant::now();
+        let now = self.read_clock();
+        let timeout_ntp64 = NTP64::try_from_duration(timeout).unwrap_or(NTP64(u64::MAX));
+        (
+            Timestamp::new(NTP64(now.0.saturating_add(timeout_ntp64.0)), *self.get_id()),
+            instant
+                .checked_add(timeout)
+                .unwrap_or_else(|| instant + FAR_FUTURE),
+        )
+    }
+
+    /// Returns the time of the physical clock of this [`HLC`] at `instant` (in the past or in the future),
+    /// from a reading of the clock and of [`Instant::now()`], saturated to the [`NTP64`] range.
+    pub fn time_at_instant(&self, instant: Instant) -> NTP64 {
+        let now_instant = Instant::now();
+        let now = self.read_clock();
+        let saturating = |duration| NTP64::try_from_duration(duration).unwrap_or(NTP64(u64::MAX));
+        if instant >= now_instant {
+            NTP64(now.0.saturating_add(saturating(instant - now_instant).0))
+        } else {
+            NTP64(now.0.saturating_sub(saturating(now_instant - instant).0))
+        }
+    }
+
+    /// Returns `true` if `timestamp` is at or after the [`Instant`]-based `deadline`,
+    /// according to the physical clock of this [`HLC`] (see [`HLC::time_at_instant()`]).
+    pub fn is_past_deadline(&self, timestamp: &Timestamp, deadline: Instant) -> bool {
+        *timestamp.get_time() >= self.time_at_instant(deadline)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use core::time::Duration;
+    use std::time::Instant;
+
+    #[test]
+    fn deadlines() {
+        let hlc = HLCBuilder::new().with_clock(system_time_clock).build();
+        let timeout = Duration::from_secs(10);
+        let before = hlc.new_timestamp();
+        let (deadline_ts, deadline) = hlc.deadline_after(timeout);
+        assert_eq!(deadline_ts.get_id(), hlc.get_id());
+        let after = hlc.new_timestamp();
+        assert!(before < deadline_ts && after < deadline_ts);
+        assert!(deadline > Instant::now());
+        assert!(!hlc.is_past_deadline(&after, deadline));
+
+        // the Timestamp and Instant deadlines match, within the time of the readings
+        let margin = NTP64::from(Duration::from_millis(100));
+        let time = hlc.time_at_instant(deadline);
+        let diff = if time > *deadline_ts.get_time() {
+            time - *deadline_ts.get_time()
+        } else {
+            *deadline_ts.get_time() - time
+        };
+        assert!(diff < margin);
+        let late = Timestamp::new(*deadline_ts.get_time() + margin, ID::rand());
+        assert!(hlc.is_past_deadline(&late, deadline));
+
+        // an Instant in the past
+        let past = hlc.time_at_instant(Instant::now() - Duration::from_secs(1));
+        assert!(past < *after.get_time());
+
+        // the deadlines saturate
+        let (deadline_ts, deadline) = hlc.deadline_after(Duration::MAX);
+        assert_eq!(*deadline_ts.get_time(), NTP64(u64::MAX));
+        assert!(deadline > Instant::now() + Duration::from_secs(86400 * 365));
+        let late = HLCBuilder::new().with_clock(|| NTP64(u64::MAX - 1)).build();
+        let (deadline_ts, deadline) = late.deadline_after(timeout);
+        assert_eq!(*deadline_ts.get_time(), NTP64(u64::MAX));
+        assert_eq!(late.time_at_instant(deadline), NTP64(u64::MAX));
+    }
+}
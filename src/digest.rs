@@ -0,0 +1,227 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{hash::fnv1a_64, Timestamp, KEY_SIZE, LMASK, NTP64};
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::convert::TryFrom;
+use core::ops::Range;
+use core::time::Duration;
+use serde::{Deserialize, Serialize};
+
+/// The digest of the entries of a bucket.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BucketDigest {
+    /// The number of entries in the bucket.
+    pub count: u64,
+    /// The XOR of the hashes of the entries in the bucket.
+    pub hash: u64,
+}
+
+/// A summary of a set of `(Timestamp, payload hash)` entries, split into time buckets,
+/// allowing 2 peers to find the time windows where their sets diverge (e.g. for anti-entropy).
+///
+/// The entries are bucketed according to their [`Timestamp`]'s time without its counter part,
+/// so both peers bucket identically whatever the counter values.
+/// Each bucket's digest is order-independent: entries can be inserted (and removed) incrementally in any order.
+///
+/// The hash of an entry is the [FNV-1a](http://www.isthe.com/chongo/tech/comp/fnv/) 64 bits hash of
+/// [`Timestamp::to_key()`] followed by the payload hash as little endian.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use uhlc::{RangeDigest, HLC};
+///
+/// let hlc = HLC::default();
+/// let (ts1, ts2) = (hlc.new_timestamp(), hlc.new_timestamp());
+///
+/// let mut local = RangeDigest::new(Duration::from_secs(60));
+/// local.insert(&ts1, 0x1234);
+/// local.insert(&ts2, 0x5678);
+///
+/// let mut remote = RangeDigest::new(Duration::from_secs(60));
+/// remote.insert(&ts2, 0x5678);
+/// remote.insert(&ts1, 0x1234);
+/// assert_eq!(local.diff(&remote), Some(vec![]));
+///
+/// remote.remove(&ts2, 0x5678);
+/// let divergent = local.diff(&remote).unwrap();
+/// assert_eq!(divergent.len(), 1);
+/// assert!(divergent[0].contains(ts2.get_time()));
+/// ```
+///
+/// A deserialized [`RangeDigest`] (e.g. received from a remote peer) is checked to have a valid bucket width.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "RawRangeDigest")]
+pub struct RangeDigest {
+    width: NTP64,
+    buckets: BTreeMap<NTP64, BucketDigest>,
+}
+
+// A RangeDigest as deserialized, before checking its bucket width
+#[derive(Deserialize)]
+struct RawRangeDigest {
+    width: NTP64,
+    buckets: BTreeMap<NTP64, BucketDigest>,
+}
+
+impl TryFrom<RawRangeDigest> for RangeDigest {
+    type Error = &'static str;
+
+    fn try_from(raw: RawRangeDigest) -> Result<Self, Self::Error> {
+        if raw.width.0 & LMASK == 0 || raw.width.0 & !LMASK != 0 {
+            return Err("invalid RangeDigest bucket width");
+        }
+        Ok(RangeDigest {
+            width: raw.width,
+            buckets: raw.buckets,
+        })
+    }
+}
+
+impl RangeDigest {
+    /// Create an empty [`RangeDigest`] with buckets of `width`.
+    ///
+    /// # Panics
+    ///
+    /// If `width` is lower than the resolution of the [`Timestamp`]s without their counter part.
+    pub fn new(width: Duration) -> RangeDigest {
+        let width = NTP64::from(width);
+        assert!(
+            width.0 & LMASK != 0,
+            "RangeDigest's bucket width is too small"
+        );
+        RangeDigest {
+            width: NTP64(width.0 & LMASK),
+            buckets: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the width of the buckets.
+    #[inline]
+    pub fn get_width(&self) -> NTP64 {
+        self.width
+    }
+
+    /// Returns the time range of the bucket containing `time`.
+    pub fn bucket_of(&self, time: &NTP64) -> Range<NTP64> {
+        let start = NTP64((time.0 & LMASK) / self.width.0 * self.width.0);
+        start..NTP64(start.0.saturating_add(self.width.0))
+    }
+
+    /// Add an entry with `payload_hash` at `timestamp`.
+    pub fn insert(&mut self, timestamp: &Timestamp, payload_hash: u64) {
+        let start = self.bucket_of(timestamp.get_time()).start;
+        let bucket = self.buckets.entry(start).or_default();
+        bucket.count += 1;
+        bucket.hash ^= entry_hash(timestamp, payload_hash);
+    }
+
+    /// Remove an entry with `payload_hash` at `timestamp`, that was previously inserted.
+    pub fn remove(&mut self, timestamp: &Timestamp, payload_hash: u64) {
+        let start = self.bucket_of(timestamp.get_time()).start;
+        if let Some(bucket) = self.buckets.get_mut(&start) {
+            bucket.count = bucket.count.saturating_sub(1);
+            bucket.hash ^= entry_hash(timestamp, payload_hash);
+            if bucket.count == 0 && bucket.hash == 0 {
+                self.buckets.remove(&start);
+            }
+        }
+    }
+
+    /// Returns an iterator over the non-empty buckets, with their time range and digest, in time order.
+    pub fn buckets(&self) -> impl Iterator<Item = (Range<NTP64>, &BucketDigest)> {
+        self.buckets
+            .iter()
+            .map(move |(start, digest)| (self.bucket_of(start), digest))
+    }
+
+    /// Returns the time ranges of the buckets whose digest differ between this [`RangeDigest`] and `other`,
+    /// in time order, or `None` if the 2 [`RangeDigest`]s have different bucket widths.
+    pub fn diff(&self, other: &RangeDigest) -> Option<Vec<Range<NTP64>>> {
+        if self.width != other.width {
+            return None;
+        }
+        let empty = BucketDigest::default();
+        let mut starts: Vec<NTP64> = self
+            .buckets
+            .keys()
+            .chain(other.buckets.keys())
+            .copied()
+            .collect();
+        starts.sort_unstable();
+        starts.dedup();
+        let divergent = starts
+            .into_iter()
+            .filter(|start| {
+                self.buckets.get(start).unwrap_or(&empty)
+                    != other.buckets.get(start).unwrap_or(&empty)
+            })
+            .map(|start| self.bucket_of(&start))
+            .collect();
+        Some(divergent)
+    }
+}
+
+fn entry_hash(timestamp: &Timestamp, payload_hash: u64) -> u64 {
+    let mut bytes = [0u8; KEY_SIZE + 8];
+    bytes[..KEY_SIZE].copy_from_slice(&timestamp.to_key());
+    bytes[KEY_SIZE..].copy_from_slice(&payload_hash.to_le_bytes());
+    fnv1a_64(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use core::convert::TryFrom;
+    use core::time::Duration;
+
+    #[test]
+    fn range_digest() {
+        let id = ID::try_from([0x01]).unwrap();
+        let ts = |secs: u64, counter: u64| {
+            Timestamp::new(
+                NTP64(NTP64::from(Duration::from_secs(secs)).0 + counter),
+                id,
+            )
+        };
+        let mut a = RangeDigest::new(Duration::from_secs(10));
+        let mut b = RangeDigest::new(Duration::from_secs(10));
+        for secs in [1, 5, 12, 25] {
+            a.insert(&ts(secs, 0), secs);
+            b.insert(&ts(secs, 0), secs);
+        }
+        assert_eq!(a.diff(&b), Some(vec![]));
+        assert_eq!(a.buckets().count(), 3);
+
+        // the counter doesn't change the bucket, but changes the digest
+        b.remove(&ts(12, 0), 12);
+        b.insert(&ts(12, 1), 12);
+        let diff = a.diff(&b).unwrap();
+        assert_eq!(diff, [a.bucket_of(ts(12, 0).get_time())]);
+        assert!(diff[0].contains(ts(19, 0).get_time()));
+
+        // an entry only in b
+        b.insert(&ts(45, 0), 45);
+        assert_eq!(a.diff(&b).unwrap().len(), 2);
+        assert_eq!(b.diff(&a), a.diff(&b));
+
+        let json = serde_json::to_string(&b).unwrap();
+        assert_eq!(serde_json::from_str::<RangeDigest>(&json).unwrap(), b);
+
+        // a remote digest with another width can't be compared, nor one with an invalid width be deserialized
+        let other = RangeDigest::new(Duration::from_secs(60));
+        assert_eq!(a.diff(&other), None);
+        assert!(serde_json::from_str::<RangeDigest>(r#"{"width":0,"buckets":{}}"#).is_err());
+        assert!(serde_json::from_str::<RangeDigest>(r#"{"width":1,"buckets":{}}"#).is_err());
+    }
+}
@@ -0,0 +1,139 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{DecodeError, Timestamp, KEY_SIZE};
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+/// The size in bytes of a [`DomainTimestamp`] key (see [`DomainTimestamp::to_key()`]).
+pub const DOMAIN_KEY_SIZE: usize = 2 + KEY_SIZE;
+
+/// A [`Timestamp`] tagged with the logical clock domain (e.g. a tenant) it belongs to,
+/// allowing to multiplex several clock domains over a same transport.
+///
+/// The domain comes first in the ordering and in the serialization: all the [`DomainTimestamp`]s
+/// of a domain are lower than the ones of the next domain.
+///
+/// # Examples
+///
+/// ```
+/// use uhlc::{DomainTimestamp, Timestamp, HLC};
+///
+/// let hlc = HLC::default();
+/// let (ts1, ts2) = (hlc.new_timestamp(), hlc.new_timestamp());
+/// let (d1, d2) = (DomainTimestamp::new(1, ts2), DomainTimestamp::new(2, ts1));
+/// assert!(d1 < d2);
+/// assert_eq!(Timestamp::from(d1), ts2);
+/// assert_eq!(d2.to_string(), format!("2:{ts1}"));
+/// ```
+#[derive(Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DomainTimestamp {
+    domain: u16,
+    timestamp: Timestamp,
+}
+
+impl DomainTimestamp {
+    /// Create a [`DomainTimestamp`] tagging `timestamp` with `domain`.
+    #[inline]
+    pub const fn new(domain: u16, timestamp: Timestamp) -> DomainTimestamp {
+        DomainTimestamp { domain, timestamp }
+    }
+
+    /// Returns the domain tag.
+    #[inline]
+    pub const fn get_domain(&self) -> u16 {
+        self.domain
+    }
+
+    /// Returns the [`Timestamp`].
+    #[inline]
+    pub const fn get_timestamp(&self) -> &Timestamp {
+        &self.timestamp
+    }
+
+    /// Encode this [`DomainTimestamp`] as a key for byte-ordered storages: the domain as big endian (2 bytes),
+    /// followed by the [`Timestamp`]'s key (see [`Timestamp::to_key()`]).
+    /// Comparing 2 keys byte per byte gives the same ordering than comparing the 2 [`DomainTimestamp`]s.
+    pub fn to_key(&self) -> [u8; DOMAIN_KEY_SIZE] {
+        let mut key = [0u8; DOMAIN_KEY_SIZE];
+        key[..2].copy_from_slice(&self.domain.to_be_bytes());
+        key[2..].copy_from_slice(&self.timestamp.to_key());
+        key
+    }
+
+    /// Decode a [`DomainTimestamp`] from a key encoded with [`DomainTimestamp::to_key()`]
+    /// (or with a truncated [`Timestamp`]'s key, see [`Timestamp::to_truncated_key()`]).
+    pub fn from_key(key: &[u8]) -> Result<DomainTimestamp, DecodeError> {
+        if key.len() < 2 {
+            return Err(DecodeError::InvalidLength(key.len()));
+        }
+        let (domain, timestamp) = key.split_at(2);
+        let timestamp = Timestamp::from_key(timestamp).map_err(|e| match e {
+            DecodeError::InvalidLength(len) => DecodeError::InvalidLength(len + 2),
+            e => e,
+        })?;
+        Ok(DomainTimestamp::new(
+            u16::from_be_bytes([domain[0], domain[1]]),
+            timestamp,
+        ))
+    }
+}
+
+impl From<DomainTimestamp> for Timestamp {
+    fn from(ts: DomainTimestamp) -> Self {
+        ts.timestamp
+    }
+}
+
+impl fmt::Display for DomainTimestamp {
+    /// Formats as `"<domain>:<timestamp>"`, the [`Timestamp`] honoring the formatting flags
+    /// (see [`Timestamp`]'s [`fmt::Display`]).
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:", self.domain)?;
+        fmt::Display::fmt(&self.timestamp, f)
+    }
+}
+
+impl fmt::Debug for DomainTimestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{:?}", self.domain, self.timestamp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn domain_timestamp() {
+        let hlc = HLC::default();
+        let (ts1, ts2) = (hlc.new_timestamp(), hlc.new_timestamp());
+        let mut stamps = [
+            DomainTimestamp::new(2, ts1),
+            DomainTimestamp::new(1, ts2),
+            DomainTimestamp::new(1, ts1),
+        ];
+        stamps.sort();
+        assert_eq!(stamps[0], DomainTimestamp::new(1, ts1));
+        assert_eq!(stamps[2].get_domain(), 2);
+        assert!(stamps.windows(2).all(|w| w[0].to_key() < w[1].to_key()));
+
+        let d = stamps[1];
+        assert_eq!(DomainTimestamp::from_key(&d.to_key()), Ok(d));
+        assert_eq!(
+            DomainTimestamp::from_key(&d.to_key()[..9]),
+            Err(DecodeError::InvalidLength(9))
+        );
+        let json = serde_json::to_string(&d).unwrap();
+        assert!(json.starts_with(r#"{"domain":1,"timestamp":"#));
+        assert_eq!(serde_json::from_str::<DomainTimestamp>(&json).unwrap(), d);
+    }
+}
@@ -0,0 +1,244 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{monotonic_clock, system_time_clock, HLCBuilder, CSIZE, ID, NTP64};
+use core::{fmt, time::Duration};
+use serde::{Deserialize, Serialize};
+use std::env::{self, VarError};
+use std::format;
+use std::string::{String, ToString};
+
+/// The physical clocks that can be selected with the `UHLC_CLOCK` environment variable.
+///
+/// It's serialized as `"system"` or `"monotonic"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[serde(rename_all = "lowercase")]
+pub enum ClockKind {
+    /// `"system"`: [`system_time_clock()`].
+    System,
+    /// `"monotonic"`: [`monotonic_clock()`].
+    Monotonic,
+}
+
+impl ClockKind {
+    /// Returns the clock function.
+    pub fn clock(&self) -> fn() -> NTP64 {
+        match self {
+            ClockKind::System => system_time_clock,
+            ClockKind::Monotonic => monotonic_clock,
+        }
+    }
+}
+
+/// The configuration of an [`crate::HLC`] read from the environment variables:
+///  * `UHLC_ID`: the [`ID`] in hexadecimal (see [`ID::from_str()`](core::str::FromStr::from_str));
+///  * `UHLC_MAX_DELTA_MS`: the maximum delta in milliseconds, within the range of a [`NTP64`] (~136 years);
+///  * `UHLC_COUNTER_BITS`: the expected size of the logical counter in bits. It's fixed to [`CSIZE`]
+///    at compile time, so any other value is an error (e.g. to detect a deployment expecting another build);
+///  * `UHLC_CLOCK`: the physical clock, `system` or `monotonic` (see [`ClockKind`]).
+///
+/// The unset variables are `None`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct EnvConfig {
+    /// The value of `UHLC_ID`.
+    pub id: Option<ID>,
+    /// The value of `UHLC_MAX_DELTA_MS`.
+    pub max_delta: Option<Duration>,
+    /// The value of `UHLC_COUNTER_BITS`.
+    pub counter_bits: Option<u8>,
+    /// The value of `UHLC_CLOCK`.
+    pub clock: Option<ClockKind>,
+}
+
+impl EnvConfig {
+    /// Read the configuration from the environment variables.
+    ///
+    /// Returns an [`EnvError`] if a variable is set with an invalid value.
+    pub fn from_env() -> Result<EnvConfig, EnvError> {
+        Self::from_vars(|name| match env::var(name) {
+            Ok(value) => Ok(Some(value)),
+            Err(VarError::NotPresent) => Ok(None),
+            Err(e) => Err(e.to_string()),
+        })
+    }
+
+    // Read the configuration with `get`, returning the value of a variable if it's set
+    fn from_vars<F>(get: F) -> Result<EnvConfig, EnvError>
+    where
+        F: Fn(&'static str) -> Result<Option<String>, String>,
+    {
+        Ok(EnvConfig {
+            id: parse_var(&get, "UHLC_ID", |s| s.parse::<ID>().map_err(|e| e.cause))?,
+            max_delta: parse_var(&get, "UHLC_MAX_DELTA_MS", |s| {
+                let delta = Duration::from_millis(s.parse::<u64>().map_err(|e| e.to_string())?);
+                match NTP64::try_from_duration(delta) {
+                    Ok(_) => Ok(delta),
+                    Err(e) => Err(format!("the maximum delta {s}ms is out of range: {e}")),
+                }
+            })?,
+            counter_bits: parse_var(&get, "UHLC_COUNTER_BITS", |s| {
+                match s.parse::<u8>().map_err(|e| e.to_string())? {
+                    CSIZE => Ok(CSIZE),
+                    bits => Err(format!(
+                        "the counter size is fixed to {CSIZE} bits in this build, not {bits}"
+                    )),
+                }
+            })?,
+            clock: parse_var(&get, "UHLC_CLOCK", |s| match s {
+                "system" => Ok(ClockKind::System),
+                "monotonic" => Ok(ClockKind::Monotonic),
+                _ => Err(format!("unknown clock '{s}' (must be system or monotonic)")),
+            })?,
+        })
+    }
+}
+
+// Parse the value of `var` returned by `get` with `parse`, if it's set
+fn parse_var<F, T, P>(get: &F, var: &'static str, parse: P) -> Result<Option<T>, EnvError>
+where
+    F: Fn(&'static str) -> Result<Option<String>, String>,
+    P: FnOnce(&str) -> Result<T, String>,
+{
+    get(var)
+        .and_then(|value| value.map(|s| parse(&s)).transpose())
+        .map_err(|cause| EnvError { var, cause })
+}
+
+/// An error returned when an environment variable has an invalid value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct EnvError {
+    /// The name of the environment variable.
+    pub var: &'static str,
+    pub cause: String,
+}
+
+impl fmt::Display for EnvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Error parsing environment variable ${{{}}}: {}",
+            self.var, self.cause
+        )
+    }
+}
+
+impl core::error::Error for EnvError {}
+
+impl HLCBuilder {
+    ///
+    /// Constructs a new HLCBuilder configured from the environment variables (see [`EnvConfig`]),
+    /// the unset ones keeping their default configuration (see [`Self::new()`]).
+    ///
+    /// Contrary to [`Self::new()`], an invalid value of an environment variable is returned as an [`EnvError`]
+    /// instead of panicking.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use uhlc::HLCBuilder;
+    ///
+    /// let hlc = HLCBuilder::from_env().unwrap().build();
+    /// ```
+    ///
+    pub fn from_env() -> Result<HLCBuilder, EnvError> {
+        let config = EnvConfig::from_env()?;
+        Ok(HLCBuilder::new().with_env_config(&config))
+    }
+
+    ///
+    /// Apply the values set in an [`EnvConfig`] to the HLC to be created.
+    ///
+    pub fn with_env_config(mut self, config: &EnvConfig) -> HLCBuilder {
+        if let Some(id) = config.id {
+            self = self.with_id(id);
+        }
+        if let Some(delta) = config.max_delta {
+            self = self.with_max_delta(delta);
+        }
+        if let Some(clock) = config.clock {
+            self = self.with_clock_kind(clock);
+        }
+        self
+    }
+
+    ///
+    /// Configure the physical clock of the HLC to be created as a [`ClockKind`]
+    /// (see [`Self::with_clock()`]), so it's part of the [`crate::HLCConfig`] returned by [`Self::to_config()`].
+    ///
+    pub fn with_clock_kind(mut self, kind: ClockKind) -> HLCBuilder {
+        self = self.with_clock(kind.clock());
+        self.clock_kind = Some(kind);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use core::time::Duration;
+    use std::string::ToString;
+
+    #[test]
+    fn env_config() {
+        let vars = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &'static str| {
+                Ok(vars
+                    .iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| value.to_string()))
+            }
+        };
+        assert_eq!(EnvConfig::from_vars(vars(&[])), Ok(EnvConfig::default()));
+
+        const COUNTER_BITS: &str = match CSIZE {
+            8 => "8",
+            16 => "16",
+            _ => "4",
+        };
+        let config = EnvConfig::from_vars(vars(&[
+            ("UHLC_ID", "1bc0"),
+            ("UHLC_MAX_DELTA_MS", "100"),
+            ("UHLC_COUNTER_BITS", COUNTER_BITS),
+            ("UHLC_CLOCK", "monotonic"),
+        ]))
+        .unwrap();
+        assert_eq!(config.id, Some("1bc0".parse().unwrap()));
+        assert_eq!(config.clock, Some(ClockKind::Monotonic));
+        let hlc = HLCBuilder::new().with_env_config(&config).build();
+        assert_eq!(hlc.get_id(), &config.id.unwrap());
+        assert_eq!(*hlc.get_delta(), NTP64::from(Duration::from_millis(100)));
+
+        let err = EnvConfig::from_vars(vars(&[("UHLC_COUNTER_BITS", "3")])).unwrap_err();
+        assert_eq!(err.var, "UHLC_COUNTER_BITS");
+        let err = EnvConfig::from_vars(vars(&[("UHLC_CLOCK", "gps")])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error parsing environment variable ${UHLC_CLOCK}: unknown clock 'gps' (must be system or monotonic)"
+        );
+        assert!(EnvConfig::from_vars(vars(&[("UHLC_ID", "0")])).is_err());
+
+        // a delta beyond the NTP64 range is an error, not a panic of the builder
+        let err =
+            EnvConfig::from_vars(vars(&[("UHLC_MAX_DELTA_MS", "4294967296000")])).unwrap_err();
+        assert_eq!(err.var, "UHLC_MAX_DELTA_MS");
+        assert!(err.cause.contains("out of range"));
+        let config = EnvConfig::from_vars(vars(&[("UHLC_MAX_DELTA_MS", "4294967295000")])).unwrap();
+        assert_eq!(
+            *HLCBuilder::new()
+                .with_env_config(&config)
+                .build()
+                .get_delta(),
+            NTP64::new(u32::MAX, 0)
+        );
+    }
+}
@@ -0,0 +1,296 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{Timestamp, NTP64};
+use core::fmt;
+use core::ops::{Add, AddAssign, Div, Mul, Sub, SubAssign};
+use core::time::Duration;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "std")]
+use std::time::SystemTime;
+
+/// A point in time, as a [`NTP64`] relative to UNIX_EPOCH (1st Jan 1970).
+///
+/// [`NTP64`] is used both for points in time and for durations: [`EpochTime`] and [`Span`] make
+/// this explicit, and only allow the arithmetic that makes sense:
+///
+/// | Operation                  | Result        |
+/// |----------------------------|---------------|
+/// | `EpochTime - EpochTime`    | [`Span`]      |
+/// | `EpochTime ± Span`         | [`EpochTime`] |
+/// | `Span ± Span`              | [`Span`]      |
+/// | `Span * u32`, `Span / u32` | [`Span`]      |
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use uhlc::{EpochTime, Span, HLC};
+///
+/// let hlc = HLC::default();
+/// let start = EpochTime::from(hlc.new_timestamp());
+/// let deadline = start + Span::from(Duration::from_secs(1));
+/// assert_eq!(deadline - start, Span::from(Duration::from_secs(1)));
+/// assert!(EpochTime::from(hlc.new_timestamp()) < deadline);
+/// ```
+#[derive(
+    Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Default, Deserialize, Serialize,
+)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(transparent)]
+pub struct EpochTime(pub NTP64);
+
+/// A duration between 2 [`EpochTime`]s, as a [`NTP64`].
+#[derive(
+    Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Default, Deserialize, Serialize,
+)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(transparent)]
+pub struct Span(pub NTP64);
+
+impl EpochTime {
+    /// Returns the underlying [`NTP64`].
+    #[inline]
+    pub const fn as_ntp64(&self) -> NTP64 {
+        self.0
+    }
+
+    /// Returns the [`Span`] elapsed since UNIX_EPOCH.
+    #[inline]
+    pub const fn since_epoch(&self) -> Span {
+        Span(self.0)
+    }
+
+    /// Returns the [`Span`] elapsed from `earlier` to this time, or `None` if `earlier` is later than this time.
+    #[inline]
+    pub fn checked_since(&self, earlier: EpochTime) -> Option<Span> {
+        self.0 .0.checked_sub(earlier.0 .0).map(|t| Span(NTP64(t)))
+    }
+
+    /// Convert to a [`SystemTime`].
+    #[inline]
+    #[cfg(feature = "std")]
+    pub fn to_system_time(self) -> SystemTime {
+        self.0.to_system_time()
+    }
+}
+
+impl Span {
+    /// Returns the underlying [`NTP64`].
+    #[inline]
+    pub const fn as_ntp64(&self) -> NTP64 {
+        self.0
+    }
+
+    /// Convert to a [`Duration`], rounded to the nearest nanosecond (see [`NTP64::to_duration()`]).
+    #[inline]
+    pub const fn to_duration(self) -> Duration {
+        self.0.to_duration()
+    }
+}
+
+impl From<NTP64> for EpochTime {
+    #[inline]
+    fn from(time: NTP64) -> Self {
+        EpochTime(time)
+    }
+}
+
+impl From<EpochTime> for NTP64 {
+    #[inline]
+    fn from(time: EpochTime) -> Self {
+        time.0
+    }
+}
+
+/// The time of the [`Timestamp`] (including its logical counter).
+impl From<Timestamp> for EpochTime {
+    #[inline]
+    fn from(timestamp: Timestamp) -> Self {
+        EpochTime(*timestamp.get_time())
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<EpochTime> for SystemTime {
+    #[inline]
+    fn from(time: EpochTime) -> Self {
+        time.to_system_time()
+    }
+}
+
+impl From<NTP64> for Span {
+    #[inline]
+    fn from(span: NTP64) -> Self {
+        Span(span)
+    }
+}
+
+impl From<Span> for NTP64 {
+    #[inline]
+    fn from(span: Span) -> Self {
+        span.0
+    }
+}
+
+/// Converts a [`Duration`] to the nearest [`Span`] (see [`NTP64::from()`]).
+///
+/// # Panics
+/// If the number of seconds exceeds the 32-bits Seconds part.
+impl From<Duration> for Span {
+    #[inline]
+    fn from(duration: Duration) -> Self {
+        Span(NTP64::from(duration))
+    }
+}
+
+impl From<Span> for Duration {
+    #[inline]
+    fn from(span: Span) -> Self {
+        span.to_duration()
+    }
+}
+
+impl Sub for EpochTime {
+    type Output = Span;
+
+    #[inline]
+    fn sub(self, other: Self) -> Span {
+        Span(self.0 - other.0)
+    }
+}
+
+impl Add<Span> for EpochTime {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, other: Span) -> Self {
+        EpochTime(self.0 + other.0)
+    }
+}
+
+impl AddAssign<Span> for EpochTime {
+    #[inline]
+    fn add_assign(&mut self, other: Span) {
+        *self = *self + other;
+    }
+}
+
+impl Sub<Span> for EpochTime {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, other: Span) -> Self {
+        EpochTime(self.0 - other.0)
+    }
+}
+
+impl SubAssign<Span> for EpochTime {
+    #[inline]
+    fn sub_assign(&mut self, other: Span) {
+        *self = *self - other;
+    }
+}
+
+impl Add for Span {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, other: Self) -> Self {
+        Span(self.0 + other.0)
+    }
+}
+
+impl AddAssign for Span {
+    #[inline]
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Span {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, other: Self) -> Self {
+        Span(self.0 - other.0)
+    }
+}
+
+impl SubAssign for Span {
+    #[inline]
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl Mul<u32> for Span {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, other: u32) -> Self {
+        Span(self.0 * other)
+    }
+}
+
+impl Div<u32> for Span {
+    type Output = Self;
+
+    #[inline]
+    fn div(self, other: u32) -> Self {
+        Span(self.0 / other)
+    }
+}
+
+impl fmt::Display for EpochTime {
+    /// Formats the time as its [`NTP64`] (i.e. in RFC3339 format with the alternate flag `{:#}`).
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Display for Span {
+    /// Formats the span as its [`Duration`], e.g. `1.5s`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.to_duration(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use alloc::string::ToString;
+    use core::time::Duration;
+
+    #[test]
+    fn epoch_time_and_span() {
+        let t1 = EpochTime(NTP64::new(1000, 0));
+        let t2 = EpochTime(NTP64::new(1001, 1 << 31));
+        let span = t2 - t1;
+        assert_eq!(span, Span::from(Duration::from_millis(1500)));
+        assert_eq!(t1 + span, t2);
+        assert_eq!(t2 - span, t1);
+        assert_eq!(t2.checked_since(t1), Some(span));
+        assert_eq!(t1.checked_since(t2), None);
+        assert_eq!(span * 2 / 3, Span::from(Duration::from_secs(1)));
+        assert_eq!(span + span - span, span);
+        assert_eq!(Duration::from(span), Duration::from_millis(1500));
+        assert_eq!(t1.since_epoch(), Span::from(Duration::from_secs(1000)));
+
+        let mut t = t1;
+        t += span;
+        assert_eq!(t, t2);
+        t -= span;
+        assert_eq!(t, t1);
+
+        assert_eq!(span.to_string(), "1.5s");
+        assert_eq!(t1.to_string(), t1.as_ntp64().to_string());
+    }
+}
@@ -0,0 +1,482 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{
+    CertifyError, DecodeError, IdError, ParseIDError, ParseNTP64Error, ParseTimestampError,
+    SizeError, TimeRangeError, Violation, ID, NTP64,
+};
+use core::fmt;
+
+/// The error returned by [`crate::HLC::update_with_timestamp()`] when an incoming [`crate::Timestamp`]
+/// is rejected.
+///
+/// It only carries the figures of the rejection, without any allocation:
+/// the human readable message is produced by its [`fmt::Display`] implementation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum UpdateError {
+    /// The [`crate::Timestamp`] exceeds the maximum delta (see [`crate::HLCBuilder::with_max_delta()`]).
+    DeltaExceeded {
+        /// The [`ID`] of the [`crate::Timestamp`].
+        id: ID,
+        /// The time of the [`crate::Timestamp`].
+        time: NTP64,
+        /// The physical time of the [`crate::HLC`] (without counter part).
+        now: NTP64,
+        /// The maximum delta of the [`crate::HLC`].
+        delta: NTP64,
+    },
+    /// Its [`ID`] exceeds its update rate limit (see [`crate::HLCBuilder::with_update_rate_limit()`]).
+    RateLimited {
+        /// The [`ID`] of the [`crate::Timestamp`].
+        id: ID,
+        /// The maximum number of updates per period.
+        max: u32,
+        /// The period of the limit.
+        period: NTP64,
+    },
+    /// Its [`ID`] exceeds its drift budget (see [`crate::HLCBuilder::with_drift_budget()`]).
+    DriftBudgetExceeded {
+        /// The [`ID`] of the [`crate::Timestamp`].
+        id: ID,
+        /// The drift budget per period.
+        budget: NTP64,
+        /// The period of the budget.
+        period: NTP64,
+        /// The drift already used during the current period.
+        used: NTP64,
+        /// How far the [`crate::Timestamp`] is ahead of the physical time of the [`crate::HLC`].
+        drift: NTP64,
+    },
+    /// Its [`ID`] is quarantined (see [`crate::HLCBuilder::with_quarantine()`]).
+    PeerQuarantined(ID),
+    /// The [`crate::Timestamp`] is so close to the maximum time that the time of the [`crate::HLC`] would overflow.
+    FrontierOverflow {
+        /// The [`ID`] of the [`crate::Timestamp`].
+        id: ID,
+        /// The time of the [`crate::Timestamp`].
+        time: NTP64,
+    },
+}
+
+impl fmt::Display for UpdateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UpdateError::DeltaExceeded {
+                id,
+                time,
+                now,
+                delta,
+            } => write!(
+                f,
+                "incoming timestamp from {} exceeding delta {}ms is rejected: {:#} vs. now: {:#}",
+                id,
+                delta.to_duration().as_millis(),
+                time,
+                now
+            ),
+            UpdateError::RateLimited { id, max, period } => write!(
+                f,
+                "incoming timestamp from {} exceeding rate limit of {} updates per {}ms is rejected",
+                id,
+                max,
+                period.to_duration().as_millis()
+            ),
+            UpdateError::DriftBudgetExceeded {
+                id,
+                budget,
+                period,
+                used,
+                drift,
+            } => write!(
+                f,
+                "incoming timestamp from {} exceeding drift budget of {}ms per {}ms is rejected: {}ms already used, {}ms ahead of now",
+                id,
+                budget.to_duration().as_millis(),
+                period.to_duration().as_millis(),
+                used.to_duration().as_millis(),
+                drift.to_duration().as_millis()
+            ),
+            UpdateError::PeerQuarantined(id) => write!(
+                f,
+                "incoming timestamp from {id} is rejected: {id} is quarantined"
+            ),
+            UpdateError::FrontierOverflow { id, time } => write!(
+                f,
+                "incoming timestamp from {} is rejected: the HLC's time would overflow after {}",
+                id, time
+            ),
+        }
+    }
+}
+
+impl core::error::Error for UpdateError {}
+
+/// The error returned by [`crate::HLCBuilder::build_checked()`] when the configuration of the
+/// [`crate::HLC`] is nonsensical.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum ConfigError {
+    /// The maximum delta is lower than the resolution of the [`crate::HLC`]'s physical time
+    /// (i.e. without its counter part), so the [`crate::Timestamp`]s of peers with an identical
+    /// physical time would be rejected as soon as their counter is ahead.
+    MaxDeltaTooSmall(NTP64),
+    /// The update rate limit is zero, so all the updates are rejected.
+    ZeroRateLimit,
+    /// The period of the named limit is zero (i.e. lower than the time resolution), so the limit never applies.
+    ZeroPeriod(&'static str),
+    /// The size of the logical counter (in bits) differs from [`crate::CSIZE`], fixed at compile time.
+    CounterBits(u8),
+    /// The duration of the named setting exceeds the range of a [`NTP64`] (~136 years).
+    OutOfRange(&'static str),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::MaxDeltaTooSmall(delta) => write!(
+                f,
+                "Invalid HLC configuration: maximum delta {delta} is lower than the time resolution"
+            ),
+            ConfigError::ZeroRateLimit => write!(
+                f,
+                "Invalid HLC configuration: an update rate limit of 0 rejects all updates"
+            ),
+            ConfigError::ZeroPeriod(limit) => write!(
+                f,
+                "Invalid HLC configuration: the period of the {limit} is 0"
+            ),
+            ConfigError::CounterBits(bits) => write!(
+                f,
+                "Invalid HLC configuration: the counter size is fixed to {} bits in this build, not {bits}",
+                crate::CSIZE
+            ),
+            ConfigError::OutOfRange(setting) => write!(
+                f,
+                "Invalid HLC configuration: the {setting} exceeds the range of a NTP64"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for ConfigError {}
+
+/// The error returned by [`crate::HLC::rotate_id()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum RotateIdError {
+    /// The new [`ID`] is the current one.
+    SameId(ID),
+}
+
+impl fmt::Display for RotateIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RotateIdError::SameId(id) => {
+                write!(f, "Can't rotate the HLC's ID: {id} is already its ID")
+            }
+        }
+    }
+}
+
+impl core::error::Error for RotateIdError {}
+
+/// The error returned by [`crate::HLC::try_new_timestamp()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum LeadError {
+    /// The new [`crate::Timestamp`] would exceed the maximum logical lead
+    /// (see [`crate::HLCBuilder::with_max_logical_lead()`]).
+    MaxLeadExceeded {
+        /// How far the new [`crate::Timestamp`] would be ahead of the physical clock.
+        lead: NTP64,
+        /// The maximum logical lead of the [`crate::HLC`].
+        max: NTP64,
+    },
+}
+
+impl fmt::Display for LeadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LeadError::MaxLeadExceeded { lead, max } => write!(
+                f,
+                "Can't generate a timestamp {}ms ahead of the physical clock: the maximum logical lead is {}ms",
+                lead.to_duration().as_millis(),
+                max.to_duration().as_millis()
+            ),
+        }
+    }
+}
+
+impl core::error::Error for LeadError {}
+
+/// The error returned by [`crate::HLC::reserve()`] when the logical counter of the current physical time
+/// can't hold the requested number of [`crate::Timestamp`]s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CounterExhausted {
+    /// The requested number of [`crate::Timestamp`]s.
+    pub requested: u8,
+    /// The number of counter values left for the current physical time.
+    pub available: u64,
+}
+
+impl fmt::Display for CounterExhausted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Can't reserve {} timestamps: only {} left for the current physical time",
+            self.requested, self.available
+        )
+    }
+}
+
+impl core::error::Error for CounterExhausted {}
+
+/// The error returned by [`crate::Timestamp::format_into()`], [`crate::ID::format_into()`]
+/// and [`crate::NTP64::format_into()`] when the buffer is too small for the rendering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BufferTooSmall {
+    /// The length of the rendering.
+    pub needed: usize,
+    /// The length of the buffer.
+    pub available: usize,
+}
+
+impl fmt::Display for BufferTooSmall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Buffer too small: {} bytes needed, {} available",
+            self.needed, self.available
+        )
+    }
+}
+
+impl core::error::Error for BufferTooSmall {}
+
+/// An error aggregating all the errors that can be returned by this crate,
+/// except the `MsgpackError` of the `rmp` feature, which is generic over the reader's error.
+///
+/// Each of those errors converts into an [`Error`], allowing to propagate them uniformly with `?`.
+///
+/// # Examples
+///
+/// ```
+/// use uhlc::{Timestamp, HLC, NTP64};
+///
+/// fn update_from_str(hlc: &HLC, s: &str) -> Result<NTP64, uhlc::Error> {
+///     let ts: Timestamp = s.parse()?;
+///     hlc.update_with_timestamp(&ts)?;
+///     Ok(*ts.get_time())
+/// }
+///
+/// let hlc = HLC::default();
+/// assert!(update_from_str(&hlc, "0/33").is_ok());
+/// assert!(matches!(update_from_str(&hlc, "0/0"), Err(uhlc::Error::ParseTimestamp(_))));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum Error {
+    /// An [`crate::ID`] failed to be parsed.
+    ParseID(ParseIDError),
+    /// An [`crate::NTP64`] failed to be parsed.
+    ParseNTP64(ParseNTP64Error),
+    /// A [`crate::Timestamp`] failed to be parsed.
+    ParseTimestamp(ParseTimestampError),
+    /// An [`crate::ID`] was built from too many bytes, or from zeros only.
+    Size(SizeError),
+    /// An [`crate::ID`] failed to be built from bytes with [`crate::ID::from_bytes()`].
+    Id(IdError),
+    /// A [`crate::Timestamp`] failed to be decoded from bytes.
+    Decode(DecodeError),
+    /// An incoming [`crate::Timestamp`] was rejected by an [`crate::HLC`].
+    Update(UpdateError),
+    /// A [`crate::Timestamp`] couldn't be certified by an [`crate::HLC`].
+    Certify(CertifyError),
+    /// The configuration of an [`crate::HLC`] is invalid.
+    Config(ConfigError),
+    /// The [`ID`] of an [`crate::HLC`] couldn't be rotated.
+    RotateId(RotateIdError),
+    /// A new [`crate::Timestamp`] would exceed the maximum logical lead of an [`crate::HLC`].
+    Lead(LeadError),
+    /// [`crate::Timestamp`]s couldn't be reserved by an [`crate::HLC`].
+    CounterExhausted(CounterExhausted),
+    /// A rendering didn't fit in a buffer.
+    BufferTooSmall(BufferTooSmall),
+    /// A time can't be represented as a [`crate::NTP64`].
+    TimeRange(TimeRangeError),
+    /// A [`crate::Timestamp`] is not greater than the previous one checked by a [`crate::MonotonicChecker`].
+    Monotonic(Violation),
+    /// An environment variable configuring an [`crate::HLC`] is invalid.
+    #[cfg(feature = "std")]
+    Env(crate::EnvError),
+    /// A recorded event failed to be parsed.
+    #[cfg(all(feature = "record", feature = "std"))]
+    ParseEvent(crate::ParseEventError),
+    /// A replayed event diverged from the recorded one.
+    #[cfg(feature = "record")]
+    Replay(crate::ReplayError),
+    /// A [`crate::SignedTimestamp`] failed to be verified.
+    ///
+    /// The ed25519 signature error is opaque, so it's not carried.
+    #[cfg(feature = "signed")]
+    Signature,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::ParseID(e) => fmt::Display::fmt(e, f),
+            Error::ParseNTP64(e) => fmt::Display::fmt(e, f),
+            Error::ParseTimestamp(e) => fmt::Display::fmt(e, f),
+            Error::Size(e) => fmt::Display::fmt(e, f),
+            Error::Id(e) => fmt::Display::fmt(e, f),
+            Error::Decode(e) => fmt::Display::fmt(e, f),
+            Error::Update(e) => fmt::Display::fmt(e, f),
+            Error::Certify(e) => fmt::Display::fmt(e, f),
+            Error::Config(e) => fmt::Display::fmt(e, f),
+            Error::RotateId(e) => fmt::Display::fmt(e, f),
+            Error::Lead(e) => fmt::Display::fmt(e, f),
+            Error::CounterExhausted(e) => fmt::Display::fmt(e, f),
+            Error::BufferTooSmall(e) => fmt::Display::fmt(e, f),
+            Error::TimeRange(e) => fmt::Display::fmt(e, f),
+            Error::Monotonic(e) => fmt::Display::fmt(e, f),
+            #[cfg(feature = "std")]
+            Error::Env(e) => fmt::Display::fmt(e, f),
+            #[cfg(all(feature = "record", feature = "std"))]
+            Error::ParseEvent(e) => fmt::Display::fmt(e, f),
+            #[cfg(feature = "record")]
+            Error::Replay(e) => fmt::Display::fmt(e, f),
+            #[cfg(feature = "signed")]
+            Error::Signature => write!(f, "Invalid signature"),
+        }
+    }
+}
+
+impl core::error::Error for Error {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Error::ParseID(e) => Some(e),
+            Error::ParseNTP64(e) => Some(e),
+            Error::ParseTimestamp(e) => Some(e),
+            Error::Size(e) => Some(e),
+            Error::Id(e) => Some(e),
+            Error::Decode(e) => Some(e),
+            Error::Update(e) => Some(e),
+            Error::Certify(e) => Some(e),
+            Error::Config(e) => Some(e),
+            Error::RotateId(e) => Some(e),
+            Error::Lead(e) => Some(e),
+            Error::CounterExhausted(e) => Some(e),
+            Error::BufferTooSmall(e) => Some(e),
+            Error::TimeRange(e) => Some(e),
+            Error::Monotonic(e) => Some(e),
+            #[cfg(feature = "std")]
+            Error::Env(e) => Some(e),
+            #[cfg(all(feature = "record", feature = "std"))]
+            Error::ParseEvent(e) => Some(e),
+            #[cfg(feature = "record")]
+            Error::Replay(e) => Some(e),
+            #[cfg(feature = "signed")]
+            Error::Signature => None,
+        }
+    }
+}
+
+macro_rules! impl_from_error {
+    ($($variant:ident($err:ty)),*) => {
+        $(
+            impl From<$err> for Error {
+                fn from(e: $err) -> Self {
+                    Error::$variant(e)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_error!(
+    ParseID(ParseIDError),
+    ParseNTP64(ParseNTP64Error),
+    ParseTimestamp(ParseTimestampError),
+    Size(SizeError),
+    Id(IdError),
+    Decode(DecodeError),
+    Update(UpdateError),
+    Certify(CertifyError),
+    Config(ConfigError),
+    RotateId(RotateIdError),
+    Lead(LeadError),
+    CounterExhausted(CounterExhausted),
+    BufferTooSmall(BufferTooSmall),
+    TimeRange(TimeRangeError),
+    Monotonic(Violation)
+);
+#[cfg(feature = "std")]
+impl_from_error!(Env(crate::EnvError));
+#[cfg(all(feature = "record", feature = "std"))]
+impl_from_error!(ParseEvent(crate::ParseEventError));
+#[cfg(feature = "record")]
+impl_from_error!(Replay(crate::ReplayError));
+
+#[cfg(feature = "signed")]
+impl From<ed25519_dalek::SignatureError> for Error {
+    fn from(_: ed25519_dalek::SignatureError) -> Self {
+        Error::Signature
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use core::error::Error as _;
+
+    #[test]
+    fn from_errors() {
+        let err: Error = "0x1".parse::<ID>().unwrap_err().into();
+        assert!(matches!(err, Error::ParseID(_)));
+        assert_eq!(err.to_string(), "Invalid ID: Leading 0s are not valid");
+        assert!(err.source().is_some());
+
+        let hlc = HLCBuilder::new()
+            .with_max_delta(core::time::Duration::from_secs(1))
+            .build();
+        let future_ts = Timestamp::new(NTP64(u64::MAX >> 1), ID::rand());
+        let update_err = hlc.update_with_timestamp(&future_ts).unwrap_err();
+        assert!(matches!(
+            update_err,
+            UpdateError::DeltaExceeded { id, time, delta, .. }
+                if id == *future_ts.get_id() && time == *future_ts.get_time() && delta == *hlc.get_delta()
+        ));
+        assert!(update_err.to_string().starts_with(&format!(
+            "incoming timestamp from {} exceeding delta 1000ms is rejected: ",
+            future_ts.get_id()
+        )));
+        let err: Error = update_err.into();
+        assert!(matches!(err, Error::Update(_)));
+
+        let err: Error = NTP64::try_from_duration(core::time::Duration::MAX)
+            .unwrap_err()
+            .into();
+        assert_eq!(err, Error::TimeRange(TimeRangeError::AfterEraEnd));
+        assert!(err.source().is_some());
+        let mut checker = MonotonicChecker::new();
+        let ts = HLC::default().new_timestamp();
+        let err: Error = checker.check_all([ts, ts]).unwrap_err().into();
+        assert!(matches!(err, Error::Monotonic(_)));
+    }
+}
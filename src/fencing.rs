@@ -0,0 +1,105 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::Timestamp;
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+/// A fencing token derived from a [`Timestamp`] (see [`Timestamp::as_fencing_token()`]),
+/// e.g. for a lock service to reject the writes of a client holding an expired lock.
+///
+/// The tokens derived from the [`Timestamp`]s of a same [`crate::HLC`] are strictly increasing,
+/// so a token supersedes all the lower ones. Note that the tokens derived from the [`Timestamp`]s
+/// of different [`crate::HLC`]s may be equal: the tokens should be issued by a single [`crate::HLC`]
+/// (e.g. the lock service's one).
+///
+/// # Examples
+///
+/// ```
+/// use uhlc::{FencingToken, HLC};
+///
+/// let hlc = HLC::default();
+/// let first = FencingToken::from(hlc.new_timestamp());
+/// let second = FencingToken::from(hlc.new_timestamp());
+/// assert!(second.supersedes(&first));
+/// assert_eq!(second.as_u64(), second.to_string().parse::<u64>().unwrap());
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FencingToken(pub u64);
+
+impl FencingToken {
+    /// Returns the numeric form of this token.
+    #[inline]
+    pub const fn as_u64(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns `true` if this token is greater than `other`, i.e. if it fences `other` off.
+    #[inline]
+    pub fn supersedes(&self, other: &FencingToken) -> bool {
+        self > other
+    }
+}
+
+impl From<Timestamp> for FencingToken {
+    fn from(timestamp: Timestamp) -> Self {
+        FencingToken(timestamp.as_fencing_token())
+    }
+}
+
+impl fmt::Display for FencingToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl Timestamp {
+    /// Returns a fencing token for this [`Timestamp`]: the 64 bits of its time, i.e. the physical time
+    /// in the NTP64 format with the HLC logical counter in its last [`crate::CSIZE`] bits (see [`crate::NTP64`]).
+    ///
+    /// As the times of the [`Timestamp`]s generated by an [`crate::HLC`] are strictly increasing,
+    /// so are their fencing tokens (see [`FencingToken`]).
+    #[inline]
+    pub const fn as_fencing_token(&self) -> u64 {
+        self.get_time().as_u64()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn fencing_tokens() {
+        let hlc = HLC::default();
+        let mut previous = FencingToken::from(hlc.new_timestamp());
+        for _ in 0..100 {
+            let ts = hlc.new_timestamp();
+            let token = FencingToken::from(ts);
+            assert_eq!(token.as_u64(), ts.as_fencing_token());
+            assert!(token.supersedes(&previous));
+            assert!(!previous.supersedes(&token));
+            previous = token;
+        }
+
+        // the tokens follow the updates of the HLC
+        let other = HLC::default();
+        other.update_with_timestamp(&hlc.new_timestamp()).unwrap();
+        assert!(FencingToken::from(other.new_timestamp()).supersedes(&previous));
+
+        let json = serde_json::to_string(&previous).unwrap();
+        assert_eq!(json, previous.to_string());
+        assert_eq!(
+            serde_json::from_str::<FencingToken>(&json).unwrap(),
+            previous
+        );
+    }
+}
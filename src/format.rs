@@ -0,0 +1,337 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{BufferTooSmall, Timestamp, CMASK, ID, LMASK, NTP64};
+use core::fmt;
+
+/// A rendering of [`Timestamp`]s, to be used with [`Timestamp::format_with()`].
+///
+/// This allows to choose a rendering once (e.g. in the configuration of a logging framework),
+/// rather than choosing between `{}` and `{:#}` at each formatting of a [`Timestamp`].
+///
+/// The following formatters are provided: [`DecimalFormatter`], [`Rfc3339Formatter`], [`SortableFormatter`], [`HexFormatter`] and [`VerboseFormatter`].
+/// This trait is also implemented for closures with the same signature than [`TimestampFormatter::fmt()`].
+///
+/// # Examples
+/// ```
+/// use std::convert::TryFrom;
+/// use uhlc::*;
+///
+/// fn log_event(formatter: &impl TimestampFormatter, ts: &Timestamp) {
+///     println!("[{}] event", ts.format_with(formatter));
+/// }
+///
+/// let t = Timestamp::new(NTP64(7386690599959157260), ID::try_from([0x33]).unwrap());
+/// assert_eq!(t.format_with(&DecimalFormatter).to_string(), "7386690599959157260/33");
+/// assert_eq!(t.format_with(&HexFormatter).to_string(), "6682cbf6dc485a0c/33");
+///
+/// let secs_only = |ts: &Timestamp, f: &mut std::fmt::Formatter| write!(f, "{}", ts.get_time().as_secs());
+/// assert_eq!(t.format_with(&secs_only).to_string(), "1719847926");
+/// ```
+pub trait TimestampFormatter {
+    /// Formats `timestamp` into `f`.
+    fn fmt(&self, timestamp: &Timestamp, f: &mut fmt::Formatter) -> fmt::Result;
+}
+
+impl<F> TimestampFormatter for F
+where
+    F: Fn(&Timestamp, &mut fmt::Formatter) -> fmt::Result,
+{
+    fn fmt(&self, timestamp: &Timestamp, f: &mut fmt::Formatter) -> fmt::Result {
+        self(timestamp, f)
+    }
+}
+
+/// Formats a [`Timestamp`] with its time as an unsigned integer in decimal format, as its [`fmt::Display`] does:
+/// `"<ntp64_time>/<hlc_id_hexadecimal>"`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DecimalFormatter;
+
+impl TimestampFormatter for DecimalFormatter {
+    fn fmt(&self, timestamp: &Timestamp, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", timestamp.get_time(), timestamp.get_id())
+    }
+}
+
+/// Formats a [`Timestamp`] with its time in RFC3339 representation with nanoseconds precision,
+/// as its [`fmt::Display`] does with the alternate flag (`{:#}`): `"<rfc3339_time>/<hlc_id_hexadecimal>"`.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Rfc3339Formatter;
+
+#[cfg(feature = "std")]
+impl TimestampFormatter for Rfc3339Formatter {
+    fn fmt(&self, timestamp: &Timestamp, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:#}/{}", timestamp.get_time(), timestamp.get_id())
+    }
+}
+
+/// Formats a [`Timestamp`] as a fixed-width String of 48 lowercase hexadecimal digits:
+/// the encoding of [`Timestamp::to_key()`].
+///
+/// Sorting such Strings lexicographically gives the same ordering than sorting the [`Timestamp`]s.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SortableFormatter;
+
+impl TimestampFormatter for SortableFormatter {
+    fn fmt(&self, timestamp: &Timestamp, f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in timestamp.to_key().iter() {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats a [`Timestamp`] with its time as 16 lowercase hexadecimal digits: `"<ntp64_time_hexadecimal>/<hlc_id_hexadecimal>"`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HexFormatter;
+
+impl TimestampFormatter for HexFormatter {
+    fn fmt(&self, timestamp: &Timestamp, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:016x}/{}",
+            timestamp.get_time().as_u64(),
+            timestamp.get_id()
+        )
+    }
+}
+
+/// Formats a [`Timestamp`] with its time split into seconds, nanoseconds and HLC logical counter:
+/// `"<seconds>.<nanoseconds>+<counter>/<hlc_id_hexadecimal>"`, as [`Timestamp::debug_verbose()`].
+///
+/// This makes the behavior of the HLC visible at a glance (e.g. when debugging ordering issues),
+/// while the counter is hidden in the raw [`NTP64`] of [`fmt::Debug`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VerboseFormatter;
+
+impl TimestampFormatter for VerboseFormatter {
+    fn fmt(&self, timestamp: &Timestamp, f: &mut fmt::Formatter) -> fmt::Result {
+        let time = timestamp.get_time().as_u64();
+        let physical = NTP64(time & LMASK);
+        write!(
+            f,
+            "{}.{:09}+{}/{}",
+            physical.as_secs(),
+            physical.subsec_nanos(),
+            time & CMASK,
+            timestamp.get_id()
+        )
+    }
+}
+
+/// A [`Timestamp`] rendered with a [`TimestampFormatter`], returned by [`Timestamp::format_with()`].
+///
+/// It implements [`fmt::Display`] without any allocation.
+pub struct FormattedTimestamp<'a, F: ?Sized> {
+    timestamp: &'a Timestamp,
+    formatter: &'a F,
+}
+
+impl<F: TimestampFormatter + ?Sized> fmt::Display for FormattedTimestamp<'_, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.formatter.fmt(self.timestamp, f)
+    }
+}
+
+impl Timestamp {
+    /// Returns a [`fmt::Display`]able rendering of this [`Timestamp`] with `formatter`.
+    pub fn format_with<'a, F: TimestampFormatter + ?Sized>(
+        &'a self,
+        formatter: &'a F,
+    ) -> FormattedTimestamp<'a, F> {
+        FormattedTimestamp {
+            timestamp: self,
+            formatter,
+        }
+    }
+
+    /// Returns a [`fmt::Display`]able rendering of this [`Timestamp`] with [`VerboseFormatter`]:
+    /// `"<seconds>.<nanoseconds>+<counter>/<hlc_id_hexadecimal>"`.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::convert::TryFrom;
+    /// use uhlc::*;
+    ///
+    /// let t = Timestamp::new(NTP64(0x6682_cbf6_dc48_0003), ID::try_from([0x33]).unwrap());
+    /// assert_eq!(t.debug_verbose().to_string(), "1719847926.860473633+3/33");
+    /// ```
+    pub fn debug_verbose(&self) -> FormattedTimestamp<'_, VerboseFormatter> {
+        self.format_with(&VerboseFormatter)
+    }
+}
+
+// A fmt::Write into a byte slice, counting the bytes that don't fit rather than failing,
+// so the needed length is known when the slice is too small
+struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl fmt::Write for SliceWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        if let Some(dst) = self.buf.get_mut(self.len..end) {
+            dst.copy_from_slice(s.as_bytes());
+        }
+        self.len = end;
+        Ok(())
+    }
+}
+
+// Renders `value` with its fmt::Display into `buf`, returning the number of bytes written
+fn format_into(value: &dyn fmt::Display, buf: &mut [u8]) -> Result<usize, BufferTooSmall> {
+    let mut writer = SliceWriter { buf, len: 0 };
+    // SliceWriter never fails, only the Display of the value could
+    let _ = fmt::write(&mut writer, format_args!("{value}"));
+    if writer.len <= writer.buf.len() {
+        Ok(writer.len)
+    } else {
+        Err(BufferTooSmall {
+            needed: writer.len,
+            available: writer.buf.len(),
+        })
+    }
+}
+
+impl Timestamp {
+    /// The maximum length of a [`Timestamp`] rendered by [`Timestamp::format_into()`]:
+    /// a 20 digits time, the `/` separator and a 32 digits [`ID`].
+    pub const FORMATTED_MAX_LEN: usize = NTP64::FORMATTED_MAX_LEN + 1 + ID::FORMATTED_MAX_LEN;
+
+    /// Renders this [`Timestamp`] as its [`fmt::Display`] does (`"<ntp64_time>/<hlc_id_hexadecimal>"`)
+    /// into `buf`, without any allocation, and returns the number of bytes written.
+    ///
+    /// If `buf` is shorter than the rendering, [`BufferTooSmall`] is returned with the needed length,
+    /// and the content of `buf` is unspecified. A buffer of [`Timestamp::FORMATTED_MAX_LEN`] bytes always fits.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::convert::TryFrom;
+    /// use uhlc::*;
+    ///
+    /// let t = Timestamp::new(NTP64(7386690599959157260), ID::try_from([0x33]).unwrap());
+    /// let mut buf = [0u8; Timestamp::FORMATTED_MAX_LEN];
+    /// let len = t.format_into(&mut buf).unwrap();
+    /// assert_eq!(&buf[..len], b"7386690599959157260/33");
+    /// assert_eq!(t.format_into(&mut buf[..8]).unwrap_err().needed, 22);
+    /// ```
+    pub fn format_into(&self, buf: &mut [u8]) -> Result<usize, BufferTooSmall> {
+        format_into(self, buf)
+    }
+}
+
+impl ID {
+    /// The maximum length of an [`ID`] rendered by [`ID::format_into()`]: 32 hexadecimal digits.
+    pub const FORMATTED_MAX_LEN: usize = 2 * ID::MAX_SIZE;
+
+    /// Renders this [`ID`] as its [`fmt::Display`] does (lowercase hexadecimal) into `buf`,
+    /// without any allocation, and returns the number of bytes written.
+    ///
+    /// If `buf` is shorter than the rendering, [`BufferTooSmall`] is returned with the needed length,
+    /// and the content of `buf` is unspecified.
+    pub fn format_into(&self, buf: &mut [u8]) -> Result<usize, BufferTooSmall> {
+        format_into(self, buf)
+    }
+}
+
+impl NTP64 {
+    /// The maximum length of a [`NTP64`] rendered by [`NTP64::format_into()`]: the 20 digits of [`u64::MAX`].
+    pub const FORMATTED_MAX_LEN: usize = 20;
+
+    /// Renders this [`NTP64`] as its [`fmt::Display`] does (an unsigned integer in decimal format) into `buf`,
+    /// without any allocation, and returns the number of bytes written.
+    ///
+    /// If `buf` is shorter than the rendering, [`BufferTooSmall`] is returned with the needed length,
+    /// and the content of `buf` is unspecified.
+    pub fn format_into(&self, buf: &mut [u8]) -> Result<usize, BufferTooSmall> {
+        format_into(self, buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use core::time::Duration;
+
+    #[test]
+    fn formatters() {
+        // timestamps with the same times but different IDs
+        let time = *HLC::default().new_timestamp().get_time();
+        let mut stamps: Vec<Timestamp> = (0..100)
+            .map(|i| Timestamp::new(time + (i % 10), ID::rand()))
+            .collect();
+        for ts in stamps.iter() {
+            assert_eq!(
+                ts.format_with(&DecimalFormatter).to_string(),
+                format!("{ts}")
+            );
+            #[cfg(feature = "std")]
+            assert_eq!(
+                ts.format_with(&Rfc3339Formatter).to_string(),
+                format!("{ts:#}")
+            );
+            assert_eq!(ts.format_with(&SortableFormatter).to_string().len(), 48);
+        }
+
+        // the sortable rendering preserves the ordering, whatever the IDs
+        let mut sortables: Vec<String> = stamps
+            .iter()
+            .map(|ts| ts.format_with(&SortableFormatter).to_string())
+            .collect();
+        stamps.sort();
+        sortables.sort();
+        for (ts, s) in stamps.iter().zip(sortables.iter()) {
+            assert_eq!(&ts.format_with(&SortableFormatter).to_string(), s);
+        }
+
+        // the verbose rendering shows the logical counter
+        let ts = Timestamp::new(NTP64::from(Duration::from_millis(1500)) + 3, ID::MIN);
+        assert_eq!(
+            ts.debug_verbose().to_string(),
+            format!("1.500000000+3/{}", ID::MIN)
+        );
+
+        // formatters are usable as trait objects
+        let formatter: &dyn TimestampFormatter = &HexFormatter;
+        let ts = stamps[0];
+        assert_eq!(
+            ts.format_with(formatter).to_string(),
+            format!("{:016x}/{}", ts.get_time().as_u64(), ts.get_id())
+        );
+    }
+
+    #[test]
+    fn format_into() {
+        let ts = Timestamp::new(NTP64(u64::MAX), ID::MAX);
+        let mut buf = [0u8; Timestamp::FORMATTED_MAX_LEN];
+        let len = ts.format_into(&mut buf).unwrap();
+        assert_eq!(len, Timestamp::FORMATTED_MAX_LEN);
+        assert_eq!(&buf[..len], ts.to_string().as_bytes());
+
+        let ts = HLC::default().new_timestamp();
+        let len = ts.get_id().format_into(&mut buf).unwrap();
+        assert_eq!(&buf[..len], ts.get_id().to_string().as_bytes());
+        let len = ts.get_time().format_into(&mut buf).unwrap();
+        assert_eq!(&buf[..len], ts.get_time().to_string().as_bytes());
+
+        let needed = ts.to_string().len();
+        assert_eq!(ts.format_into(&mut buf[..needed]), Ok(needed));
+        assert_eq!(
+            ts.format_into(&mut buf[..needed - 1]),
+            Err(BufferTooSmall {
+                needed,
+                available: needed - 1
+            })
+        );
+        assert!(NTP64(0).format_into(&mut []).is_err());
+    }
+}
@@ -0,0 +1,268 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{Mutex, UpdateError, ID, NTP64};
+use alloc::collections::BTreeMap;
+
+// The maximum number of peers whose usage is tracked. Beyond, the stale usages are dropped,
+// then the least recently seen one, so that rotating or forged IDs can't grow the memory without bound.
+pub(crate) const MAX_TRACKED_PEERS: usize = 1024;
+
+// A limit of `max` per `period`, measured with the HLC's physical clock.
+#[derive(Clone, Copy)]
+pub(crate) struct Limit<T> {
+    pub(crate) max: T,
+    pub(crate) period: NTP64,
+}
+
+// The usage of a limit by a peer during the current window.
+#[derive(Clone, Copy, Default)]
+struct Window<T> {
+    start: NTP64,
+    used: T,
+}
+
+impl<T: Default> Window<T> {
+    // Returns true if `now` is not within the current window
+    fn is_expired<U>(&self, now: NTP64, limit: Option<Limit<U>>) -> bool {
+        limit.map_or(true, |limit| {
+            now < self.start || now - self.start >= limit.period
+        })
+    }
+
+    // Start a new window if `now` is not within the current one
+    fn refresh(&mut self, now: NTP64, period: NTP64) {
+        if now < self.start || now - self.start >= period {
+            self.start = now;
+            self.used = T::default();
+        }
+    }
+}
+
+#[derive(Default)]
+struct PeerUsage {
+    updates: Window<u32>,
+    drift: Window<NTP64>,
+    rejections: Window<u32>,
+    // when the peer was quarantined, if it is
+    quarantined: Option<NTP64>,
+    last_seen: NTP64,
+}
+
+/// Guards an [`crate::HLC`] against peers that would keep pushing its time forward,
+/// limiting per [`ID`] the rate of accepted updates and their cumulative forward drift,
+/// and quarantining the [`ID`]s with too many rejected updates.
+pub(crate) struct PeerGuard {
+    pub(crate) rate_limit: Option<Limit<u32>>,
+    pub(crate) drift_budget: Option<Limit<NTP64>>,
+    pub(crate) quarantine: Option<Limit<u32>>,
+    peers: Mutex<BTreeMap<ID, PeerUsage>>,
+}
+
+impl PeerGuard {
+    pub(crate) fn new() -> PeerGuard {
+        PeerGuard {
+            rate_limit: None,
+            drift_budget: None,
+            quarantine: None,
+            peers: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    // Returns the usage of peer `id` seen at `now`, tracking it if it's not yet.
+    fn usage<'a>(
+        &self,
+        peers: &'a mut BTreeMap<ID, PeerUsage>,
+        id: &ID,
+        now: NTP64,
+    ) -> &'a mut PeerUsage {
+        if !peers.contains_key(id) && peers.len() >= MAX_TRACKED_PEERS {
+            peers.retain(|_, usage| !self.is_stale(usage, now));
+            if peers.len() >= MAX_TRACKED_PEERS {
+                // evict the least recently seen peer, quarantined ones last
+                let oldest = peers
+                    .iter()
+                    .min_by_key(|(_, usage)| (self.is_quarantined_at(usage, now), usage.last_seen))
+                    .map(|(id, _)| *id);
+                if let Some(oldest) = oldest {
+                    peers.remove(&oldest);
+                }
+            }
+        }
+        let usage = peers.entry(*id).or_default();
+        usage.last_seen = now;
+        usage
+    }
+
+    // Returns true if dropping the usage doesn't change the guard's decisions:
+    // all its windows and its quarantine expired.
+    fn is_stale(&self, usage: &PeerUsage, now: NTP64) -> bool {
+        !self.is_quarantined_at(usage, now)
+            && usage.updates.is_expired(now, self.rate_limit)
+            && usage.drift.is_expired(now, self.drift_budget)
+            && usage.rejections.is_expired(now, self.quarantine)
+    }
+
+    // Returns true if the peer of `usage` is quarantined at `now`: its quarantine lasts for the quarantine period.
+    fn is_quarantined_at(&self, usage: &PeerUsage, now: NTP64) -> bool {
+        match (usage.quarantined, self.quarantine) {
+            (Some(since), Some(limit)) => since <= now && now - since < limit.period,
+            _ => false,
+        }
+    }
+
+    // Fail fast if peer `id` is quarantined at `now`.
+    pub(crate) fn check_quarantine(&self, id: &ID, now: NTP64) -> Result<(), UpdateError> {
+        if self.quarantine.is_none() {
+            return Ok(());
+        }
+        match lock!(self.peers).get(id) {
+            Some(usage) if self.is_quarantined_at(usage, now) => {
+                Err(UpdateError::PeerQuarantined(*id))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // Account for a rejected update from peer `id` at `now`, quarantining it if it exceeds the limit.
+    pub(crate) fn reject(&self, id: &ID, now: NTP64) {
+        if let Some(limit) = self.quarantine {
+            let mut peers = lock!(self.peers);
+            let usage = self.usage(&mut peers, id, now);
+            usage.rejections.refresh(now, limit.period);
+            usage.rejections.used += 1;
+            if usage.rejections.used > limit.max && !self.is_quarantined_at(usage, now) {
+                usage.quarantined = Some(now);
+            }
+        }
+    }
+
+    // Lift the quarantine of peer `id` at `now`, returning true if it was quarantined.
+    pub(crate) fn pardon(&self, id: &ID, now: NTP64) -> bool {
+        match lock!(self.peers).get_mut(id) {
+            Some(usage) => {
+                let quarantined = self.is_quarantined_at(usage, now);
+                usage.rejections = Window::default();
+                usage.quarantined = None;
+                quarantined
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn is_quarantined(&self, id: &ID, now: NTP64) -> bool {
+        self.check_quarantine(id, now).is_err()
+    }
+
+    // Check if an update with `msg_time` from peer `id` is acceptable at `now`, and if so account for it.
+    // Otherwise, returns the reason of the rejection.
+    pub(crate) fn accept(&self, id: &ID, msg_time: NTP64, now: NTP64) -> Result<(), UpdateError> {
+        if self.rate_limit.is_none() && self.drift_budget.is_none() {
+            return Ok(());
+        }
+        let drift = if msg_time > now {
+            msg_time - now
+        } else {
+            NTP64(0)
+        };
+
+        let mut peers = lock!(self.peers);
+        let usage = self.usage(&mut peers, id, now);
+        if let Some(limit) = self.rate_limit {
+            usage.updates.refresh(now, limit.period);
+            if usage.updates.used >= limit.max {
+                return Err(UpdateError::RateLimited {
+                    id: *id,
+                    max: limit.max,
+                    period: limit.period,
+                });
+            }
+        }
+        if let Some(limit) = self.drift_budget {
+            usage.drift.refresh(now, limit.period);
+            if usage.drift.used + drift > limit.max {
+                return Err(UpdateError::DriftBudgetExceeded {
+                    id: *id,
+                    budget: limit.max,
+                    period: limit.period,
+                    used: usage.drift.used,
+                    drift,
+                });
+            }
+            usage.drift.used = usage.drift.used + drift;
+        }
+        if self.rate_limit.is_some() {
+            usage.updates.used += 1;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::TryFrom;
+
+    #[test]
+    fn bounded_peers() {
+        let mut guard = PeerGuard::new();
+        guard.rate_limit = Some(Limit {
+            max: 1,
+            period: NTP64(100),
+        });
+        let id = |i: usize| ID::try_from(i as u64 + 1).unwrap();
+        let now = NTP64(1000);
+
+        // rotating IDs don't grow the tracked peers beyond the limit
+        for i in 0..2 * MAX_TRACKED_PEERS {
+            assert!(guard.accept(&id(i), NTP64(0), now).is_ok());
+            assert!(lock!(guard.peers).len() <= MAX_TRACKED_PEERS);
+        }
+        // the least recently seen peers were evicted, the latest ones are still limited
+        assert!(guard
+            .accept(&id(2 * MAX_TRACKED_PEERS - 1), NTP64(0), now)
+            .is_err());
+        assert!(guard.accept(&id(0), NTP64(0), now).is_ok());
+
+        // once their windows expired, the usages are dropped rather than the live ones
+        let later = now + NTP64(100);
+        assert!(guard.accept(&id(0), NTP64(0), later).is_ok());
+        assert!(guard.accept(&id(usize::MAX - 1), NTP64(0), later).is_ok());
+        assert_eq!(lock!(guard.peers).len(), 2);
+        assert!(guard.accept(&id(0), NTP64(0), later).is_err());
+    }
+
+    #[test]
+    fn bounded_quarantine() {
+        let mut guard = PeerGuard::new();
+        guard.quarantine = Some(Limit {
+            max: 0,
+            period: NTP64(100),
+        });
+        let id = |i: usize| ID::try_from(i as u64 + 1).unwrap();
+        let now = NTP64(1000);
+
+        // forged IDs don't grow the tracked peers beyond the limit
+        for i in 0..2 * MAX_TRACKED_PEERS {
+            guard.reject(&id(i), now);
+            assert!(lock!(guard.peers).len() <= MAX_TRACKED_PEERS);
+        }
+        assert!(guard.is_quarantined(&id(2 * MAX_TRACKED_PEERS - 1), now));
+
+        // the quarantine expires after its period, and the expired ones are dropped
+        let later = now + NTP64(100);
+        assert!(!guard.is_quarantined(&id(2 * MAX_TRACKED_PEERS - 1), later));
+        guard.reject(&id(usize::MAX - 1), later);
+        assert_eq!(lock!(guard.peers).len(), 1);
+        assert!(guard.is_quarantined(&id(usize::MAX - 1), later));
+        assert!(guard.pardon(&id(usize::MAX - 1), later));
+        assert!(!guard.is_quarantined(&id(usize::MAX - 1), later));
+    }
+}
@@ -0,0 +1,148 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{Timestamp, ID};
+
+// FNV-1a 64 bits parameters (see http://www.isthe.com/chongo/tech/comp/fnv/)
+const FNV1A_64_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV1A_64_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+// The FNV-1a 64 bits hash of `bytes`
+pub(crate) const fn fnv1a_64(bytes: &[u8]) -> u64 {
+    let mut hash = FNV1A_64_OFFSET_BASIS;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(FNV1A_64_PRIME);
+        i += 1;
+    }
+    hash
+}
+
+impl ID {
+    /// Returns a stable 64 bits hash of this [`ID`].
+    ///
+    /// Unlike [`core::hash::Hash`], this hash is guaranteed to remain the same across releases and platforms:
+    /// it's the [FNV-1a](http://www.isthe.com/chongo/tech/comp/fnv/) 64 bits hash of the 16 bytes
+    /// of the le-encoded [`ID`] (see [`ID::to_le_bytes()`]). Thus, it can also be computed in other languages.
+    #[inline]
+    pub fn stable_hash64(&self) -> u64 {
+        fnv1a_64(&self.to_le_bytes())
+    }
+
+    /// Returns the shard of this [`ID`] amongst `n` shards (i.e. a number in `0..n`),
+    /// as [`ID::stable_hash64()`] modulo `n`.
+    ///
+    /// # Panics
+    ///
+    /// If `n` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use uhlc::ID;
+    ///
+    /// let id: ID = "33".parse().unwrap();
+    /// assert_eq!(id.shard(8), 6);
+    /// ```
+    #[inline]
+    pub fn shard(&self, n: u32) -> u32 {
+        (self.stable_hash64() % u64::from(n)) as u32
+    }
+
+    /// Derive an [`ID`] from arbitrary identity material (e.g. an ed25519 public key),
+    /// so an HLC's identity can deterministically follow a cryptographic identity.
+    ///
+    /// This algorithm is guaranteed to remain the same across releases and platforms:
+    /// the [`ID`]'s le-encoded bytes are the first 16 bytes of the SHA-256 hash of `bytes`
+    /// (in the unlikely case they are all zeros, the first byte is set to 1).
+    /// Thus, it can also be computed in other languages.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use uhlc::ID;
+    ///
+    /// let public_key = [0x2a; 32];
+    /// let id = ID::from_public_key_hash(&public_key);
+    /// assert_eq!(id, ID::from_public_key_hash(&public_key));
+    /// assert_ne!(id, ID::from_public_key_hash(&[0x2b; 32]));
+    /// ```
+    #[cfg(feature = "pubkey-id")]
+    pub fn from_public_key_hash(bytes: &[u8]) -> ID {
+        use sha2::{Digest, Sha256};
+        let hash = Sha256::digest(bytes);
+        let mut id = [0u8; ID::MAX_SIZE];
+        id.copy_from_slice(&hash[..ID::MAX_SIZE]);
+        if id.iter().all(|b| *b == 0) {
+            id[0] = 1;
+        }
+        ID::from_le_bytes_checked(id).expect("non-zero hash")
+    }
+}
+
+impl Timestamp {
+    /// Returns a stable 64 bits hash of this [`Timestamp`].
+    ///
+    /// Unlike [`core::hash::Hash`], this hash is guaranteed to remain the same across releases and platforms:
+    /// it's the [FNV-1a](http://www.isthe.com/chongo/tech/comp/fnv/) 64 bits hash of the 24 bytes
+    /// of [`Timestamp::to_key()`] (i.e. the time as big endian followed by the le-encoded [`ID`]).
+    /// Thus, it can also be computed in other languages.
+    #[inline]
+    pub fn stable_hash64(&self) -> u64 {
+        fnv1a_64(&self.to_key())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fnv1a_64;
+    use crate::*;
+    use core::convert::TryFrom;
+
+    #[test]
+    fn stable_hash() {
+        // FNV-1a reference test vectors
+        assert_eq!(fnv1a_64(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a_64(b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv1a_64(b"foobar"), 0x85944171f73967e8);
+
+        // those values must never change across releases
+        let id = ID::try_from([0x33]).unwrap();
+        assert_eq!(id.stable_hash64(), 0xe73cd90042c2f896);
+        let ts = Timestamp::new(NTP64(7386690599959157260), id);
+        assert_eq!(ts.stable_hash64(), 0xd29f0bf2c79003b9);
+
+        for n in 1..100 {
+            let id = ID::rand();
+            assert!(id.shard(n) < n);
+            assert_eq!(id.shard(n), id.shard(n));
+        }
+    }
+
+    #[cfg(feature = "pubkey-id")]
+    #[test]
+    fn public_key_hash() {
+        // the first 16 bytes of the SHA-256 reference test vectors, that must never change across releases
+        let id = ID::from_public_key_hash(b"abc");
+        assert_eq!(
+            id.to_le_bytes(),
+            [
+                0xba, 0x78, 0x16, 0xbf, 0x8f, 0x01, 0xcf, 0xea, 0x41, 0x41, 0x40, 0xde, 0x5d, 0xae,
+                0x22, 0x23
+            ]
+        );
+        assert_eq!(id.to_string(), "2322ae5dde404141eacf018fbf1678ba");
+        assert_eq!(
+            ID::from_public_key_hash(b"").to_string(),
+            "24b96f99c8f4fb9a141cfc9842c4b0e3"
+        );
+    }
+}
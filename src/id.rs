@@ -8,10 +8,7 @@
 //
 // SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
 //
-use alloc::{
-    format,
-    string::{String, ToString},
-};
+use alloc::string::{String, ToString};
 use core::{
     convert::{TryFrom, TryInto},
     fmt,
@@ -38,11 +35,11 @@ use serde::{Deserialize, Serialize};
 /// let id1 = ID::try_from(&buf[..3]).unwrap();
 /// assert_eq!(id1.size(), 3);
 /// assert_eq!(id1.to_le_bytes(), buf);
-/// assert_eq!(&id1.to_le_bytes()[..id1.size()], &[0x1a, 0x2b, 0x3c]);
+/// assert_eq!(id1.significant_bytes(), &[0x1a, 0x2b, 0x3c]);
 /// let id2: ID = "3c2b1a".parse().unwrap();
 /// assert_eq!(id2.size(), 3);
 /// assert_eq!(id2.to_le_bytes(), buf);
-/// assert_eq!(&id2.to_le_bytes()[..id2.size()], &[0x1a, 0x2b, 0x3c]);
+/// assert_eq!(id2.significant_bytes(), &[0x1a, 0x2b, 0x3c]);
 /// assert_eq!(id2.to_string(), "3c2b1a");
 /// assert_eq!(id1, id2);
 /// ```
@@ -62,39 +59,195 @@ impl ID {
     /// The maximum size of an le-encoded [`ID`](`ID`) in bytes: 16.
     pub const MAX_SIZE: usize = u128::BITS as usize / 8;
 
+    /// The lowest [`ID`](`ID`), to build the lowest [`crate::Timestamp`] of a time.
+    ///
+    /// As the [`ID`](`ID`)s are ordered by their le-encoded bytes, it's `1 << 120` (i.e. its last byte is 1).
+    pub const MIN: ID = {
+        let mut bytes = [0u8; Self::MAX_SIZE];
+        bytes[Self::MAX_SIZE - 1] = 1;
+        ID(bytes)
+    };
+
+    /// The greatest [`ID`](`ID`) (i.e. `u128::MAX`), to build the greatest [`crate::Timestamp`] of a time.
+    pub const MAX: ID = ID([0xff; Self::MAX_SIZE]);
+
     /// The size of this [`ID`](`ID`) in bytes. I.e., the number of significant bytes of the le-encoded [`ID`](`ID`).
     #[inline]
-    pub fn size(&self) -> usize {
+    pub const fn size(&self) -> usize {
         Self::MAX_SIZE - (u128::from_le_bytes(self.0).leading_zeros() as usize / 8)
     }
 
-    /// This ID as bytes
+    /// The significant bytes of this [`ID`](`ID`), le-encoded. I.e., its first [`ID::size()`] bytes.
     ///
-    /// If you want to retrive a le-encoded slice of the [`ID`](`ID`), you can do it as follows:
     /// ```
     /// use uhlc::ID;
     /// use std::convert::TryFrom;
     ///
     /// let id = ID::try_from(&[0x01]).unwrap();
-    /// let slice = &id.to_le_bytes()[..id.size()];
+    /// let slice = id.significant_bytes();
     /// assert_eq!(1, slice.len());
     /// assert_eq!(&[0x01], slice);
     /// ```
     #[inline]
-    pub fn to_le_bytes(&self) -> [u8; Self::MAX_SIZE] {
+    pub fn significant_bytes(&self) -> &[u8] {
+        &self.0[..self.size()]
+    }
+
+    /// Returns an iterator over the significant bytes of this [`ID`](`ID`), le-encoded.
+    /// See [`ID::significant_bytes()`].
+    #[inline]
+    pub fn iter_significant_bytes(&self) -> core::iter::Copied<core::slice::Iter<'_, u8>> {
+        self.significant_bytes().iter().copied()
+    }
+
+    /// This ID as bytes
+    ///
+    /// To retrieve only the significant bytes of the [`ID`](`ID`), without copy,
+    /// use [`ID::significant_bytes()`].
+    #[inline]
+    pub const fn to_le_bytes(&self) -> [u8; Self::MAX_SIZE] {
         self.0
     }
 
+    /// Create an [`ID`] from its le-encoded bytes.
+    ///
+    /// This is the primary way to build an [`ID`] from bytes: contrary to the [`TryFrom`]
+    /// implementations, it doesn't depend on the length of `bytes` for type inference.
+    ///
+    /// Returns [`IdError::TooLong`] if `bytes` is longer than [`ID::MAX_SIZE`],
+    /// or [`IdError::Zero`] if its bytes are all zeros (or empty).
+    ///
+    /// ```
+    /// use uhlc::{IdError, ID};
+    ///
+    /// let id = ID::from_bytes(&[0x1a, 0x2b, 0x3c]).unwrap();
+    /// assert_eq!(id.significant_bytes(), &[0x1a, 0x2b, 0x3c]);
+    /// assert_eq!(ID::from_bytes(&[0, 0]), Err(IdError::Zero));
+    /// assert_eq!(ID::from_bytes(&[1; 17]), Err(IdError::TooLong(17)));
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<ID, IdError> {
+        let size = bytes.len();
+        if size > Self::MAX_SIZE {
+            return Err(IdError::TooLong(size));
+        }
+        let mut id = [0u8; ID::MAX_SIZE];
+        id[..size].copy_from_slice(bytes);
+        ID::from_le_bytes_checked(id).ok_or(IdError::Zero)
+    }
+
+    /// Create an [`ID`] from a [`NonZeroU128`], in `const` contexts.
+    ///
+    /// This is equivalent to [`ID::from()`], which can't be used in `const` contexts.
+    #[inline]
+    pub const fn from_nonzero_u128(id: NonZeroU128) -> ID {
+        ID(id.get().to_le_bytes())
+    }
+
+    // Create an [`ID`] from its le-encoded bytes, or returns None if they are all zeros.
+    pub(crate) const fn from_le_bytes_checked(bytes: [u8; Self::MAX_SIZE]) -> Option<ID> {
+        if u128::from_le_bytes(bytes) == 0 {
+            None
+        } else {
+            Some(ID(bytes))
+        }
+    }
+
+    /// The number of bytes of this [`ID`](`ID`) that can carry entropy, i.e. its [`ID::size()`].
+    ///
+    /// If the [`ID`](`ID`)s of a system are picked at random, it allows to assess their risk of collision
+    /// with [`collision_probability()`] (e.g. to alert when the configured IDs are too short for the cluster size).
+    #[inline]
+    pub const fn entropy_bytes(&self) -> usize {
+        self.size()
+    }
+
     /// Generate a random [`ID`](`ID`).
     #[inline]
     pub fn rand() -> Self {
         use rand::rngs::OsRng;
         let id: u128 = OsRng.gen_range(1..u128::MAX);
+        invariant!(id != 0, "ID invariant violated: random ID is zero");
         Self(id.to_le_bytes())
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+/// Returns the probability that at least 2 of `num_nodes` [`ID`]s picked uniformly at random
+/// on `id_bytes` bytes are the same.
+///
+/// It's computed exactly up to 100,000 nodes, and beyond with the birthday bound approximation:
+/// `1 - e^(-n(n-1) / 2^(8b+1))` (accurate for so many nodes).
+///
+/// # Examples
+/// ```
+/// use uhlc::{id::collision_probability, ID};
+///
+/// // ~1.2% for 10,000 nodes with 4 bytes IDs
+/// let p = collision_probability(10_000, 4);
+/// assert!(p > 0.011 && p < 0.012);
+/// // negligible for 16 bytes IDs
+/// assert!(collision_probability(1_000_000, ID::MAX.entropy_bytes()) < 1e-20);
+/// assert_eq!(collision_probability(1, 1), 0.0);
+/// ```
+#[cfg(feature = "std")]
+pub fn collision_probability(num_nodes: u64, id_bytes: usize) -> f64 {
+    let space = 2f64.powi(8 * id_bytes.min(i32::MAX as usize / 8) as i32);
+    if num_nodes as f64 > space {
+        return 1.0;
+    }
+    if num_nodes <= 100_000 {
+        // 1 - the probability that each node picks an ID different from the previous ones
+        let ln_unique: f64 = (1..num_nodes).map(|i| (-(i as f64) / space).ln_1p()).sum();
+        return -ln_unique.exp_m1();
+    }
+    let n = num_nodes as f64;
+    -(-n * (n - 1.0) / (2.0 * space)).exp_m1()
+}
+
+/// An identity type convertible to the [`ID`] of an [`crate::HLC`], e.g. a public key or a peer ID.
+///
+/// Implementing it for the application's identity types allows to configure an HLC directly with
+/// [`crate::HLCBuilder::with_id_of()`].
+///
+/// # Examples
+/// ```
+/// use std::convert::TryFrom;
+/// use uhlc::{HLCBuilder, NodeId, ID};
+///
+/// struct PeerId(u64);
+///
+/// impl NodeId for PeerId {
+///     fn to_uhlc_id(&self) -> ID {
+///         ID::try_from(self.0).expect("non-zero PeerId")
+///     }
+/// }
+///
+/// let hlc = HLCBuilder::new().with_id_of(&PeerId(42)).build();
+/// assert_eq!(*hlc.get_id(), ID::try_from(42u64).unwrap());
+/// ```
+pub trait NodeId {
+    /// The [`ID`] of this node, that must be unique in the system.
+    fn to_uhlc_id(&self) -> ID;
+}
+
+impl NodeId for ID {
+    fn to_uhlc_id(&self) -> ID {
+        *self
+    }
+}
+
+impl<const N: usize> NodeId for crate::IdN<N> {
+    fn to_uhlc_id(&self) -> ID {
+        self.to_id()
+    }
+}
+
+impl<T: NodeId + ?Sized> NodeId for &T {
+    fn to_uhlc_id(&self) -> ID {
+        (**self).to_uhlc_id()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct SizeError(pub usize);
 impl fmt::Display for SizeError {
@@ -108,8 +261,48 @@ impl fmt::Display for SizeError {
     }
 }
 
-#[cfg(feature = "std")]
-impl std::error::Error for SizeError {}
+impl core::error::Error for SizeError {}
+
+/// An error returned by [`ID::from_bytes()`] and the conversions to [`crate::IdN`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum IdError {
+    /// The bytes are longer than [`ID::MAX_SIZE`].
+    TooLong(usize),
+    /// The bytes are all zeros.
+    Zero,
+    /// The [`ID`] has more significant bytes (`size`) than an [`crate::IdN`] can store (`max`).
+    TooWide { size: usize, max: usize },
+}
+
+impl fmt::Display for IdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdError::TooLong(size) => write!(
+                f,
+                "Maximum ID size ({} bytes) exceeded: {}",
+                ID::MAX_SIZE,
+                size
+            ),
+            IdError::Zero => write!(f, "Invalid zero ID"),
+            IdError::TooWide { size, max } => {
+                write!(f, "ID of {} bytes doesn't fit in {} bytes", size, max)
+            }
+        }
+    }
+}
+
+impl core::error::Error for IdError {}
+
+impl From<IdError> for SizeError {
+    fn from(e: IdError) -> Self {
+        match e {
+            IdError::TooLong(size) => SizeError(size),
+            IdError::Zero => SizeError(0),
+            IdError::TooWide { size, .. } => SizeError(size),
+        }
+    }
+}
 
 macro_rules! impl_from_sized_slice_for_id {
     ($N: expr) => {
@@ -119,13 +312,7 @@ macro_rules! impl_from_sized_slice_for_id {
             /// Performs the conversion.
             /// NOTE: the bytes slice is interpreted as little endian
             fn try_from(value: &[u8; $N]) -> Result<Self, Self::Error> {
-                let mut id = [0u8; ID::MAX_SIZE];
-                id[..$N].copy_from_slice(value);
-                let id = u128::from_le_bytes(id);
-                match NonZeroU128::new(id) {
-                    Some(_) => Ok(Self(id.to_le_bytes())),
-                    None => Err(SizeError(0)),
-                }
+                ID::from_bytes(value).map_err(SizeError::from)
             }
         }
 
@@ -163,17 +350,7 @@ impl TryFrom<&[u8]> for ID {
     /// Performs the conversion.  
     /// NOTE: the bytes slice is interpreted as little endian
     fn try_from(slice: &[u8]) -> Result<Self, Self::Error> {
-        let size = slice.len();
-        if size > Self::MAX_SIZE {
-            return Err(SizeError(size));
-        }
-        let mut id = [0u8; ID::MAX_SIZE];
-        id[..size].copy_from_slice(slice);
-        let id = u128::from_le_bytes(id);
-        match NonZeroU128::new(id) {
-            Some(_) => Ok(Self(id.to_le_bytes())),
-            None => Err(SizeError(0)),
-        }
+        ID::from_bytes(slice).map_err(SizeError::from)
     }
 }
 
@@ -243,7 +420,31 @@ impl TryFrom<u128> for ID {
 
 impl From<NonZeroU128> for ID {
     fn from(id: NonZeroU128) -> Self {
-        Self(id.get().to_le_bytes())
+        ID::from_nonzero_u128(id)
+    }
+}
+
+impl ID {
+    /// Parse an [`ID`] in hexadecimal format (see [`ID::from_str()`]) at the start of `s`,
+    /// returning it with the remaining input. The [`ID`] is made of all the hexadecimal digits
+    /// at the start of `s`, so it must be followed by a non-hexadecimal character (or the end of `s`).
+    ///
+    /// # Examples
+    /// ```
+    /// use uhlc::ID;
+    ///
+    /// let (id, rest) = ID::parse_prefix("3c2b1a: started").unwrap();
+    /// assert_eq!(id, "3c2b1a".parse().unwrap());
+    /// assert_eq!(rest, ": started");
+    /// assert!(ID::parse_prefix(": started").is_err());
+    /// ```
+    pub fn parse_prefix(s: &str) -> Result<(ID, &str), ParseIDError> {
+        let end = s
+            .bytes()
+            .position(|c| !c.is_ascii_hexdigit())
+            .unwrap_or(s.len());
+        let (hex, rest) = s.split_at(end);
+        ID::from_str(hex).map(|id| (id, rest))
     }
 }
 
@@ -278,12 +479,49 @@ pub struct ParseIDError {
     pub cause: String,
 }
 
+impl fmt::Display for ParseIDError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid ID: {}", self.cause)
+    }
+}
+
+impl core::error::Error for ParseIDError {}
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+impl ID {
+    /// Write this [`ID`] as lowercase hexadecimal digits without leading zeros
+    /// (i.e. as it's displayed), without any allocation.
+    ///
+    /// # Examples
+    /// ```
+    /// use uhlc::ID;
+    /// use std::convert::TryFrom;
+    ///
+    /// let id = ID::try_from([0xab, 0x0c]).unwrap();
+    /// let mut s = String::new();
+    /// id.write_hex(&mut s).unwrap();
+    /// assert_eq!(s, "cab");
+    /// ```
+    pub fn write_hex<W: fmt::Write + ?Sized>(&self, w: &mut W) -> fmt::Result {
+        let id = u128::from_le_bytes(self.0);
+        let mut digits = [0u8; 2 * Self::MAX_SIZE];
+        for (i, digit) in digits.iter_mut().enumerate() {
+            let shift = 4 * (2 * Self::MAX_SIZE - 1 - i);
+            *digit = HEX_DIGITS[((id >> shift) & 0xf) as usize];
+        }
+        // an ID is never 0, so there is at least 1 significant digit
+        let start = (id.leading_zeros() / 4) as usize;
+        match core::str::from_utf8(&digits[start..]) {
+            Ok(s) => w.write_str(s),
+            Err(_) => Err(fmt::Error),
+        }
+    }
+}
+
 impl fmt::Debug for ID {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let id = u128::from_le_bytes(self.0);
-        let s = format!("{:02x}", id);
-        let t = s.as_str().strip_prefix('0').unwrap_or(s.as_str());
-        write!(f, "{}", t)
+        self.write_hex(f)
     }
 }
 
@@ -293,7 +531,48 @@ impl fmt::Display for ID {
     }
 }
 
+/// Formats the [`ID`] as a `u128` in hexadecimal, honoring all the formatting flags
+/// (e.g. `{:#034x}`), unlike [`fmt::Display`].
+impl fmt::LowerHex for ID {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&u128::from_le_bytes(self.0), f)
+    }
+}
+
+impl fmt::UpperHex for ID {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::UpperHex::fmt(&u128::from_le_bytes(self.0), f)
+    }
+}
+
+/// Formats the [`ID`] as a `u128` in binary, honoring all the formatting flags.
+impl fmt::Binary for ID {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Binary::fmt(&u128::from_le_bytes(self.0), f)
+    }
+}
+
 mod tests {
+    #[test]
+    fn errors() {
+        fn boxed<E: core::error::Error + Send + Sync + 'static>(
+            e: E,
+        ) -> alloc::boxed::Box<dyn core::error::Error + Send + Sync> {
+            alloc::boxed::Box::new(e)
+        }
+
+        let e = boxed("0x1".parse::<crate::ID>().unwrap_err());
+        assert_eq!(e.to_string(), "Invalid ID: Leading 0s are not valid");
+        let e = boxed(
+            <crate::ID as core::convert::TryFrom<&[u8]>>::try_from(&[0u8; 17][..]).unwrap_err(),
+        );
+        assert_eq!(e.to_string(), "Maximum ID size (16 bytes) exceeded: 17");
+        let e = boxed("x".parse::<crate::NTP64>().unwrap_err());
+        assert!(e.to_string().starts_with("Invalid NTP64: "));
+        let e = boxed("1".parse::<crate::Timestamp>().unwrap_err());
+        assert_eq!(e.to_string(), "Invalid Timestamp: No '/' found in String");
+    }
+
     #[test]
     fn parse_display() {
         let id = "1".parse::<crate::ID>().unwrap();
@@ -316,10 +595,93 @@ mod tests {
             .unwrap();
         assert_eq!(id.to_string(), "6bd9cb5f9f2644508fbbb0df1d6cce3a");
 
+        for _ in 0..100 {
+            let id = crate::ID::rand();
+            let hex = alloc::format!("{:x}", u128::from_le_bytes(id.to_le_bytes()));
+            assert_eq!(id.to_string(), hex);
+        }
+
         "0".parse::<crate::ID>().unwrap_err();
         "0bcd".parse::<crate::ID>().unwrap_err();
         "6bd9cb5f9f2644508fbbb0df1d6cce3a0"
             .parse::<crate::ID>()
             .unwrap_err();
     }
+
+    #[test]
+    fn significant_bytes() {
+        use core::convert::TryFrom;
+
+        let id = crate::ID::try_from([0x1a, 0x2b, 0x3c]).unwrap();
+        assert_eq!(id.significant_bytes(), &[0x1a, 0x2b, 0x3c]);
+        assert_eq!(id.iter_significant_bytes().len(), id.size());
+        assert!(id
+            .iter_significant_bytes()
+            .eq([0x1a, 0x2b, 0x3c].iter().copied()));
+
+        let id = crate::ID::rand();
+        assert_eq!(id.significant_bytes(), &id.to_le_bytes()[..id.size()]);
+        assert_eq!(crate::ID::try_from(id.significant_bytes()).unwrap(), id);
+    }
+
+    #[test]
+    fn from_bytes() {
+        use crate::{IdError, ID};
+
+        let id = ID::from_bytes(&[0x01, 0x00]).unwrap();
+        assert_eq!(id.size(), 1);
+        assert_eq!(ID::from_bytes(&[]), Err(IdError::Zero));
+        assert_eq!(ID::from_bytes(&[0; 16]), Err(IdError::Zero));
+        assert_eq!(ID::from_bytes(&[1; 17]), Err(IdError::TooLong(17)));
+        assert_eq!(ID::from_bytes(&[0xff; 16]).unwrap().size(), ID::MAX_SIZE);
+    }
+
+    #[test]
+    fn hex_binary() {
+        let id = crate::ID::from_bytes(&[0xab, 0x01]).unwrap();
+        assert_eq!(alloc::format!("{id:x}"), "1ab");
+        assert_eq!(alloc::format!("{id:X}"), "1AB");
+        assert_eq!(alloc::format!("{id:#08x}"), "0x0001ab");
+        assert_eq!(alloc::format!("{id:b}"), "110101011");
+        assert_eq!(alloc::format!("{id:x}"), id.to_string());
+    }
+
+    #[test]
+    fn node_id() {
+        use crate::*;
+
+        struct PublicKey([u8; 4]);
+        impl NodeId for PublicKey {
+            fn to_uhlc_id(&self) -> ID {
+                ID::from_bytes(&self.0).unwrap()
+            }
+        }
+
+        let key = PublicKey([0x01, 0x02, 0x03, 0x04]);
+        let hlc = HLCBuilder::new().with_id_of(&key).build();
+        assert_eq!(hlc.get_id().significant_bytes(), &key.0);
+        let small = IdN::<2>::rand();
+        let hlc = HLCBuilder::new().with_id_of(&&small).build();
+        assert_eq!(*hlc.get_id(), ID::from(small));
+        let id = ID::rand();
+        assert_eq!(HLCBuilder::new().with_id_of(&id).build().get_id(), &id);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn collision_probability() {
+        use crate::id::collision_probability;
+        use crate::*;
+
+        assert_eq!(collision_probability(0, 4), 0.0);
+        assert_eq!(collision_probability(1, 0), 0.0);
+        assert_eq!(collision_probability(2, 0), 1.0);
+        // 2 nodes with 1 byte IDs: 1/256
+        assert!((collision_probability(2, 1) - 1.0 / 256.0).abs() < 1e-12);
+        // ~50% for 77164 nodes with 4 bytes IDs
+        assert!((collision_probability(77_164, 4) - 0.5).abs() < 1e-3);
+        assert!(collision_probability(1000, 4) > collision_probability(1000, 5));
+        assert!(collision_probability(u64::MAX, usize::MAX) >= 0.0);
+        assert_eq!(ID::from_bytes(&[1, 2, 3]).unwrap().entropy_bytes(), 3);
+    }
 }
@@ -0,0 +1,237 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{IdError, Timestamp, ID, NTP64};
+use core::{convert::TryFrom, fmt};
+use rand::Rng;
+
+/// An [`ID`] stored on `N` bytes only (from 1 to [`ID::MAX_SIZE`]), for memory-constrained targets.
+///
+/// It's ordered as the [`ID`] it converts to, which is always possible (with [`ID::from()`]).
+/// The opposite conversion (with [`IdN::try_from()`]) fails if the [`ID`] has more than `N` significant bytes.
+///
+/// # Examples
+/// ```
+/// use std::convert::TryFrom;
+/// use uhlc::{IdN, ID};
+///
+/// let id = ID::try_from([0x01, 0x02]).unwrap();
+/// let small = IdN::<4>::try_from(id).unwrap();
+/// assert_eq!(ID::from(small), id);
+/// assert!(IdN::<1>::try_from(id).is_err());
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct IdN<const N: usize>([u8; N]);
+
+impl<const N: usize> IdN<N> {
+    // Fails the build if N is out of bounds
+    const VALID_SIZE: () = assert!(
+        N > 0 && N <= ID::MAX_SIZE,
+        "the size of an IdN must be from 1 to 16 bytes"
+    );
+
+    /// The number of bytes of this [`IdN`].
+    pub const SIZE: usize = N;
+
+    /// Create an [`IdN`] from its le-encoded bytes, or returns [`IdError::Zero`] if they are all zeros.
+    pub fn from_le_bytes(bytes: [u8; N]) -> Result<Self, IdError> {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID_SIZE;
+        if bytes.iter().all(|b| *b == 0) {
+            Err(IdError::Zero)
+        } else {
+            Ok(IdN(bytes))
+        }
+    }
+
+    /// This [`IdN`] as bytes, in little-endian.
+    #[inline]
+    pub const fn to_le_bytes(&self) -> [u8; N] {
+        self.0
+    }
+
+    /// Convert to an [`ID`].
+    #[inline]
+    pub fn to_id(&self) -> ID {
+        let mut bytes = [0u8; ID::MAX_SIZE];
+        bytes[..N].copy_from_slice(&self.0);
+        ID::from_le_bytes_checked(bytes).expect("IdN is non-zero")
+    }
+
+    /// Generate a random [`IdN`].
+    pub fn rand() -> Self {
+        use rand::rngs::OsRng;
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID_SIZE;
+        let mut bytes = [0u8; N];
+        while bytes.iter().all(|b| *b == 0) {
+            OsRng.fill(&mut bytes[..]);
+        }
+        IdN(bytes)
+    }
+}
+
+impl<const N: usize> From<IdN<N>> for ID {
+    fn from(id: IdN<N>) -> Self {
+        id.to_id()
+    }
+}
+
+impl<const N: usize> TryFrom<ID> for IdN<N> {
+    type Error = IdError;
+
+    /// Returns [`IdError::TooWide`] if the [`ID`] has more than `N` significant bytes.
+    fn try_from(id: ID) -> Result<Self, IdError> {
+        let size = id.size();
+        if size > N {
+            return Err(IdError::TooWide { size, max: N });
+        }
+        let mut bytes = [0u8; N];
+        bytes.copy_from_slice(&id.to_le_bytes()[..N]);
+        IdN::from_le_bytes(bytes)
+    }
+}
+
+impl<const N: usize> fmt::Debug for IdN<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.to_id(), f)
+    }
+}
+
+impl<const N: usize> fmt::Display for IdN<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.to_id(), f)
+    }
+}
+
+/// A [`Timestamp`] with its [`ID`] stored on `N` bytes only (see [`IdN`]), for memory-constrained targets.
+///
+/// Its time is stored as 2 `u32` so it's 4 bytes aligned: with `N = 4` it takes 12 bytes, half of a [`Timestamp`].
+/// It's ordered as the [`Timestamp`] it converts to.
+///
+/// # Examples
+/// ```
+/// use std::convert::TryFrom;
+/// use uhlc::{HLCBuilder, SmallTimestamp, Timestamp, ID};
+///
+/// let hlc = HLCBuilder::new().with_id(ID::try_from([0x01, 0x02, 0x03, 0x04]).unwrap()).build();
+/// let ts = hlc.new_timestamp();
+/// let small = SmallTimestamp::<4>::try_from(ts).unwrap();
+/// assert_eq!(core::mem::size_of_val(&small), 12);
+/// assert_eq!(Timestamp::from(small), ts);
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SmallTimestamp<const N: usize> {
+    // the Seconds and Fraction parts of the NTP64 time
+    time: [u32; 2],
+    id: IdN<N>,
+}
+
+impl<const N: usize> SmallTimestamp<N> {
+    /// Create a [`SmallTimestamp`] with a [`NTP64`] and an [`IdN`].
+    #[inline]
+    pub const fn new(time: NTP64, id: IdN<N>) -> Self {
+        SmallTimestamp {
+            time: [(time.0 >> 32) as u32, time.0 as u32],
+            id,
+        }
+    }
+
+    /// Returns the [`NTP64`] time.
+    #[inline]
+    pub const fn get_time(&self) -> NTP64 {
+        NTP64(((self.time[0] as u64) << 32) | self.time[1] as u64)
+    }
+
+    /// Returns the [`IdN`].
+    #[inline]
+    pub const fn get_id(&self) -> &IdN<N> {
+        &self.id
+    }
+}
+
+impl<const N: usize> From<SmallTimestamp<N>> for Timestamp {
+    fn from(ts: SmallTimestamp<N>) -> Self {
+        Timestamp::new(ts.get_time(), ts.id.to_id())
+    }
+}
+
+impl<const N: usize> TryFrom<Timestamp> for SmallTimestamp<N> {
+    type Error = IdError;
+
+    /// Returns [`IdError::TooWide`] if the [`ID`] of the [`Timestamp`] has more than `N` significant bytes.
+    fn try_from(ts: Timestamp) -> Result<Self, IdError> {
+        Ok(SmallTimestamp::new(
+            *ts.get_time(),
+            IdN::try_from(*ts.get_id())?,
+        ))
+    }
+}
+
+impl<const N: usize> fmt::Debug for SmallTimestamp<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}/{:?}", self.get_time(), self.id)
+    }
+}
+
+impl<const N: usize> fmt::Display for SmallTimestamp<N> {
+    /// Formats as the [`Timestamp`] it converts to (see [`Timestamp`]'s [`fmt::Display`]).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&Timestamp::from(*self), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use core::convert::TryFrom;
+
+    #[test]
+    fn id_n() {
+        let id = ID::try_from([0x01, 0x02, 0x03]).unwrap();
+        let small = IdN::<3>::try_from(id).unwrap();
+        assert_eq!(small.to_le_bytes(), [0x01, 0x02, 0x03]);
+        assert_eq!(ID::from(small), id);
+        assert_eq!(small.to_string(), id.to_string());
+        assert_eq!(
+            IdN::<2>::try_from(id),
+            Err(IdError::TooWide { size: 3, max: 2 })
+        );
+        assert_eq!(IdN::<2>::from_le_bytes([0, 0]), Err(IdError::Zero));
+        assert_ne!(ID::from(IdN::<4>::rand()).size(), 0);
+
+        // ordered as the IDs
+        let mut ids: Vec<IdN<4>> = (0..100).map(|_| IdN::rand()).collect();
+        ids.sort();
+        assert!(ids.windows(2).all(|w| ID::from(w[0]) <= ID::from(w[1])));
+    }
+
+    #[test]
+    fn small_timestamp() {
+        let hlc = HLCBuilder::new().with_id(IdN::<4>::rand().into()).build();
+        let mut prev = SmallTimestamp::<4>::try_from(hlc.new_timestamp()).unwrap();
+        assert_eq!(core::mem::size_of::<SmallTimestamp<4>>(), 12);
+        for _ in 0..100 {
+            let ts = hlc.new_timestamp();
+            let small = SmallTimestamp::<4>::try_from(ts).unwrap();
+            assert_eq!(Timestamp::from(small), ts);
+            assert_eq!(small.to_string(), ts.to_string());
+            assert!(small > prev);
+            prev = small;
+        }
+        let ts = Timestamp::new(NTP64(1), ID::MAX);
+        assert_eq!(
+            SmallTimestamp::<15>::try_from(ts),
+            Err(IdError::TooWide { size: 16, max: 15 })
+        );
+    }
+}
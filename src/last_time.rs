@@ -0,0 +1,130 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::NTP64;
+
+#[cfg(all(target_has_atomic = "64", not(uhlc_loom)))]
+use core::sync::atomic::{AtomicU64, Ordering};
+
+// With `--cfg uhlc_loom`, the atomic is modeled by loom to check all the possible interleavings
+// of the concurrent accesses (see tests/loom.rs)
+#[cfg(all(target_has_atomic = "64", uhlc_loom))]
+use loom::sync::atomic::{AtomicU64, Ordering};
+
+#[cfg(not(target_has_atomic = "64"))]
+use super::Mutex;
+
+/// The latest time of an [`crate::HLC`].
+///
+/// On targets supporting 64 bits atomics, it's lock-free: an update is an optimistic read
+/// of the current value, published with a compare-and-swap (retried only if another thread
+/// updated the value in the meantime). Otherwise, it's protected by a mutex.
+pub(crate) struct LastTime {
+    #[cfg(target_has_atomic = "64")]
+    time: AtomicU64,
+    #[cfg(not(target_has_atomic = "64"))]
+    time: Mutex<NTP64>,
+}
+
+impl LastTime {
+    pub(crate) fn new(time: NTP64) -> LastTime {
+        LastTime {
+            #[cfg(target_has_atomic = "64")]
+            time: AtomicU64::new(time.0),
+            #[cfg(not(target_has_atomic = "64"))]
+            time: Mutex::new(time),
+        }
+    }
+
+    #[cfg(target_has_atomic = "64")]
+    pub(crate) fn get(&self) -> NTP64 {
+        NTP64(self.time.load(Ordering::Acquire))
+    }
+
+    #[cfg(not(target_has_atomic = "64"))]
+    pub(crate) fn get(&self) -> NTP64 {
+        *lock!(self.time)
+    }
+
+    // Read the latest time without blocking, or None if it's concurrently updated
+    #[cfg(target_has_atomic = "64")]
+    pub(crate) fn try_get(&self) -> Option<NTP64> {
+        Some(self.get())
+    }
+
+    #[cfg(all(not(target_has_atomic = "64"), feature = "std"))]
+    pub(crate) fn try_get(&self) -> Option<NTP64> {
+        self.time.try_lock().ok().map(|time| *time)
+    }
+
+    #[cfg(all(not(target_has_atomic = "64"), not(feature = "std")))]
+    pub(crate) fn try_get(&self) -> Option<NTP64> {
+        self.time.try_lock().map(|time| *time)
+    }
+
+    // Replace the latest time with `f(latest time)`, returning the new latest time.
+    // `f` must be a pure function, as it might be called several times under contention.
+    #[cfg(target_has_atomic = "64")]
+    pub(crate) fn update<F: Fn(NTP64) -> NTP64>(&self, f: F) -> NTP64 {
+        let mut current = self.time.load(Ordering::Acquire);
+        loop {
+            let new = f(NTP64(current));
+            match self.time.compare_exchange_weak(
+                current,
+                new.0,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return new,
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    #[cfg(not(target_has_atomic = "64"))]
+    pub(crate) fn update<F: Fn(NTP64) -> NTP64>(&self, f: F) -> NTP64 {
+        let mut time = lock!(self.time);
+        *time = f(*time);
+        *time
+    }
+
+    // Replace the latest time with `f(latest time)` if it's not None, returning the new latest time.
+    // Otherwise the latest time is unchanged and None is returned.
+    // `f` must be a pure function, as it might be called several times under contention.
+    #[cfg(target_has_atomic = "64")]
+    pub(crate) fn try_update<F: Fn(NTP64) -> Option<NTP64>>(&self, f: F) -> Option<NTP64> {
+        let mut current = self.time.load(Ordering::Acquire);
+        loop {
+            let new = f(NTP64(current))?;
+            match self.time.compare_exchange_weak(
+                current,
+                new.0,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return Some(new),
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    #[cfg(not(target_has_atomic = "64"))]
+    pub(crate) fn try_update<F: Fn(NTP64) -> Option<NTP64>>(&self, f: F) -> Option<NTP64> {
+        let mut time = lock!(self.time);
+        *time = f(*time)?;
+        Some(*time)
+    }
+}
+
+impl Default for LastTime {
+    fn default() -> Self {
+        LastTime::new(NTP64(0))
+    }
+}
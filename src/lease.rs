@@ -0,0 +1,143 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{Timestamp, HLC, NTP64};
+use core::time::Duration;
+use serde::{Deserialize, Serialize};
+
+/// A lease (or a TTL) starting at a [`Timestamp`] and lasting for a [`Duration`].
+///
+/// The expiration of the lease is evaluated against the current time of an [`HLC`]
+/// (i.e. the latest of its physical time and of its latest issued or received timestamp),
+/// without generating any new [`Timestamp`].
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use uhlc::{Lease, HLC};
+///
+/// let hlc = HLC::default();
+/// let lease = Lease::new(hlc.new_timestamp(), Duration::from_secs(10));
+/// assert!(!lease.is_expired(&hlc));
+/// assert!(lease.remaining(&hlc) <= Duration::from_secs(10));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Lease {
+    start: Timestamp,
+    duration: NTP64,
+}
+
+impl Lease {
+    /// Create a new [`Lease`] starting at `start` and lasting for `duration`.
+    ///
+    /// A `duration` exceeding the range of a [`NTP64`] (~136 years) is saturated: such a lease never expires.
+    pub fn new(start: Timestamp, duration: Duration) -> Lease {
+        Lease {
+            start,
+            duration: NTP64::try_from_duration(duration).unwrap_or(NTP64(u64::MAX)),
+        }
+    }
+
+    /// Returns the [`Timestamp`] at which this lease started.
+    #[inline]
+    pub fn get_start(&self) -> &Timestamp {
+        &self.start
+    }
+
+    /// Returns the duration of this lease.
+    #[inline]
+    pub fn get_duration(&self) -> Duration {
+        self.duration.to_duration()
+    }
+
+    /// Returns the time at which this lease expires,
+    /// saturated to the maximum [`NTP64`] if it's after the end of its range (i.e. it never expires).
+    #[inline]
+    pub fn get_expiry(&self) -> NTP64 {
+        NTP64(self.start.get_time().0.saturating_add(self.duration.0))
+    }
+
+    /// Returns `true` if this lease is expired according to the current time of `hlc`.
+    pub fn is_expired(&self, hlc: &HLC) -> bool {
+        hlc.current_time() >= self.get_expiry()
+    }
+
+    /// Returns the remaining duration of this lease according to the current time of `hlc`,
+    /// or [`Duration::ZERO`] if it is expired.
+    pub fn remaining(&self, hlc: &HLC) -> Duration {
+        let now = hlc.current_time();
+        let expiry = self.get_expiry();
+        if now < expiry {
+            (expiry - now).to_duration()
+        } else {
+            Duration::ZERO
+        }
+    }
+
+    /// Returns a new [`Lease`] with the same duration, starting at `start` (typically a new [`Timestamp`]).
+    pub fn renew(&self, start: Timestamp) -> Lease {
+        Lease {
+            start,
+            duration: self.duration,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use core::time::Duration;
+
+    #[test]
+    fn lease() {
+        let hlc = HLCBuilder::new().with_clock(zero_clock).build();
+        let lease = Lease::new(hlc.new_timestamp(), Duration::from_secs(1));
+        assert!(!lease.is_expired(&hlc));
+        assert!(lease.remaining(&hlc) > Duration::from_millis(999));
+        assert!(lease.remaining(&hlc) < Duration::from_millis(1001));
+
+        // receiving a timestamp beyond the expiry expires the lease
+        let other = HLCBuilder::new()
+            .with_clock(|| NTP64::from(Duration::from_millis(1200)))
+            .build();
+        let hlc = HLCBuilder::new()
+            .with_clock(zero_clock)
+            .with_max_delta(Duration::from_secs(2))
+            .build();
+        let lease = Lease::new(hlc.new_timestamp(), Duration::from_secs(1));
+        hlc.update_with_timestamp(&other.new_timestamp()).unwrap();
+        assert!(lease.is_expired(&hlc));
+        assert_eq!(lease.remaining(&hlc), Duration::ZERO);
+
+        let renewed = lease.renew(hlc.new_timestamp());
+        assert!(!renewed.is_expired(&hlc));
+        assert_eq!(renewed.get_duration(), lease.get_duration());
+
+        let json = serde_json::to_string(&renewed).unwrap();
+        assert_eq!(serde_json::from_str::<Lease>(&json).unwrap(), renewed);
+    }
+
+    #[test]
+    fn lease_saturation() {
+        let hlc = HLCBuilder::new().with_clock(zero_clock).build();
+        // a duration beyond the NTP64 range never expires
+        let lease = Lease::new(hlc.new_timestamp(), Duration::MAX);
+        assert_eq!(lease.get_expiry(), NTP64(u64::MAX));
+        assert!(!lease.is_expired(&hlc));
+
+        // nor does a lease whose expiry is beyond the end of the NTP64 range, instead of wrapping
+        let late = Timestamp::new(NTP64(u64::MAX - 10), *hlc.get_id());
+        let lease = Lease::new(late, Duration::from_secs(1));
+        assert_eq!(lease.get_expiry(), NTP64(u64::MAX));
+        assert!(!lease.is_expired(&hlc));
+    }
+}
@@ -53,43 +53,222 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 extern crate alloc;
 
-use alloc::{format, string::String};
+use alloc::sync::Arc;
 use core::cmp;
+use core::fmt;
+use core::sync::atomic::{AtomicPtr, Ordering};
 use core::time::Duration;
 
 #[cfg(feature = "std")]
 use {
     lazy_static::lazy_static,
-    std::env::var,
     std::sync::Mutex,
-    std::time::{SystemTime, UNIX_EPOCH},
+    std::time::{Instant, SystemTime, UNIX_EPOCH},
 };
 
 #[cfg(not(feature = "std"))]
 use spin::Mutex; // No_std-friendly alternative to std::sync::Mutex
 
-mod id;
+// Check an internal invariant in debug builds, and also in release builds with the `paranoid` feature.
+macro_rules! invariant {
+    ($cond:expr, $($msg:tt)+) => {
+        if cfg!(any(debug_assertions, feature = "paranoid")) {
+            assert!($cond, $($msg)+);
+        }
+    };
+}
+
+/// The [`ID`] of an [`HLC`], and the helpers to choose it.
+pub mod id;
 pub use id::*;
 
+mod id_n;
+pub use id_n::*;
+
 mod ntp64;
 pub use ntp64::*;
 
 mod timestamp;
 pub use timestamp::*;
 
-/// The size of counter part in [`NTP64`] (in bits)
-pub const CSIZE: u8 = 4u8;
-// Bit-mask of the counter part within the 64 bits time
-const CMASK: u64 = (1u64 << CSIZE) - 1u64;
-// Bit-mask of the logical clock part within the 64 bits time
-const LMASK: u64 = !CMASK;
+mod epoch;
+pub use epoch::*;
+
+mod storage_keys;
+pub use storage_keys::*;
+
+mod domain;
+pub use domain::*;
+
+mod range;
+pub use range::*;
+
+mod reservation;
+pub use reservation::*;
+
+mod certificate;
+pub use certificate::*;
+
+mod format;
+pub use format::*;
+
+mod error;
+pub use error::*;
+
+mod rate;
+pub use rate::*;
+
+mod lease;
+pub use lease::*;
+
+mod fencing;
+pub use fencing::*;
+
+mod monotonic;
+pub use monotonic::*;
+
+mod lww;
+pub use lww::*;
+
+mod timestamped_log;
+pub use timestamped_log::*;
+
+mod merge;
+pub use merge::*;
+
+mod sequence;
+pub use sequence::*;
+
+mod digest;
+pub use digest::*;
+
+mod clock_sync;
+pub use clock_sync::*;
+
+mod model;
+pub use model::*;
+
+mod commit_wait;
+
+/// Alternative serde representations, to use with `#[serde(with = "...")]`.
+pub mod serialization;
+
+/// Conformance assertions on [`Timestamp`]s, to check the HLC invariants on end-to-end pipelines.
+pub mod testkit;
+
+mod hash;
+
+mod short_id;
+
+#[cfg(feature = "std")]
+mod utc_offset;
+#[cfg(feature = "std")]
+pub use utc_offset::*;
+
+#[cfg(feature = "std")]
+mod env_config;
+#[cfg(feature = "std")]
+pub use env_config::*;
+
+#[cfg(feature = "std")]
+mod config;
+#[cfg(feature = "std")]
+pub use config::*;
+
+#[cfg(feature = "std")]
+mod calibration;
+#[cfg(feature = "std")]
+pub use calibration::*;
+
+#[cfg(feature = "std")]
+mod self_test;
+#[cfg(feature = "std")]
+pub use self_test::*;
+
+#[cfg(feature = "std")]
+mod deadline;
+
+#[cfg(feature = "arrow")]
+mod arrow;
+#[cfg(feature = "arrow")]
+pub use arrow::*;
+
+#[cfg(feature = "sqlx-postgres")]
+mod postgres;
+
+#[cfg(feature = "otel")]
+mod otel;
+#[cfg(feature = "otel")]
+pub use otel::*;
+
+#[cfg(feature = "tracing-subscriber")]
+mod tracing_timer;
+#[cfg(feature = "tracing-subscriber")]
+pub use tracing_timer::*;
+
+#[cfg(any(feature = "slog", feature = "log-kv"))]
+mod structured_log;
+
+#[cfg(feature = "signed")]
+mod signed;
+#[cfg(feature = "signed")]
+pub use signed::*;
+
+#[cfg(feature = "async")]
+mod stream;
+#[cfg(feature = "async")]
+pub use stream::*;
+
+#[cfg(any(feature = "zerocopy", feature = "bytemuck"))]
+mod raw;
+#[cfg(any(feature = "zerocopy", feature = "bytemuck"))]
+pub use raw::*;
+
+#[cfg(feature = "turmoil")]
+mod turmoil_clock;
+#[cfg(feature = "turmoil")]
+pub use turmoil_clock::*;
+
+#[cfg(feature = "rmp")]
+mod msgpack;
+#[cfg(feature = "rmp")]
+pub use msgpack::*;
+
+#[cfg(feature = "zenoh-interop")]
+mod zenoh_id;
+#[cfg(feature = "zenoh-interop")]
+pub use zenoh_id::*;
+
+/// [winnow](https://docs.rs/winnow) parser combinators for the stamps, to compose into protocol grammars.
+#[cfg(feature = "parser")]
+pub mod parse;
+
+/// The size of counter part in [`NTP64`] (in bits):
+/// 4 by default, or selected at compile time with the `csize-8` or `csize-16` feature.
+/// As features are additive, the largest enabled size wins.
+pub const CSIZE: u8 = if cfg!(feature = "csize-16") {
+    16u8
+} else if cfg!(feature = "csize-8") {
+    8u8
+} else {
+    4u8
+};
+/// Bit-mask of the logical counter part within the 64 bits time of a [`NTP64`]: its [`CSIZE`] lowest bits.
+///
+/// The [`HLC`] masks those bits of the times returned by its physical clock (see [`HLCBuilder::with_clock()`])
+/// to hold its logical counter: a custom clock source with a finer precision than `2^-(32-CSIZE)` seconds
+/// (e.g. ~4ns with the default `CSIZE` of 4 bits) loses it. See also [`NTP64::strip_counter()`] and [`NTP64::counter()`].
+pub const CMASK: u64 = (1u64 << CSIZE) - 1u64;
+/// Bit-mask of the physical time part within the 64 bits time of a [`NTP64`]: all but its [`CSIZE`] lowest bits
+/// (see [`CMASK`]).
+pub const LMASK: u64 = !CMASK;
 
 // HLC Delta in milliseconds: maximum accepted drift for an external timestamp.
 // I.e.: if an incoming timestamp has a time > now() + delta, then the HLC is not updated.
 const DEFAULT_DELTA_MS: u64 = 500;
-#[cfg(feature = "std")]
+#[cfg(all(feature = "std", not(feature = "no-env")))]
 lazy_static! {
-    static ref DELTA_MS: u64 = match var("UHLC_MAX_DELTA_MS") {
+    static ref DELTA_MS: u64 = match std::env::var("UHLC_MAX_DELTA_MS") {
         Ok(s) => s.parse().unwrap_or_else(|e| panic!(
             "Error parsing environment variable ${{UHLC_MAX_DELTA_MS}}={} : {}",
             s, e
@@ -103,6 +282,8 @@ lazy_static! {
 }
 #[cfg(not(feature = "std"))]
 static DELTA_MS: &u64 = &DEFAULT_DELTA_MS; // Environment variables do not make sense in no_std environment
+#[cfg(all(feature = "std", feature = "no-env"))]
+static DELTA_MS: &u64 = &DEFAULT_DELTA_MS; // Environment variables are ignored with the no-env feature
 
 ///
 /// The builder of [`HLC`].
@@ -121,19 +302,37 @@ static DELTA_MS: &u64 = &DEFAULT_DELTA_MS; // Environment variables do not make
 ///    .with_max_delta(Duration::from_secs(1))
 ///    .build();
 /// println!("{}", custom_hlc.new_timestamp());
+#[derive(Debug)]
 pub struct HLCBuilder {
     hlc: HLC,
+    // the kind of the configured clock, if known (see `HLCBuilder::to_config()`)
+    #[cfg(feature = "std")]
+    clock_kind: Option<ClockKind>,
 }
 
 impl HLCBuilder {
     ///
     /// Constructs a new HLCBuilder for the creation of an [`HLC`], with the following default configuration:
     ///  * a random u128 as HLC identifier.
-    ///   Can be changed calling [`Self::with_id()`].
-    ///  * [`system_time_clock()`] as physical clock (i.e. the ).
-    ///   Can be changed calling [`Self::with_clock()`].
-    ///  * 500 millisecond as maximum delta (i.e. the maximum accepted drift for an external timestamp).
-    ///   Can be changed calling [`Self::with_max_delta()`].
+    ///    Can be changed calling [`Self::with_id()`].
+    ///  * [`global_clock()`] as physical clock (i.e. [`system_time_clock()`] unless another clock
+    ///    was installed with [`set_global_clock()`]).
+    ///    Can be changed calling [`Self::with_clock()`].
+    ///  * 500 millisecond as maximum delta (i.e. the maximum accepted drift for an external timestamp),
+    ///    unless overridden by the `UHLC_MAX_DELTA_MS` environment variable (ignored with the `no-env` feature).
+    ///    Can be changed calling [`Self::with_max_delta()`].
+    ///  * no limit on the updates per peer [`ID`].
+    ///    Can be changed calling [`Self::with_update_rate_limit()`] and [`Self::with_drift_budget()`].
+    ///  * no quarantine of the peers [`ID`] with rejected updates.
+    ///    Can be changed calling [`Self::with_quarantine()`].
+    ///    With limits or quarantine, the usage of at most 1024 peers is tracked: beyond, the peers whose limits
+    ///    and quarantine expired are forgotten first, then the least recently seen ones (quarantined ones last).
+    ///  * no quantization of the physical time.
+    ///    Can be changed calling [`Self::with_time_quantization()`].
+    ///  * no jitter of the physical time.
+    ///    Can be changed calling [`Self::with_time_jitter()`].
+    ///  * no maximum lead of the [`Timestamp`]s over the physical time.
+    ///    Can be changed calling [`Self::with_max_logical_lead()`].
     ///
     pub fn new() -> HLCBuilder {
         HLCBuilder::default()
@@ -149,6 +348,16 @@ impl HLCBuilder {
         self
     }
 
+    ///
+    /// Configure the identifier for the HLC to be created from an application's identity type
+    /// (see [`NodeId`]).
+    ///
+    /// **NOTE: the identifier must be unique in the system.**
+    ///
+    pub fn with_id_of<N: NodeId + ?Sized>(self, node: &N) -> HLCBuilder {
+        self.with_id(node.to_uhlc_id())
+    }
+
     ///
     /// Configure a specific physical clock for the HLC to be created.
     ///
@@ -158,6 +367,10 @@ impl HLCBuilder {
     ///
     pub fn with_clock(mut self, clock: fn() -> NTP64) -> HLCBuilder {
         self.hlc.clock = clock;
+        #[cfg(feature = "std")]
+        {
+            self.clock_kind = None;
+        }
         self
     }
 
@@ -169,8 +382,262 @@ impl HLCBuilder {
         self
     }
 
+    ///
+    /// Configure the maximum number of updates from a same [`ID`] that are accepted by the HLC
+    /// when calling [`HLC::update_with_timestamp()`], per `period` of its physical clock.
+    ///
+    /// Once the limit is reached, the updates with timestamps from this [`ID`] are rejected until the end of the period.
+    ///
+    pub fn with_update_rate_limit(mut self, max_updates: u32, period: Duration) -> HLCBuilder {
+        self.guard_mut().rate_limit = Some(Limit {
+            max: max_updates,
+            period: period.into(),
+        });
+        self
+    }
+
+    ///
+    /// Configure the forward drift budget of each [`ID`], per `period` of the HLC's physical clock.
+    ///
+    /// Even within the maximum delta (see [`Self::with_max_delta()`]), a peer can keep this HLC
+    /// ahead of its physical time. When calling [`HLC::update_with_timestamp()`], the drift of an incoming
+    /// timestamp (i.e. how much it is ahead of the physical time) is accumulated per [`ID`].
+    /// Once the `budget` of an [`ID`] is exhausted, the updates with timestamps from this [`ID`] that are ahead
+    /// of the physical time are rejected until the end of the period.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use uhlc::HLCBuilder;
+    ///
+    /// // a peer may make this HLC drift of at most 100ms per minute
+    /// let hlc = HLCBuilder::new()
+    ///     .with_drift_budget(Duration::from_millis(100), Duration::from_secs(60))
+    ///     .build();
+    /// ```
+    ///
+    pub fn with_drift_budget(mut self, budget: Duration, period: Duration) -> HLCBuilder {
+        self.guard_mut().drift_budget = Some(Limit {
+            max: budget.into(),
+            period: period.into(),
+        });
+        self
+    }
+
+    ///
+    /// Configure the quarantine of the peers with a broken clock: once an [`ID`] had more than
+    /// `max_rejections` updates rejected by [`HLC::update_with_timestamp()`] (except with
+    /// [`UpdateError::FrontierOverflow`]) within `period` of the HLC's physical clock, all its further
+    /// updates fail fast with [`UpdateError::PeerQuarantined`] during the next `period`, or until
+    /// [`HLC::pardon()`] is called for this [`ID`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use uhlc::{HLCBuilder, Timestamp, UpdateError, HLC, NTP64};
+    ///
+    /// let hlc = HLCBuilder::new()
+    ///     .with_quarantine(2, Duration::from_secs(60))
+    ///     .build();
+    /// let peer = HLC::default();
+    /// let broken_ts = Timestamp::new(NTP64(u64::MAX >> 1), *peer.get_id());
+    /// for _ in 0..3 {
+    ///     assert!(hlc.update_with_timestamp(&broken_ts).is_err());
+    /// }
+    /// let result = hlc.update_with_timestamp(&peer.new_timestamp());
+    /// assert!(matches!(result, Err(UpdateError::PeerQuarantined(_))));
+    ///
+    /// assert!(hlc.pardon(peer.get_id()));
+    /// assert!(hlc.update_with_timestamp(&peer.new_timestamp()).is_ok());
+    /// ```
+    ///
+    pub fn with_quarantine(mut self, max_rejections: u32, period: Duration) -> HLCBuilder {
+        self.guard_mut().quarantine = Some(Limit {
+            max: max_rejections,
+            period: period.into(),
+        });
+        self
+    }
+
+    ///
+    /// Configure the HLC to be created to round down the physical time of its [`Timestamp`]s to a multiple
+    /// of `granularity`, deliberately reducing their precision so they don't leak the precise activity times
+    /// (e.g. in privacy-sensitive local-first applications). The uniqueness and ordering of the [`Timestamp`]s
+    /// within a same quantum rely on the logical part of their time and on their [`ID`].
+    ///
+    /// The [`Timestamp`]s generated within a same quantum have the time of the quantum start, plus 1 fraction
+    /// of second per [`Timestamp`]. Thus, up to `granularity × 2^32` [`Timestamp`]s per quantum
+    /// (e.g. ~4.3 billions for a 1s quantum) keep the time of their quantum. Beyond, their time runs ahead of it.
+    ///
+    /// The incoming [`Timestamp`]s are still checked against the maximum delta with the precise physical time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use uhlc::HLCBuilder;
+    ///
+    /// let hlc = HLCBuilder::new()
+    ///     .with_time_quantization(Duration::from_secs(60))
+    ///     .build();
+    /// let t1 = hlc.new_timestamp();
+    /// let t2 = hlc.new_timestamp();
+    /// assert!(t2 > t1);
+    /// assert_eq!(t1.get_time().as_secs() % 60, 0);
+    /// assert_eq!(t1.get_time().subsec_nanos(), 0);
+    /// ```
+    ///
+    pub fn with_time_quantization(mut self, granularity: Duration) -> HLCBuilder {
+        self.hlc.quantum = Some(granularity.into());
+        self
+    }
+
+    ///
+    /// Configure the HLC to be created to set back the physical time of its [`Timestamp`]s by a jitter
+    /// lower than `max_jitter`, so the activity of different nodes can't be precisely correlated.
+    ///
+    /// The jitter is deterministic per node: it's [`ID::stable_hash64()`] modulo `max_jitter`
+    /// (as a [`NTP64`]), thus constant for an [`ID`] and the [`Timestamp`]s of an HLC remain monotonic.
+    /// As the time is only set back, the [`Timestamp`]s of this HLC are never refused by its peers
+    /// because of the jitter, and their ordering with the [`Timestamp`]s of other HLCs is preserved
+    /// for the events more than `max_jitter` apart.
+    /// The jitter is applied before the time quantization (see [`Self::with_time_quantization()`]).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use uhlc::HLCBuilder;
+    ///
+    /// let hlc = HLCBuilder::new()
+    ///     .with_time_jitter(Duration::from_millis(100))
+    ///     .build();
+    /// let t1 = hlc.new_timestamp();
+    /// let t2 = hlc.new_timestamp();
+    /// assert!(t2 > t1);
+    /// ```
+    ///
+    pub fn with_time_jitter(mut self, max_jitter: Duration) -> HLCBuilder {
+        self.hlc.jitter = Some(max_jitter.into());
+        self
+    }
+
+    ///
+    /// Configure the maximum lead of the [`Timestamp`]s of the HLC to be created over its physical clock
+    /// (see [`HLC::logical_lead()`]), enforcing the bounded divergence of the HLC from the real time even when
+    /// the logical counter increments and the accepted [`Timestamp`]s keep pushing its frontier ahead.
+    ///
+    /// When a new [`Timestamp`] would be more than `max_lead` ahead of the physical clock,
+    /// [`HLC::new_timestamp()`] blocks until the physical clock catches up (by sleeping with the `std` feature,
+    /// by spinning otherwise), while [`HLC::try_new_timestamp()`] returns a [`LeadError`].
+    /// [`HLC::new_timestamp_with_now()`] doesn't enforce this maximum.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use uhlc::{HLCBuilder, LeadError, NTP64};
+    ///
+    /// let hlc = HLCBuilder::new()
+    ///     .with_max_logical_lead(Duration::from_millis(100))
+    ///     .build();
+    /// let peer = HLCBuilder::new().build();
+    /// peer.advance_frontier(hlc.read_clock() + NTP64::from(Duration::from_millis(400)));
+    /// hlc.update_with_timestamp(&peer.new_timestamp()).unwrap();
+    /// assert!(matches!(hlc.try_new_timestamp(), Err(LeadError::MaxLeadExceeded { .. })));
+    /// ```
+    ///
+    pub fn with_max_logical_lead(mut self, max_lead: Duration) -> HLCBuilder {
+        self.hlc.max_lead = Some(max_lead.into());
+        self
+    }
+
+    ///
+    /// Configure a [`Recorder`] for the HLC to be created, that will record each issued [`Timestamp`]
+    /// and each accepted or rejected update.
+    ///
+    #[cfg(feature = "record")]
+    pub fn with_recorder(mut self, recorder: Arc<dyn Recorder>) -> HLCBuilder {
+        self.hlc.recorder = Some(Arc::new(Recording::new(recorder)));
+        self
+    }
+
     pub fn build(self) -> HLC {
-        self.hlc
+        let mut hlc = self.hlc;
+        hlc.started = (hlc.clock)();
+        hlc
+    }
+
+    ///
+    /// Build the [`HLC`] as [`Self::build()`] does, but first validate its configuration,
+    /// returning a [`ConfigError`] if it's nonsensical.
+    ///
+    /// Configurations that are valid but suspicious (e.g. a physical clock returning 0 while `std` is available)
+    /// are logged as warnings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use uhlc::{ConfigError, HLCBuilder};
+    ///
+    /// assert!(HLCBuilder::new().build_checked().is_ok());
+    /// let result = HLCBuilder::new()
+    ///     .with_update_rate_limit(0, Duration::from_secs(1))
+    ///     .build_checked();
+    /// assert_eq!(result.unwrap_err(), ConfigError::ZeroRateLimit);
+    /// ```
+    ///
+    pub fn build_checked(self) -> Result<HLC, ConfigError> {
+        let hlc = &self.hlc;
+        if hlc.delta.0 & LMASK == 0 {
+            return Err(ConfigError::MaxDeltaTooSmall(hlc.delta));
+        }
+        if let Some(limit) = hlc.guard.rate_limit {
+            if limit.max == 0 {
+                return Err(ConfigError::ZeroRateLimit);
+            }
+            if limit.period.0 & LMASK == 0 {
+                return Err(ConfigError::ZeroPeriod("update rate limit"));
+            }
+        }
+        if let Some(limit) = hlc.guard.drift_budget {
+            if limit.period.0 & LMASK == 0 {
+                return Err(ConfigError::ZeroPeriod("drift budget"));
+            }
+            #[cfg(feature = "std")]
+            if limit.max == NTP64(0) {
+                log::warn!("HLC configured with a drift budget of 0: all updates ahead of its physical time will be rejected");
+            }
+        }
+        if let Some(limit) = hlc.guard.quarantine {
+            if limit.period.0 & LMASK == 0 {
+                return Err(ConfigError::ZeroPeriod("quarantine"));
+            }
+        }
+        if let Some(quantum) = hlc.quantum {
+            if quantum.0 & LMASK == 0 {
+                return Err(ConfigError::ZeroPeriod("time quantization"));
+            }
+        }
+        if let Some(jitter) = hlc.jitter {
+            if jitter.0 & LMASK == 0 {
+                return Err(ConfigError::ZeroPeriod("time jitter"));
+            }
+        }
+        let hlc = self.build();
+        #[cfg(feature = "std")]
+        if hlc.started == NTP64(0) {
+            log::warn!("HLC configured with a physical clock returning 0: its timestamps won't reflect the wall time");
+        }
+        Ok(hlc)
+    }
+
+    // The HLC under construction is not shared yet, so its guard can be configured in place
+    fn guard_mut(&mut self) -> &mut PeerGuard {
+        Arc::get_mut(&mut self.hlc.guard).expect("HLC under construction shouldn't be shared")
     }
 }
 
@@ -179,23 +646,56 @@ impl Default for HLCBuilder {
         HLCBuilder {
             hlc: HLC {
                 id: ID::rand(),
-                #[cfg(feature = "std")]
-                clock: system_time_clock,
-                #[cfg(not(feature = "std"))]
-                clock: zero_clock,
+                clock: global_clock,
                 delta: NTP64::from(Duration::from_millis(*DELTA_MS)),
                 last_time: Default::default(),
+                guard: Arc::new(PeerGuard::new()),
+                quantum: None,
+                jitter: None,
+                max_lead: None,
+                started: NTP64(0),
+                #[cfg(feature = "record")]
+                recorder: None,
             },
+            #[cfg(feature = "std")]
+            clock_kind: None,
         }
     }
 }
 
 /// An Hybric Logical Clock generating [`Timestamp`]s
+///
+/// Cloning an [`HLC`] is cheap and returns a handle to the same logical clock: all the clones
+/// share their latest time, so the [`Timestamp`]s they generate are unique and monotonic.
+///
+/// # Examples
+///
+/// ```
+/// use uhlc::HLC;
+///
+/// let hlc = HLC::default();
+/// let handle = hlc.clone();
+/// let ts1 = hlc.new_timestamp();
+/// let ts2 = handle.new_timestamp();
+/// assert!(ts2 > ts1);
+/// ```
+#[derive(Clone)]
 pub struct HLC {
     id: ID,
     clock: fn() -> NTP64,
     delta: NTP64,
-    last_time: Mutex<NTP64>,
+    last_time: Arc<LastTime>,
+    guard: Arc<PeerGuard>,
+    // the granularity the physical time is rounded down to, if any
+    quantum: Option<NTP64>,
+    // the bound of the per-node jitter the physical time is set back by, if any
+    jitter: Option<NTP64>,
+    // the maximum lead of the issued Timestamps over the physical clock, if any
+    max_lead: Option<NTP64>,
+    // the physical time when the HLC was built
+    started: NTP64,
+    #[cfg(feature = "record")]
+    recorder: Option<Arc<Recording>>,
 }
 
 #[cfg(feature = "std")]
@@ -215,7 +715,74 @@ macro_rules! lock {
     };
 }
 
+mod guard;
+use guard::{Limit, PeerGuard};
+
+mod last_time;
+use last_time::LastTime;
+
+mod pool;
+pub use pool::*;
+
+#[cfg(feature = "record")]
+mod record;
+#[cfg(feature = "record")]
+pub use record::*;
+
+#[cfg(feature = "embedded-rtc")]
+mod rtc;
+#[cfg(feature = "embedded-rtc")]
+pub use rtc::*;
+
+#[cfg(feature = "test-util")]
+mod test_util;
+
 impl HLC {
+    /// Create a new [`HLC`] with the specified identifier, physical clock and maximum delta.
+    ///
+    /// This is a shortcut for:
+    /// ```
+    /// # use uhlc::{HLCBuilder, ID};
+    /// # use std::time::Duration;
+    /// # let (id, clock, max_delta) = (ID::rand(), uhlc::zero_clock, Duration::from_millis(500));
+    /// HLCBuilder::new()
+    ///     .with_id(id)
+    ///     .with_clock(clock)
+    ///     .with_max_delta(max_delta)
+    ///     .build();
+    /// ```
+    /// See [`HLCBuilder`] for the other configuration options.
+    ///
+    /// **NOTE: the identifier must be unique in the system.**
+    pub fn new(id: ID, clock: fn() -> NTP64, max_delta: Duration) -> HLC {
+        HLCBuilder::new()
+            .with_id(id)
+            .with_clock(clock)
+            .with_max_delta(max_delta)
+            .build()
+    }
+
+    /// Create a new [`HLC`] with the specified identifier, [`system_time_clock()`] as physical clock
+    /// and the default maximum delta (see [`HLCBuilder::new()`]).
+    ///
+    /// **NOTE: the identifier must be unique in the system.**
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use uhlc::{HLC, ID};
+    ///
+    /// let hlc = HLC::with_system_clock(ID::rand());
+    /// assert!(hlc.new_timestamp().get_time().to_system_time() <= std::time::SystemTime::now());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn with_system_clock(id: ID) -> HLC {
+        HLCBuilder::new()
+            .with_id(id)
+            .with_clock(system_time_clock)
+            .build()
+    }
+
     /// Generate a new [`Timestamp`].
     ///
     /// This timestamp is unique in the system and is always greater
@@ -234,15 +801,179 @@ impl HLC {
     /// assert!(ts2 > ts1);
     /// ```
     pub fn new_timestamp(&self) -> Timestamp {
-        let mut now = (self.clock)();
-        now.0 &= LMASK;
-        let mut last_time = lock!(self.last_time);
-        if now.0 > (last_time.0 & LMASK) {
-            *last_time = now
-        } else {
-            *last_time += 1;
+        if self.max_lead.is_none() {
+            return self.new_timestamp_at((self.clock)());
         }
-        Timestamp::new(*last_time, self.id)
+        loop {
+            match self.try_new_timestamp_at((self.clock)()) {
+                Ok(timestamp) => return timestamp,
+                #[cfg(feature = "std")]
+                Err(LeadError::MaxLeadExceeded { lead, max }) => std::thread::sleep(cmp::max(
+                    (lead - max).to_duration(),
+                    Duration::from_nanos(1),
+                )),
+                #[cfg(not(feature = "std"))]
+                Err(_) => core::hint::spin_loop(),
+            }
+        }
+    }
+
+    /// Generate a new [`Timestamp`] as [`HLC::new_timestamp()`] does, but returns [`LeadError::MaxLeadExceeded`]
+    /// instead of blocking if it would be ahead of the physical clock by more than the maximum logical lead
+    /// (see [`HLCBuilder::with_max_logical_lead()`]).
+    pub fn try_new_timestamp(&self) -> Result<Timestamp, LeadError> {
+        self.try_new_timestamp_at((self.clock)())
+    }
+
+    // Generate a new Timestamp at `clock_time` if it's within the maximum logical lead, if any.
+    // The lead is checked atomically with the issuance, so concurrent issuances can't exceed it.
+    fn try_new_timestamp_at(&self, clock_time: NTP64) -> Result<Timestamp, LeadError> {
+        let max = match self.max_lead {
+            Some(max) => max,
+            None => return Ok(self.new_timestamp_at(clock_time)),
+        };
+        #[cfg(feature = "record")]
+        let _recording = self.recording();
+        let now = self.physical_time(clock_time);
+        let exceeded = core::cell::Cell::new(NTP64(0));
+        let issued = self.last_time.try_update(|last_time| {
+            let time = self.next_time(now, last_time);
+            let lead = self.lead_at(time, clock_time);
+            if lead > max {
+                exceeded.set(lead);
+                None
+            } else {
+                Some(time)
+            }
+        });
+        match issued {
+            Some(time) => {
+                let timestamp = Timestamp::new(time, self.id);
+                #[cfg(feature = "record")]
+                self.record(Event::Issued {
+                    now: clock_time,
+                    timestamp,
+                });
+                Ok(timestamp)
+            }
+            None => Err(LeadError::MaxLeadExceeded {
+                lead: exceeded.get(),
+                max,
+            }),
+        }
+    }
+
+    // How far `time` is ahead of the `clock_time` reading (without counter part)
+    fn lead_at(&self, time: NTP64, clock_time: NTP64) -> NTP64 {
+        NTP64(time.0.saturating_sub(clock_time.0 & LMASK))
+    }
+
+    /// Returns an iterator yielding each item of `iter` along with a new [`Timestamp`]
+    /// generated when the item is consumed (see [`HLC::new_timestamp()`]).
+    ///
+    /// As the [`Timestamp`]s are generated by this [`HLC`], they are unique and increasing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use uhlc::HLC;
+    ///
+    /// let hlc = HLC::default();
+    /// let stamped: Vec<_> = hlc.stamp_iter(["a", "b"]).collect();
+    /// assert!(stamped[0].0 < stamped[1].0);
+    /// ```
+    pub fn stamp_iter<'a, I>(&'a self, iter: I) -> impl Iterator<Item = (Timestamp, I::Item)> + 'a
+    where
+        I: IntoIterator,
+        I::IntoIter: 'a,
+    {
+        iter.into_iter()
+            .map(move |item| (self.new_timestamp(), item))
+    }
+
+    /// Returns a reading of the physical clock of this [`HLC`] (see [`HLCBuilder::with_clock()`]),
+    /// to be passed to [`HLC::new_timestamp_with_now()`].
+    pub fn read_clock(&self) -> NTP64 {
+        (self.clock)()
+    }
+
+    /// Generate a new [`Timestamp`] as [`HLC::new_timestamp()`] does, but with `now` as the reading
+    /// of the physical clock instead of calling it.
+    ///
+    /// This avoids redundant clock reads when stamping many entities at once (e.g. within a request),
+    /// the clock being read once with [`HLC::read_clock()`]. The [`Timestamp`]s are still unique and increasing,
+    /// even if `now` is outdated: then, only their logical counter increases.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use uhlc::HLC;
+    ///
+    /// let hlc = HLC::default();
+    /// let now = hlc.read_clock();
+    /// let stamps: Vec<_> = (0..3).map(|_| hlc.new_timestamp_with_now(now)).collect();
+    /// assert!(stamps[0] < stamps[1] && stamps[1] < stamps[2]);
+    /// ```
+    #[inline]
+    pub fn new_timestamp_with_now(&self, now: NTP64) -> Timestamp {
+        self.new_timestamp_at(now)
+    }
+
+    // The time of a new Timestamp at the physical time `now`, after `last_time`
+    fn next_time(&self, now: NTP64, last_time: NTP64) -> NTP64 {
+        let time = if now.0 > (last_time.0 & LMASK) {
+            invariant!(
+                now.0 & CMASK == 0,
+                "HLC invariant violated: counter of new physical time {:?} is not zero",
+                now
+            );
+            now
+        } else {
+            invariant!(
+                last_time.0 < u64::MAX,
+                "HLC invariant violated: counter overflows the maximum time {:?}",
+                last_time
+            );
+            last_time + 1
+        };
+        invariant!(
+            time > last_time,
+            "HLC invariant violated: new timestamp {:?} is not greater than previous one {:?}",
+            time,
+            last_time
+        );
+        time
+    }
+
+    // Generate a new [`Timestamp`] with `clock_time` as the reading of the physical clock.
+    pub(crate) fn new_timestamp_at(&self, clock_time: NTP64) -> Timestamp {
+        #[cfg(feature = "record")]
+        let _recording = self.recording();
+        let now = self.physical_time(clock_time);
+        let last_time = self
+            .last_time
+            .update(|last_time| self.next_time(now, last_time));
+        let timestamp = Timestamp::new(last_time, self.id);
+        #[cfg(feature = "record")]
+        self.record(Event::Issued {
+            now: clock_time,
+            timestamp,
+        });
+        timestamp
+    }
+
+    /// Lift the quarantine of a peer [`ID`] (see [`HLCBuilder::with_quarantine()`]),
+    /// so the updates with its timestamps are accepted again.
+    ///
+    /// Returns `true` if the [`ID`] was quarantined.
+    pub fn pardon(&self, id: &ID) -> bool {
+        self.guard.pardon(id, self.read_clock().strip_counter())
+    }
+
+    /// Returns `true` if the peer [`ID`] is quarantined (see [`HLCBuilder::with_quarantine()`]).
+    pub fn is_quarantined(&self, id: &ID) -> bool {
+        self.guard
+            .is_quarantined(id, self.read_clock().strip_counter())
     }
 
     /// Returns the HLC [`ID`].
@@ -253,6 +984,41 @@ impl HLC {
         &self.id
     }
 
+    /// Replace the [`ID`] of this [`HLC`] by `new_id`, keeping its latest time.
+    ///
+    /// The [`Timestamp`]s generated after the rotation are still greater than all the ones generated
+    /// (or received) before it, whatever the order of the [`ID`]s, since their time is greater.
+    /// This allows to re-issue the identity of a node (e.g. on a certificate rotation) without restarting its clock.
+    ///
+    /// The rotation applies to this [`HLC`] handle and to the clones made afterward.
+    /// The clones made before keep generating [`Timestamp`]s with the previous [`ID`], and the
+    /// [`Timestamp`]s with the previous [`ID`] can't be certified anymore by this handle (see [`HLC::certify()`]).
+    ///
+    /// Returns [`RotateIdError::SameId`] if `new_id` is the current [`ID`].
+    ///
+    /// **NOTE: the new identifier must be unique in the system.**
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use uhlc::{HLC, ID};
+    ///
+    /// let mut hlc = HLC::default();
+    /// let before = hlc.new_timestamp();
+    /// let new_id = ID::rand();
+    /// hlc.rotate_id(new_id).unwrap();
+    /// let after = hlc.new_timestamp();
+    /// assert_eq!(after.get_id(), &new_id);
+    /// assert!(after > before);
+    /// ```
+    pub fn rotate_id(&mut self, new_id: ID) -> Result<(), RotateIdError> {
+        if new_id == self.id {
+            return Err(RotateIdError::SameId(new_id));
+        }
+        self.id = new_id;
+        Ok(())
+    }
+
     /// Returns the HLC delta as [`NTP64`].
     ///
     /// The maximum delta accepted by an HLC when updating it's logical clock calling [`HLC::update_with_timestamp()`].
@@ -261,11 +1027,92 @@ impl HLC {
         &self.delta
     }
 
+    /// Returns the frontier of this [`HLC`]: the latest time it issued or received in a [`Timestamp`].
+    ///
+    /// It can be handed to another [`HLC`] with [`HLC::advance_frontier()`], e.g. by a supervisor
+    /// from an old process to its replacement during a zero-downtime deploy.
+    pub fn frontier(&self) -> NTP64 {
+        self.last_time.get()
+    }
+
+    /// Advance the frontier of this [`HLC`] to `frontier` (typically returned by [`HLC::frontier()`] of another [`HLC`]),
+    /// so all its next [`Timestamp`]s are greater than `frontier`. If its frontier is already greater or equal,
+    /// this [`HLC`] is unchanged.
+    ///
+    /// Contrary to [`HLC::update_with_timestamp()`], `frontier` is trusted: it's not checked against the maximum delta.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use uhlc::HLC;
+    ///
+    /// let old = HLC::default();
+    /// let last = old.new_timestamp();
+    ///
+    /// let new = HLC::default();
+    /// new.advance_frontier(old.frontier());
+    /// assert!(*new.new_timestamp().get_time() > *last.get_time());
+    /// ```
+    pub fn advance_frontier(&self, frontier: NTP64) {
+        self.last_time
+            .update(|last_time| cmp::max(last_time, frontier));
+    }
+
+    /// Returns how far the frontier of this [`HLC`] (see [`HLC::frontier()`]) is ahead of its physical clock,
+    /// because of the logical counter increments and of the accepted [`Timestamp`]s ahead of its physical time,
+    /// or [`Duration::ZERO`] if it's not ahead.
+    ///
+    /// This is the key health signal of an HLC deployment, e.g. to be exported as a gauge:
+    /// a growing lead means that the [`Timestamp`]s drift away from the real time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use uhlc::HLC;
+    ///
+    /// let hlc = HLC::default();
+    /// assert!(hlc.logical_lead() < Duration::from_secs(1));
+    /// ```
+    pub fn logical_lead(&self) -> Duration {
+        self.lead_at(self.last_time.get(), self.read_clock())
+            .to_duration()
+    }
+
+    // Returns true if other handles share the state of this HLC
+    pub(crate) fn is_shared(&self) -> bool {
+        Arc::strong_count(&self.last_time) > 1
+    }
+
+    // The current time of this HLC, without generating a new Timestamp:
+    // the latest of its physical time and of its last issued or received time.
+    pub(crate) fn current_time(&self) -> NTP64 {
+        cmp::max(self.physical_time((self.clock)()), self.last_time.get())
+    }
+
+    // The physical time of a `clock_time` reading used for the Timestamps: without counter part,
+    // set back by the jitter and rounded down to the time quantization (if any).
+    fn physical_time(&self, clock_time: NTP64) -> NTP64 {
+        let mut now = clock_time;
+        if let Some(jitter) = self.jitter.filter(|j| j.0 != 0) {
+            now.0 = now.0.saturating_sub(self.id.stable_hash64() % jitter.0);
+        }
+        if let Some(quantum) = self.quantum.filter(|q| q.0 != 0) {
+            now.0 -= now.0 % quantum.0;
+        }
+        now.0 &= LMASK;
+        now
+    }
+
     /// Update this [`HLC`] with a [`Timestamp`].
     ///
     /// Typically, this timestamp should have been generated by another HLC.
     /// If the timestamp exceeds the current time of this HLC by more than the configured maximum delta
     /// (see [`HLCBuilder::with_max_delta()`]) an [`Err`] is returned.
+    /// An [`Err`] is also returned if the timestamp's [`ID`] exceeds its update rate limit
+    /// (see [`HLCBuilder::with_update_rate_limit()`]) or its forward drift budget
+    /// (see [`HLCBuilder::with_drift_budget()`]), or if the timestamp's [`ID`] is quarantined
+    /// (see [`HLCBuilder::with_quarantine()`]).
     ///
     /// # Examples
     ///
@@ -286,51 +1133,204 @@ impl HLC {
     /// let ts = hlc1.new_timestamp();
     /// assert!(ts > other_ts);
     /// ```
-    pub fn update_with_timestamp(&self, timestamp: &Timestamp) -> Result<(), String> {
-        let mut now = (self.clock)();
+    pub fn update_with_timestamp(&self, timestamp: &Timestamp) -> Result<(), UpdateError> {
+        self.update_with_timestamp_at(timestamp, (self.clock)())
+    }
+
+    // Update this [`HLC`] with a [`Timestamp`], with `clock_time` as the reading of the physical clock.
+    pub(crate) fn update_with_timestamp_at(
+        &self,
+        timestamp: &Timestamp,
+        clock_time: NTP64,
+    ) -> Result<(), UpdateError> {
+        #[cfg(feature = "record")]
+        let _recording = self.recording();
+        let mut now = clock_time;
         now.0 &= LMASK;
+        let physical_time = self.physical_time(clock_time);
         let msg_time = timestamp.get_time();
-        if *msg_time > now && *msg_time - now > self.delta {
-            let err_msg = format!(
-                "incoming timestamp from {} exceeding delta {}ms is rejected: {:#} vs. now: {:#}",
-                timestamp.get_id(),
-                self.delta.to_duration().as_millis(),
-                msg_time,
-                now
+        invariant!(
+            timestamp.get_id().size() > 0,
+            "HLC invariant violated: incoming timestamp with a zero ID"
+        );
+        // the updated time, or None if it would overflow
+        let next_time = |last_time: NTP64| {
+            let max_time = cmp::max(cmp::max(physical_time, *msg_time), last_time);
+            let time = if max_time == physical_time {
+                physical_time
+            } else if max_time == *msg_time {
+                NTP64(msg_time.0.checked_add(1)?)
+            } else {
+                NTP64(last_time.0.checked_add(1)?)
+            };
+            invariant!(
+                time >= last_time,
+                "HLC invariant violated: updated time {:?} is lower than previous one {:?}",
+                time,
+                last_time
             );
+            Some(time)
+        };
+        let overflow = || UpdateError::FrontierOverflow {
+            id: *timestamp.get_id(),
+            time: *msg_time,
+        };
+        let check = self
+            .guard
+            .check_quarantine(timestamp.get_id(), now)
+            .and_then(|()| {
+                if *msg_time > now && *msg_time - now > self.delta {
+                    Err(UpdateError::DeltaExceeded {
+                        id: *timestamp.get_id(),
+                        time: *msg_time,
+                        now,
+                        delta: self.delta,
+                    })
+                } else if next_time(self.last_time.get()).is_none() {
+                    // checked before the peer's rate limit and drift budget are used by the update
+                    Err(overflow())
+                } else {
+                    self.guard.accept(timestamp.get_id(), *msg_time, now)
+                }
+            })
+            .and_then(|()| {
+                self.last_time
+                    .try_update(next_time)
+                    .map(|_| ())
+                    .ok_or_else(overflow)
+            });
+        if let Err(err_msg) = check {
+            // an overflow of this HLC's time isn't counted against the peer
+            if !matches!(err_msg, UpdateError::FrontierOverflow { .. }) {
+                self.guard.reject(timestamp.get_id(), now);
+            }
             #[cfg(feature = "std")]
             log::warn!("{}", err_msg);
             #[cfg(feature = "defmt")]
             defmt::warn!("{}", err_msg);
+            #[cfg(feature = "record")]
+            self.record(Event::Rejected {
+                now: clock_time,
+                timestamp: *timestamp,
+            });
             Err(err_msg)
         } else {
-            let mut last_time = lock!(self.last_time);
-            let max_time = cmp::max(cmp::max(now, *msg_time), *last_time);
-            if max_time == now {
-                *last_time = now;
-            } else if max_time == *msg_time {
-                *last_time = *msg_time + 1;
-            } else {
-                *last_time += 1;
-            }
+            #[cfg(feature = "record")]
+            self.record(Event::Accepted {
+                now: clock_time,
+                timestamp: *timestamp,
+            });
             Ok(())
         }
     }
+
+    /// Certify that a [`Timestamp`] was issued by this [`HLC`].
+    ///
+    /// The returned [`StampCertificate`] is the evidence that the following invariants hold:
+    ///  * the timestamp's [`ID`] is the HLC's one
+    ///  * the timestamp's time is not after the latest time of the HLC (i.e. it was already issuable)
+    ///
+    /// If any of those invariants doesn't hold, a [`CertifyError`] is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use uhlc::HLC;
+    ///
+    /// let hlc = HLC::default();
+    /// let ts = hlc.new_timestamp();
+    /// let certificate = hlc.certify(&ts).unwrap();
+    /// assert!(certificate.verify().is_ok());
+    ///
+    /// let other_ts = HLC::default().new_timestamp();
+    /// assert!(hlc.certify(&other_ts).is_err());
+    /// ```
+    pub fn certify(&self, timestamp: &Timestamp) -> Result<StampCertificate, CertifyError> {
+        let certificate = StampCertificate {
+            timestamp: *timestamp,
+            issuer: self.id,
+            frontier: self.last_time.get(),
+        };
+        certificate.verify().map(|_| certificate)
+    }
 }
 
 impl Default for HLC {
     /// Create a new [`HLC`] with a random u128 ID and using
-    /// [`system_time_clock()`] as physical clock.
+    /// [`global_clock()`] as physical clock.
     /// This is equivalent to `HLCBuilder::default().build()`
     fn default() -> Self {
         HLCBuilder::default().build()
     }
 }
 
+impl fmt::Debug for HLC {
+    /// Formats the [`HLC`]'s id, maximum delta and latest time.
+    ///
+    /// The latest time is read without blocking: if the [`HLC`] is concurrently in use,
+    /// it's displayed as `<locked>`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = f.debug_struct("HLC");
+        s.field("id", &self.id).field("delta", &self.delta);
+        match self.last_time.try_get() {
+            Some(last_time) => s.field("last_time", &last_time),
+            None => s.field("last_time", &format_args!("<locked>")),
+        };
+        s.finish_non_exhaustive()
+    }
+}
+
+// The clock installed with set_global_clock(), or null if none was installed
+static GLOBAL_CLOCK: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Install `clock` as the physical clock used by [`global_clock()`].
+///
+/// This allows to install a clock at runtime (e.g. the RTC driver of a board at boot in no_std environments),
+/// while the [`HLC`]s keep being created with [`HLC::default()`] or [`HLCBuilder::new()`].
+/// The installed clock is used by all the [`HLC`]s relying on [`global_clock()`], including the ones
+/// created before its installation.
+///
+/// # Examples
+///
+/// ```
+/// use uhlc::{set_global_clock, HLC, NTP64};
+///
+/// fn rtc_clock() -> NTP64 {
+///     // read the RTC...
+///     # uhlc::system_time_clock()
+/// }
+///
+/// set_global_clock(rtc_clock);
+/// let hlc = HLC::default();
+/// println!("{}", hlc.new_timestamp());
+/// ```
+pub fn set_global_clock(clock: fn() -> NTP64) {
+    GLOBAL_CLOCK.store(clock as *mut (), Ordering::Release);
+}
+
+/// A physical clock calling the clock installed with [`set_global_clock()`].
+///
+/// If no clock was installed, it calls [`system_time_clock()`], or [`zero_clock()`] if the feature `std` is disabled.
+/// That's the default clock used by an [`HLC`] if [`HLCBuilder::with_clock()`] is not called.
+///
+#[inline]
+pub fn global_clock() -> NTP64 {
+    let clock = GLOBAL_CLOCK.load(Ordering::Acquire);
+    if clock.is_null() {
+        #[cfg(feature = "std")]
+        return system_time_clock();
+        #[cfg(not(feature = "std"))]
+        return zero_clock();
+    }
+    // SAFETY: a non-null GLOBAL_CLOCK was stored from a `fn() -> NTP64` by set_global_clock()
+    let clock = unsafe { core::mem::transmute::<*mut (), fn() -> NTP64>(clock) };
+    clock()
+}
+
 /// A physical clock relying on std::time::SystemTime::now().
 ///
 /// It returns a NTP64 relative to std::time::UNIX_EPOCH (1st Jan 1970).
-/// That's the default clock used by an [`HLC`] if [`HLCBuilder::with_clock()`] is not called.
+/// That's the clock used by [`global_clock()`] if [`set_global_clock()`] is not called.
 ///
 #[inline]
 #[cfg(feature = "std")]
@@ -338,9 +1338,29 @@ pub fn system_time_clock() -> NTP64 {
     NTP64::from(SystemTime::now().duration_since(UNIX_EPOCH).unwrap())
 }
 
+#[cfg(feature = "std")]
+lazy_static! {
+    // The wall time and the monotonic instant at the first call of monotonic_clock()
+    static ref MONOTONIC_ANCHOR: (Duration, Instant) = (
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap(),
+        Instant::now()
+    );
+}
+
+/// A physical clock relying on std::time::Instant, anchored to std::time::SystemTime::now() at its first call.
+///
+/// It returns a NTP64 relative to std::time::UNIX_EPOCH (1st Jan 1970), which never goes backward
+/// even if the system time is adjusted, but which may diverge from the system time over time.
+///
+#[cfg(feature = "std")]
+pub fn monotonic_clock() -> NTP64 {
+    let (wall_time, instant) = *MONOTONIC_ANCHOR;
+    NTP64::from(wall_time + instant.elapsed())
+}
+
 /// A dummy clock that returns a NTP64 initialized with the value 0.
 /// Suitable to use in no_std environments where std::time::{SystemTime, UNIX_EPOCH} are not available.
-/// If the feature `std` is disabled, that's the default clock used by an [`HLC`] if [`HLCBuilder::with_clock()`] is not called.
+/// If the feature `std` is disabled, that's the clock used by [`global_clock()`] if [`set_global_clock()`] is not called.
 /// Notice that this means that the [`HLC`] will use incremental timestamps starting from 0.
 #[inline]
 pub fn zero_clock() -> NTP64 {
@@ -476,4 +1496,426 @@ mod tests {
         let future_ts = Timestamp::new(future_time, id);
         assert!(hlc.update_with_timestamp(&future_ts).is_err())
     }
+
+    #[test]
+    fn hlc_update_guard() {
+        let peer: ID = ID::rand();
+        let other_peer: ID = ID::rand();
+
+        // at most 2 updates per peer
+        let hlc = HLCBuilder::new()
+            .with_update_rate_limit(2, Duration::from_secs(3600))
+            .build();
+        let ts = Timestamp::new(*hlc.new_timestamp().get_time() - 1, peer);
+        assert!(hlc.update_with_timestamp(&ts).is_ok());
+        assert!(hlc.update_with_timestamp(&ts).is_ok());
+        assert!(hlc.update_with_timestamp(&ts).is_err());
+        let ts = Timestamp::new(*ts.get_time(), other_peer);
+        assert!(hlc.update_with_timestamp(&ts).is_ok());
+
+        // at most 200ms of forward drift per peer
+        let hlc = HLCBuilder::new()
+            .with_drift_budget(Duration::from_millis(200), Duration::from_secs(3600))
+            .build();
+        let ahead = NTP64::from(Duration::from_millis(150));
+        let ts = Timestamp::new(system_time_clock() + ahead, peer);
+        assert!(hlc.update_with_timestamp(&ts).is_ok());
+        let ts = Timestamp::new(system_time_clock() + ahead, peer);
+        assert!(hlc.update_with_timestamp(&ts).is_err());
+        // timestamps in the past don't consume the budget
+        let ts = Timestamp::new(Default::default(), peer);
+        assert!(hlc.update_with_timestamp(&ts).is_ok());
+        let ts = Timestamp::new(system_time_clock() + ahead, other_peer);
+        assert!(hlc.update_with_timestamp(&ts).is_ok());
+    }
+
+    #[test]
+    fn hlc_debug() {
+        let hlc = HLCBuilder::new()
+            .with_id(ID::try_from([0x33]).unwrap())
+            .with_clock(zero_clock)
+            .with_max_delta(Duration::from_secs(1))
+            .build();
+        let ts = hlc.new_timestamp();
+        assert_eq!(
+            format!("{hlc:?}"),
+            format!(
+                "HLC {{ id: 33, delta: {}, last_time: {}, .. }}",
+                NTP64::from(Duration::from_secs(1)).as_u64(),
+                ts.get_time().as_u64()
+            )
+        );
+    }
+
+    #[test]
+    fn hlc_clone() {
+        let hlc = HLCBuilder::new()
+            .with_clock(zero_clock)
+            .with_update_rate_limit(1, Duration::from_secs(1))
+            .build();
+        let handle = hlc.clone();
+        let ts = handle.new_timestamp();
+        assert_eq!(hlc.last_time.get(), *ts.get_time());
+
+        // the peers usage is shared too
+        let other_ts = HLC::default().new_timestamp();
+        let other_ts = Timestamp::new(NTP64(0), *other_ts.get_id());
+        assert!(hlc.update_with_timestamp(&other_ts).is_ok());
+        assert!(handle.update_with_timestamp(&other_ts).is_err());
+    }
+
+    #[test]
+    fn hlc_new() {
+        let id = ID::try_from([0x01]).unwrap();
+        let hlc = HLC::new(id, zero_clock, Duration::from_secs(1));
+        assert_eq!(hlc.get_id(), &id);
+        assert_eq!(*hlc.get_delta(), NTP64::from(Duration::from_secs(1)));
+        assert_eq!(*hlc.new_timestamp().get_time(), NTP64(1));
+    }
+
+    #[test]
+    fn hlc_build_checked() {
+        let period = Duration::from_secs(1);
+        let zero = Duration::ZERO;
+        assert!(HLCBuilder::new()
+            .with_update_rate_limit(10, period)
+            .with_drift_budget(period, period)
+            .with_quarantine(3, period)
+            .build_checked()
+            .is_ok());
+        assert!(matches!(
+            HLCBuilder::new().with_max_delta(zero).build_checked(),
+            Err(ConfigError::MaxDeltaTooSmall(_))
+        ));
+        assert!(matches!(
+            HLCBuilder::new()
+                .with_drift_budget(period, zero)
+                .build_checked(),
+            Err(ConfigError::ZeroPeriod("drift budget"))
+        ));
+        let err = HLCBuilder::new()
+            .with_quarantine(3, zero)
+            .build_checked()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid HLC configuration: the period of the quarantine is 0"
+        );
+    }
+
+    #[cfg(feature = "no-env")]
+    #[test]
+    fn hlc_no_env() {
+        std::env::set_var("UHLC_MAX_DELTA_MS", "1");
+        let hlc = HLC::default();
+        assert_eq!(
+            *hlc.get_delta(),
+            NTP64::from(Duration::from_millis(DEFAULT_DELTA_MS))
+        );
+    }
+
+    #[test]
+    fn hlc_rotate_id() {
+        let mut hlc = HLCBuilder::new()
+            .with_id(ID::try_from([0xff]).unwrap())
+            .with_clock(zero_clock)
+            .build();
+        let handle = hlc.clone();
+        let before = hlc.new_timestamp();
+        // a lower ID doesn't break the monotonicity
+        let new_id = ID::try_from([0x01]).unwrap();
+        hlc.rotate_id(new_id).unwrap();
+        assert_eq!(hlc.rotate_id(new_id), Err(RotateIdError::SameId(new_id)));
+        let after = hlc.new_timestamp();
+        assert!(after > before);
+        assert_eq!(after.get_id(), &new_id);
+        // the clone made before keeps its ID, but shares the latest time
+        let other = handle.new_timestamp();
+        assert_eq!(other.get_id(), before.get_id());
+        assert!(other > after);
+        assert!(hlc.certify(&before).is_err());
+    }
+
+    #[test]
+    fn hlc_time_quantization() {
+        let hlc = HLCBuilder::new()
+            .with_time_quantization(Duration::from_secs(1))
+            .build();
+        let second = NTP64::from(Duration::from_secs(1_000_000));
+        let mut prev = hlc.new_timestamp_at(second + NTP64(1 << 20));
+        assert_eq!(*prev.get_time(), second);
+        for i in 2..100 {
+            let ts = hlc.new_timestamp_at(second + NTP64(i << 20));
+            assert!(ts > prev);
+            assert_eq!(ts.get_time().as_secs(), 1_000_000);
+            prev = ts;
+        }
+        // the next quantum starts at the next second
+        let next = hlc.new_timestamp_at(second + NTP64::from(Duration::from_millis(1500)));
+        assert!(next > prev);
+        assert_eq!(next.get_time().as_secs(), 1_000_001);
+        assert_eq!(next.get_time().subsec_nanos(), 0);
+
+        assert_eq!(
+            HLCBuilder::new()
+                .with_time_quantization(Duration::ZERO)
+                .build_checked()
+                .err(),
+            Some(ConfigError::ZeroPeriod("time quantization"))
+        );
+    }
+
+    #[test]
+    fn hlc_time_jitter() {
+        let max_jitter = Duration::from_millis(100);
+        let id = ID::try_from([0x33]).unwrap();
+        let hlc = HLCBuilder::new()
+            .with_id(id)
+            .with_time_jitter(max_jitter)
+            .build();
+        let now = NTP64::from(Duration::from_secs(1_000_000));
+        let ts = hlc.new_timestamp_at(now);
+        let jitter = NTP64(id.stable_hash64() % NTP64::from(max_jitter).0);
+        assert_eq!(ts.get_time().0, (now - jitter).0 & LMASK);
+        assert!(now - *ts.get_time() <= NTP64::from(max_jitter));
+        // the same ID has the same jitter, while keeping monotonic
+        let other = HLCBuilder::new()
+            .with_id(id)
+            .with_time_jitter(max_jitter)
+            .build();
+        assert_eq!(other.new_timestamp_at(now), ts);
+        assert!(hlc.new_timestamp_at(now) > ts);
+
+        assert_eq!(
+            HLCBuilder::new()
+                .with_time_jitter(Duration::ZERO)
+                .build_checked()
+                .err(),
+            Some(ConfigError::ZeroPeriod("time jitter"))
+        );
+    }
+
+    #[test]
+    fn hlc_update_extreme() {
+        // a physical clock close to the maximum time, and a peer at the maximum time
+        let hlc = HLCBuilder::new()
+            .with_clock(|| NTP64(u64::MAX - 0x100))
+            .build();
+        let peer = ID::rand();
+        let max = Timestamp::new(NTP64(u64::MAX), peer);
+        let result = hlc.update_with_timestamp(&max);
+        assert!(matches!(result, Err(UpdateError::FrontierOverflow { .. })));
+        assert!(hlc.current_time() < NTP64(u64::MAX));
+        let almost_max = Timestamp::new(NTP64(u64::MAX - 1), peer);
+        assert!(hlc.update_with_timestamp(&almost_max).is_ok());
+        assert_eq!(hlc.current_time(), NTP64(u64::MAX));
+        // then any update would make the time overflow
+        let result = hlc.update_with_timestamp(&Timestamp::new(NTP64(0), peer));
+        assert!(matches!(result, Err(UpdateError::FrontierOverflow { .. })));
+
+        // a physical clock at the maximum time
+        let hlc = HLCBuilder::new().with_clock(|| NTP64(u64::MAX)).build();
+        assert!(hlc.update_with_timestamp(&max).is_err());
+        assert!(hlc.update_with_timestamp(&almost_max).is_ok());
+        // a peer far in the past or in the future
+        assert!(hlc
+            .update_with_timestamp(&Timestamp::new(NTP64(0), peer))
+            .is_err());
+        let hlc = HLC::default();
+        assert!(matches!(
+            hlc.update_with_timestamp(&max),
+            Err(UpdateError::DeltaExceeded { .. })
+        ));
+
+        // an overflowing update doesn't use the peer's drift budget, nor count towards its quarantine
+        let hour = Duration::from_secs(3600);
+        let hlc = HLCBuilder::new()
+            .with_clock(|| NTP64(u64::MAX - (1 << 32)))
+            .with_max_delta(Duration::from_secs(2))
+            .with_drift_budget(Duration::from_millis(1500), hour)
+            .with_quarantine(0, hour)
+            .build();
+        let result = hlc.update_with_timestamp(&max);
+        assert!(matches!(result, Err(UpdateError::FrontierOverflow { .. })));
+        assert!(!hlc.is_quarantined(&peer));
+        assert!(hlc.update_with_timestamp(&almost_max).is_ok());
+    }
+
+    #[test]
+    fn hlc_stamp_iter() {
+        let hlc = HLC::default();
+        let items: Vec<usize> = (0..100).collect();
+        let stamped: Vec<_> = hlc.stamp_iter(items.iter().copied()).collect();
+        for (i, pair) in stamped.windows(2).enumerate() {
+            assert_eq!(pair[0].1, i);
+            assert!(pair[1].0 > pair[0].0);
+        }
+    }
+
+    #[test]
+    fn hlc_frontier() {
+        let old = HLCBuilder::new().with_clock(zero_clock).build();
+        let new = HLCBuilder::new().with_clock(zero_clock).build();
+        assert_eq!(old.frontier(), NTP64(0));
+        let far = NTP64::from(Duration::from_secs(3600));
+        old.advance_frontier(far);
+        // a lower frontier doesn't move it back
+        old.advance_frontier(NTP64(1));
+        let last = old.new_timestamp();
+        assert_eq!(*last.get_time(), far + 1);
+        assert_eq!(old.frontier(), far + 1);
+
+        new.advance_frontier(old.frontier());
+        let next = new.new_timestamp();
+        assert!(next.get_time() > last.get_time());
+    }
+
+    #[test]
+    fn hlc_logical_lead() {
+        let hlc = HLCBuilder::new().with_clock(zero_clock).build();
+        assert_eq!(hlc.logical_lead(), Duration::ZERO);
+        hlc.new_timestamp();
+        assert_eq!(hlc.logical_lead(), NTP64(1).to_duration());
+        let ahead = NTP64::from(Duration::from_millis(300));
+        hlc.update_with_timestamp(&Timestamp::new(ahead, ID::rand()))
+            .unwrap();
+        assert_eq!(hlc.logical_lead(), Duration::from_millis(300));
+
+        let hlc = HLCBuilder::new().with_clock(system_time_clock).build();
+        hlc.new_timestamp();
+        assert_eq!(hlc.logical_lead(), Duration::ZERO);
+    }
+
+    #[test]
+    fn hlc_max_logical_lead() {
+        let max = NTP64::from(Duration::from_nanos(1));
+        let hlc = HLCBuilder::new()
+            .with_clock(zero_clock)
+            .with_max_logical_lead(max.to_duration())
+            .build();
+        for _ in 0..max.0 {
+            hlc.try_new_timestamp().unwrap();
+        }
+        assert_eq!(
+            hlc.try_new_timestamp(),
+            Err(LeadError::MaxLeadExceeded { lead: max + 1, max })
+        );
+        // not enforced with an explicit clock reading
+        assert_eq!(*hlc.new_timestamp_with_now(NTP64(0)).get_time(), max + 1);
+
+        #[cfg(feature = "std")]
+        {
+            // concurrent issuances can't exceed the maximum together
+            let max_lead = Duration::from_micros(1);
+            let hlc = HLCBuilder::new()
+                .with_clock(zero_clock)
+                .with_max_logical_lead(max_lead)
+                .build();
+            let threads: Vec<_> = (0..4)
+                .map(|_| {
+                    let hlc = hlc.clone();
+                    std::thread::spawn(move || {
+                        (0..10_000)
+                            .filter(|_| hlc.try_new_timestamp().is_ok())
+                            .count() as u64
+                    })
+                })
+                .collect();
+            let issued: u64 = threads.into_iter().map(|t| t.join().unwrap()).sum();
+            assert_eq!(issued, NTP64::from(max_lead).0);
+            assert_eq!(hlc.logical_lead(), max_lead);
+        }
+
+        #[cfg(feature = "std")]
+        {
+            // new_timestamp() waits for the physical clock to catch up with an accepted stamp
+            let max_lead = Duration::from_millis(10);
+            let hlc = HLCBuilder::new()
+                .with_clock(system_time_clock)
+                .with_max_logical_lead(max_lead)
+                .build();
+            let ahead = hlc.read_clock() + NTP64::from(Duration::from_millis(50));
+            hlc.update_with_timestamp(&Timestamp::new(ahead, ID::rand()))
+                .unwrap();
+            assert!(hlc.try_new_timestamp().is_err());
+            let ts = hlc.new_timestamp();
+            assert!(*ts.get_time() > ahead);
+            assert!(hlc.logical_lead() <= max_lead);
+        }
+    }
+
+    #[test]
+    fn hlc_new_timestamp_with_now() {
+        let hlc = HLCBuilder::new().with_clock(zero_clock).build();
+        assert_eq!(hlc.read_clock(), NTP64(0));
+        let now = NTP64::from(Duration::from_secs(10));
+        let first = hlc.new_timestamp_with_now(now);
+        assert_eq!(*first.get_time(), now);
+        // an outdated reading only increases the logical counter
+        let second = hlc.new_timestamp_with_now(now);
+        assert_eq!(*second.get_time(), now + 1);
+        let third = hlc.new_timestamp();
+        assert_eq!(*third.get_time(), now + 2);
+    }
+
+    #[cfg(any(debug_assertions, feature = "paranoid"))]
+    #[test]
+    #[should_panic(expected = "HLC invariant violated: counter overflows the maximum time")]
+    fn hlc_invariant() {
+        let hlc = HLCBuilder::new().with_clock(|| NTP64(u64::MAX)).build();
+        // the maximum physical time, then all its counter values, then an overflow
+        for _ in 0..=CMASK + 1 {
+            hlc.new_timestamp();
+        }
+    }
+
+    #[test]
+    fn hlc_global_clock() {
+        use core::sync::atomic::{AtomicUsize, Ordering};
+
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        fn counting_clock() -> NTP64 {
+            CALLS.fetch_add(1, Ordering::Relaxed);
+            system_time_clock()
+        }
+
+        // the HLC created before the installation of the clock also relies on it
+        // (notice that other tests running in parallel may also call the clock)
+        let hlc = HLC::default();
+        set_global_clock(counting_clock);
+        hlc.new_timestamp();
+        let calls = CALLS.load(Ordering::Relaxed);
+        assert!(calls >= 1);
+        HLC::default().new_timestamp();
+        assert!(CALLS.load(Ordering::Relaxed) > calls);
+        // restore the default clock for the other tests
+        set_global_clock(system_time_clock);
+    }
+
+    #[test]
+    fn hlc_certify() {
+        let id: ID = ID::rand();
+        let hlc = HLCBuilder::new().with_id(id).build();
+        let ts = hlc.new_timestamp();
+
+        let certificate = hlc.certify(&ts).unwrap();
+        assert_eq!(certificate.timestamp, ts);
+        assert_eq!(certificate.issuer, id);
+        assert_eq!(certificate.frontier, *ts.get_time());
+
+        // a timestamp from another HLC, even received by this HLC, is not certified
+        let other_ts = HLC::default().new_timestamp();
+        hlc.update_with_timestamp(&other_ts).unwrap();
+        assert!(matches!(
+            hlc.certify(&other_ts),
+            Err(CertifyError::ForeignId { .. })
+        ));
+
+        // a timestamp that this HLC didn't issue yet is not certified
+        let future_ts = Timestamp::new(*hlc.new_timestamp().get_time() + 1, id);
+        assert!(matches!(
+            hlc.certify(&future_ts),
+            Err(CertifyError::AfterFrontier { .. })
+        ));
+    }
 }
@@ -0,0 +1,84 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{Timestamp, LMASK};
+use core::cmp::Ordering;
+
+/// What decided the winner of [`resolve_lww()`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Resolution {
+    /// The [`Timestamp`]s have different physical times (i.e. without their counter part).
+    Time,
+    /// The [`Timestamp`]s have the same physical time, but different counters.
+    Counter,
+    /// The [`Timestamp`]s have the same time, the tie was broken by their [`crate::ID`].
+    Id,
+    /// The [`Timestamp`]s are identical: the first value wins.
+    Identical,
+}
+
+/// Resolve a conflict between 2 timestamped values with the "Last Writer Wins" policy,
+/// returning the value with the greatest [`Timestamp`] and what decided it.
+///
+/// The winner is the same as with the [`Ord`] implementation of [`Timestamp`]:
+/// the greatest time wins and, for equal times, the greatest [`crate::ID`] wins.
+///
+/// # Examples
+///
+/// ```
+/// use uhlc::{resolve_lww, Resolution, HLC};
+///
+/// let hlc = HLC::default();
+/// let first = (hlc.new_timestamp(), "first write");
+/// let second = (hlc.new_timestamp(), "second write");
+/// let ((_, winner), resolution) = resolve_lww(first, second);
+/// assert_eq!(winner, "second write");
+/// assert!(resolution == Resolution::Time || resolution == Resolution::Counter);
+/// ```
+pub fn resolve_lww<T>(a: (Timestamp, T), b: (Timestamp, T)) -> ((Timestamp, T), Resolution) {
+    let (ta, tb) = (a.0.get_time().as_u64(), b.0.get_time().as_u64());
+    let resolution = if ta & LMASK != tb & LMASK {
+        Resolution::Time
+    } else if ta != tb {
+        Resolution::Counter
+    } else if a.0.get_id() != b.0.get_id() {
+        Resolution::Id
+    } else {
+        Resolution::Identical
+    };
+    match a.0.cmp(&b.0) {
+        Ordering::Less => (b, resolution),
+        Ordering::Equal | Ordering::Greater => (a, resolution),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use core::convert::TryFrom;
+
+    #[test]
+    fn lww_resolution() {
+        let id1 = ID::try_from([0x01]).unwrap();
+        let id2 = ID::try_from([0x02]).unwrap();
+        // times with a physical part `t` and a logical counter `c`
+        let ts = |t: u64, c: u64, id: ID| Timestamp::new(NTP64((t << CSIZE) + c), id);
+
+        let (winner, resolution) = resolve_lww((ts(0x10, 0, id2), 'a'), (ts(0x20, 0, id1), 'b'));
+        assert_eq!((winner.1, resolution), ('b', Resolution::Time));
+        let (winner, resolution) = resolve_lww((ts(0x10, 2, id1), 'a'), (ts(0x10, 1, id2), 'b'));
+        assert_eq!((winner.1, resolution), ('a', Resolution::Counter));
+        let (winner, resolution) = resolve_lww((ts(0x10, 0, id2), 'a'), (ts(0x10, 0, id1), 'b'));
+        assert_eq!((winner.1, resolution), ('a', Resolution::Id));
+        let (winner, resolution) = resolve_lww((ts(0x10, 0, id1), 'a'), (ts(0x10, 0, id1), 'b'));
+        assert_eq!((winner.1, resolution), ('a', Resolution::Identical));
+    }
+}
@@ -0,0 +1,142 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::Timestamp;
+use alloc::{collections::BinaryHeap, vec::Vec};
+use core::cmp::Ordering;
+
+// The next item of a source, ordered so the lowest Timestamp (then the first source) is the top of the heap
+struct Head<T> {
+    timestamp: Timestamp,
+    source: usize,
+    value: T,
+}
+
+impl<T> PartialEq for Head<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for Head<T> {}
+
+impl<T> PartialOrd for Head<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Head<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (other.timestamp, other.source).cmp(&(self.timestamp, self.source))
+    }
+}
+
+/// The iterator returned by [`merge_sorted()`].
+pub struct MergeSorted<I, T> {
+    sources: Vec<I>,
+    heads: BinaryHeap<Head<T>>,
+}
+
+impl<I: Iterator<Item = (Timestamp, T)>, T> MergeSorted<I, T> {
+    fn pull(&mut self, source: usize) {
+        if let Some((timestamp, value)) = self.sources[source].next() {
+            self.heads.push(Head {
+                timestamp,
+                source,
+                value,
+            });
+        }
+    }
+}
+
+impl<I: Iterator<Item = (Timestamp, T)>, T> Iterator for MergeSorted<I, T> {
+    type Item = (Timestamp, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let head = self.heads.pop()?;
+        self.pull(head.source);
+        Some((head.timestamp, head.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.sources.iter().fold(
+            (self.heads.len(), Some(self.heads.len())),
+            |(low, high), source| {
+                let (source_low, source_high) = source.size_hint();
+                (
+                    low.saturating_add(source_low),
+                    high.zip(source_high).and_then(|(a, b)| a.checked_add(b)),
+                )
+            },
+        )
+    }
+}
+
+/// Merge iterators of items sorted by [`Timestamp`] (e.g. the logs of several peers) into a single iterator
+/// of all their items sorted by [`Timestamp`], e.g. to replay them as a single causally-consistent stream.
+///
+/// As the [`Timestamp`]s are ordered by time then by [`crate::ID`], the items with the same time are merged
+/// in the order of their [`crate::ID`]s. The items with equal [`Timestamp`]s are merged in the order of their iterators.
+///
+/// Each iterator is expected to be sorted: otherwise, the merged items aren't sorted either.
+///
+/// # Examples
+///
+/// ```
+/// use uhlc::{merge_sorted, HLC};
+///
+/// let (hlc1, hlc2) = (HLC::default(), HLC::default());
+/// let log1: Vec<_> = hlc1.stamp_iter(["a", "b", "c"]).collect();
+/// let log2: Vec<_> = hlc2.stamp_iter(["x", "y"]).collect();
+///
+/// let merged: Vec<_> = merge_sorted([log1.into_iter(), log2.into_iter()]).collect();
+/// assert_eq!(merged.len(), 5);
+/// assert!(merged.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+/// ```
+pub fn merge_sorted<S, I, T>(iters: S) -> MergeSorted<I, T>
+where
+    S: IntoIterator<Item = I>,
+    I: Iterator<Item = (Timestamp, T)>,
+{
+    let sources: Vec<I> = iters.into_iter().collect();
+    let mut merge = MergeSorted {
+        heads: BinaryHeap::with_capacity(sources.len()),
+        sources,
+    };
+    for source in 0..merge.sources.len() {
+        merge.pull(source);
+    }
+    merge
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use alloc::vec::Vec;
+    use core::convert::TryFrom;
+
+    #[test]
+    fn merge_sorted_logs() {
+        let id = |n: u8| ID::try_from([n]).unwrap();
+        let ts = |t: u64, n: u8| Timestamp::new(NTP64(t), id(n));
+        let log1 = [(ts(1, 1), 'a'), (ts(3, 1), 'b'), (ts(5, 1), 'c')];
+        let log2 = [(ts(2, 2), 'x'), (ts(3, 2), 'y')];
+        let log3 = [(ts(3, 1), 'd'), (ts(4, 3), 'z')];
+        let merged = merge_sorted([log1.iter(), log2.iter(), log3.iter()].map(|log| log.copied()));
+        assert_eq!(merged.size_hint(), (7, Some(7)));
+        let values: Vec<char> = merged.map(|(_, value)| value).collect();
+        // same time: by ID, then by iterator for equal Timestamps
+        assert_eq!(values, ['a', 'x', 'b', 'd', 'y', 'z', 'c']);
+
+        let empty: [core::iter::Empty<(Timestamp, ())>; 0] = [];
+        assert_eq!(merge_sorted(empty).next(), None);
+    }
+}
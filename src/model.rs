@@ -0,0 +1,164 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{Timestamp, ID, LMASK, NTP64};
+use core::cmp;
+
+/// The state of the reference model of an [`crate::HLC`] (see [`hlc_step()`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct HlcState {
+    /// The [`ID`] of the HLC.
+    pub id: ID,
+    /// The maximum delta of the HLC (see [`crate::HLCBuilder::with_max_delta()`]).
+    pub max_delta: NTP64,
+    /// The latest time of the HLC: the time of the latest issued or accepted [`Timestamp`].
+    pub last_time: NTP64,
+}
+
+impl HlcState {
+    /// The initial state of an HLC with `id` and `max_delta`.
+    pub fn new(id: ID, max_delta: NTP64) -> HlcState {
+        HlcState {
+            id,
+            max_delta,
+            last_time: NTP64(0),
+        }
+    }
+}
+
+/// An input of the reference model of an [`crate::HLC`] (see [`hlc_step()`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HlcInput {
+    /// A call to [`crate::HLC::new_timestamp()`], the physical clock returning `now`.
+    NewTimestamp { now: NTP64 },
+    /// A call to [`crate::HLC::update_with_timestamp()`], the physical clock returning `now`.
+    Update { now: NTP64, timestamp: Timestamp },
+}
+
+/// An output of the reference model of an [`crate::HLC`] (see [`hlc_step()`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HlcOutput {
+    /// The [`Timestamp`] returned by [`crate::HLC::new_timestamp()`].
+    Issued(Timestamp),
+    /// The update was accepted.
+    Accepted,
+    /// The update was rejected.
+    Rejected,
+}
+
+/// A pure reference model of the semantics of an [`crate::HLC`]: returns the next state and the output
+/// of the HLC in `state` for `input`, without any lock nor physical clock.
+///
+/// It mirrors [`crate::HLC::new_timestamp()`] and [`crate::HLC::update_with_timestamp()`] with the default
+/// configuration (i.e. without limits per peer), so it can be used to model-check wrappers of [`crate::HLC`],
+/// or to compare them against the stateful implementation.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use uhlc::{hlc_step, HlcInput, HlcOutput, HlcState, Timestamp, ID, NTP64};
+///
+/// let id = ID::try_from([0x01]).unwrap();
+/// let state = HlcState::new(id, NTP64(1 << 32));
+/// let (state, output) = hlc_step(state, HlcInput::NewTimestamp { now: NTP64(0x10000) });
+/// assert_eq!(output, HlcOutput::Issued(Timestamp::new(NTP64(0x10000), id)));
+///
+/// let peer = Timestamp::new(NTP64(0x20000), ID::try_from([0x02]).unwrap());
+/// let (state, output) = hlc_step(state, HlcInput::Update { now: NTP64(0x10000), timestamp: peer });
+/// assert_eq!(output, HlcOutput::Accepted);
+/// assert_eq!(state.last_time, NTP64(0x20001));
+/// ```
+pub fn hlc_step(state: HlcState, input: HlcInput) -> (HlcState, HlcOutput) {
+    match input {
+        HlcInput::NewTimestamp { now } => {
+            let now = NTP64(now.0 & LMASK);
+            let last_time = if now.0 > (state.last_time.0 & LMASK) {
+                now
+            } else {
+                state.last_time + 1
+            };
+            (
+                HlcState { last_time, ..state },
+                HlcOutput::Issued(Timestamp::new(last_time, state.id)),
+            )
+        }
+        HlcInput::Update { now, timestamp } => {
+            let now = NTP64(now.0 & LMASK);
+            let msg_time = *timestamp.get_time();
+            if msg_time > now && msg_time - now > state.max_delta {
+                return (state, HlcOutput::Rejected);
+            }
+            let max_time = cmp::max(cmp::max(now, msg_time), state.last_time);
+            let last_time = if max_time == now {
+                Some(now)
+            } else if max_time == msg_time {
+                msg_time.0.checked_add(1).map(NTP64)
+            } else {
+                state.last_time.0.checked_add(1).map(NTP64)
+            };
+            match last_time {
+                Some(last_time) => (HlcState { last_time, ..state }, HlcOutput::Accepted),
+                // the time would overflow
+                None => (state, HlcOutput::Rejected),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use core::convert::TryFrom;
+    use rand::Rng;
+
+    #[test]
+    fn model_vs_hlc() {
+        let mut rng = rand::thread_rng();
+        let id = ID::try_from([0x01]).unwrap();
+        let delta = NTP64(0x1000);
+        let hlc = HLCBuilder::new()
+            .with_id(id)
+            .with_clock(zero_clock)
+            .with_max_delta(delta.to_duration())
+            .build();
+        let mut state = HlcState::new(id, *hlc.get_delta());
+        let mut now = 0u64;
+        for _ in 0..10_000 {
+            // a physical clock mostly increasing, sometimes going backward
+            now = now.saturating_add_signed(rng.gen_range(-0x40..0x100));
+            let input = if rng.gen_bool(0.5) {
+                HlcInput::NewTimestamp { now: NTP64(now) }
+            } else {
+                let time = NTP64(now.saturating_add_signed(rng.gen_range(-0x2000..0x2000)));
+                HlcInput::Update {
+                    now: NTP64(now),
+                    timestamp: Timestamp::new(time, ID::rand()),
+                }
+            };
+            let expected;
+            (state, expected) = hlc_step(state, input);
+            let output = match input {
+                HlcInput::NewTimestamp { now } => HlcOutput::Issued(hlc.new_timestamp_at(now)),
+                HlcInput::Update { now, timestamp } => {
+                    match hlc.update_with_timestamp_at(&timestamp, now) {
+                        Ok(()) => HlcOutput::Accepted,
+                        Err(_) => HlcOutput::Rejected,
+                    }
+                }
+            };
+            assert_eq!(output, expected, "for input {input:?}");
+            assert_eq!(hlc.last_time.get(), state.last_time);
+        }
+    }
+}
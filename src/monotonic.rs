@@ -0,0 +1,193 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{Timestamp, ID};
+use alloc::collections::BTreeMap;
+use core::fmt;
+
+/// A validator checking that a sequence of [`Timestamp`]s is strictly increasing.
+///
+/// By default the whole sequence must be strictly increasing. Created with [`MonotonicChecker::per_id()`],
+/// only the [`Timestamp`]s with a same [`ID`] must be strictly increasing (e.g. when ingesting
+/// the interleaved timestamps of several HLCs).
+///
+/// # Examples
+///
+/// ```
+/// use uhlc::{MonotonicChecker, ViolationKind, HLC};
+///
+/// let hlc = HLC::default();
+/// let ts1 = hlc.new_timestamp();
+/// let ts2 = hlc.new_timestamp();
+///
+/// let mut checker = MonotonicChecker::new();
+/// assert!(checker.check(&ts1).is_ok());
+/// assert!(checker.check(&ts2).is_ok());
+/// let violation = checker.check(&ts1).unwrap_err();
+/// assert_eq!(violation.kind, ViolationKind::Regression);
+/// assert_eq!(violation.index, 2);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MonotonicChecker {
+    per_id: bool,
+    last: BTreeMap<Option<ID>, Timestamp>,
+    count: u64,
+}
+
+impl MonotonicChecker {
+    /// Create a new [`MonotonicChecker`] checking that the whole sequence is strictly increasing.
+    pub fn new() -> MonotonicChecker {
+        MonotonicChecker::default()
+    }
+
+    /// Create a new [`MonotonicChecker`] checking that the [`Timestamp`]s with a same [`ID`]
+    /// are strictly increasing.
+    pub fn per_id() -> MonotonicChecker {
+        MonotonicChecker {
+            per_id: true,
+            ..Default::default()
+        }
+    }
+
+    /// Check the next [`Timestamp`] of the sequence.
+    ///
+    /// A [`Violation`] is returned if it's not greater than the previous one.
+    /// In such case, the sequence keeps on being checked against the previous [`Timestamp`].
+    pub fn check(&mut self, timestamp: &Timestamp) -> Result<(), Violation> {
+        let index = self.count;
+        self.count += 1;
+        let key = if self.per_id {
+            Some(*timestamp.get_id())
+        } else {
+            None
+        };
+        match self.last.get_mut(&key) {
+            Some(previous) if *timestamp <= *previous => Err(Violation {
+                kind: if timestamp == previous {
+                    ViolationKind::Duplicate
+                } else {
+                    ViolationKind::Regression
+                },
+                index,
+                previous: *previous,
+                timestamp: *timestamp,
+            }),
+            Some(previous) => {
+                *previous = *timestamp;
+                Ok(())
+            }
+            None => {
+                self.last.insert(key, *timestamp);
+                Ok(())
+            }
+        }
+    }
+
+    /// Check all the [`Timestamp`]s of `timestamps`, stopping at the first [`Violation`].
+    pub fn check_all<I>(&mut self, timestamps: I) -> Result<(), Violation>
+    where
+        I: IntoIterator<Item = Timestamp>,
+    {
+        timestamps.into_iter().try_for_each(|ts| self.check(&ts))
+    }
+
+    /// Returns the number of [`Timestamp`]s checked so far.
+    #[inline]
+    pub fn get_count(&self) -> u64 {
+        self.count
+    }
+}
+
+/// The kind of a [`Violation`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ViolationKind {
+    /// The [`Timestamp`] is lower than the previous one.
+    Regression,
+    /// The [`Timestamp`] is equal to the previous one.
+    Duplicate,
+}
+
+/// The error returned by a [`MonotonicChecker`] when a [`Timestamp`] is not greater than the previous one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Violation {
+    /// The kind of violation.
+    pub kind: ViolationKind,
+    /// The index of the [`Timestamp`] in the checked sequence.
+    pub index: u64,
+    /// The previous [`Timestamp`] of the sequence (with the same [`ID`] when checking per ID).
+    pub previous: Timestamp,
+    /// The violating [`Timestamp`].
+    pub timestamp: Timestamp,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            ViolationKind::Regression => "Regression",
+            ViolationKind::Duplicate => "Duplicate",
+        };
+        write!(
+            f,
+            "{kind} at index {}: {} after {} ({})",
+            self.index,
+            self.timestamp,
+            self.previous,
+            self.timestamp.format_diff(&self.previous)
+        )
+    }
+}
+
+impl core::error::Error for Violation {}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use core::convert::TryFrom;
+
+    #[test]
+    fn monotonic_checker() {
+        let id1 = ID::try_from([0x01]).unwrap();
+        let id2 = ID::try_from([0x02]).unwrap();
+        let ts = |t: u64, id: ID| Timestamp::new(NTP64(t), id);
+
+        // interleaved sequences of 2 IDs are monotonic per ID, but not globally
+        let seq = [ts(10, id1), ts(5, id2), ts(11, id1), ts(6, id2)];
+        assert!(MonotonicChecker::per_id().check_all(seq).is_ok());
+        let mut checker = MonotonicChecker::new();
+        let violation = checker.check_all(seq).unwrap_err();
+        assert_eq!(violation.kind, ViolationKind::Regression);
+        assert_eq!(violation.index, 1);
+        assert_eq!(violation.previous, ts(10, id1));
+        assert_eq!(
+            violation.to_string(),
+            "Regression at index 1: 5/2 after 10/1 (-1ns)"
+        );
+
+        // the sequence keeps on being checked against the greatest timestamp
+        assert!(checker.check(&ts(10, id1)).is_err());
+        assert_eq!(
+            checker.check(&ts(10, id1)).unwrap_err().kind,
+            ViolationKind::Duplicate
+        );
+        assert!(checker.check(&ts(12, id1)).is_ok());
+        assert_eq!(checker.get_count(), 5);
+
+        // the timestamps of HLCs are monotonic per ID, even interleaved
+        let (hlc1, hlc2) = (HLC::default(), HLC::default());
+        let mut checker = MonotonicChecker::per_id();
+        for _ in 0..1000 {
+            assert!(checker.check(&hlc1.new_timestamp()).is_ok());
+            assert!(checker.check(&hlc2.new_timestamp()).is_ok());
+        }
+        assert_eq!(checker.get_count(), 2000);
+    }
+}
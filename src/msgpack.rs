@@ -0,0 +1,165 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{DecodeError, Timestamp, KEY_SIZE};
+use alloc::vec::Vec;
+use core::fmt;
+use rmp::{
+    decode::{RmpRead, RmpReadErr, ValueReadError},
+    encode::{RmpWrite, ValueWriteError},
+};
+
+/// The size of a [`Timestamp`] encoded as a MessagePack extension (see [`Timestamp::write_msgpack()`]):
+/// the `ext 8` header (3 bytes) followed by the [`KEY_SIZE`] bytes of [`Timestamp::to_key()`].
+pub const MSGPACK_SIZE: usize = 3 + KEY_SIZE;
+
+impl Timestamp {
+    /// The MessagePack extension type of a [`Timestamp`].
+    pub const MSGPACK_EXT_TYPE: i8 = 72;
+
+    /// Write this [`Timestamp`] as a MessagePack extension of type [`Timestamp::MSGPACK_EXT_TYPE`],
+    /// whose data are the [`KEY_SIZE`] bytes of [`Timestamp::to_key()`] (i.e. the time as big endian
+    /// followed by the le-encoded [`crate::ID`]). Thus, it can also be decoded in other languages.
+    ///
+    /// # Examples
+    /// ```
+    /// use uhlc::{Timestamp, ID, NTP64};
+    ///
+    /// let ts = Timestamp::new(NTP64(7386690599959157260), ID::rand());
+    /// let mut buf = Vec::new();
+    /// ts.write_msgpack(&mut buf).unwrap();
+    /// assert_eq!(&buf[..3], &[0xc7, 24, 72]);
+    /// assert_eq!(Timestamp::read_msgpack(&mut buf.as_slice()).unwrap(), ts);
+    /// ```
+    pub fn write_msgpack<W: RmpWrite>(&self, wr: &mut W) -> Result<(), ValueWriteError<W::Error>> {
+        rmp::encode::write_ext_meta(wr, KEY_SIZE as u32, Self::MSGPACK_EXT_TYPE)?;
+        wr.write_bytes(&self.to_key())
+            .map_err(ValueWriteError::InvalidDataWrite)
+    }
+
+    /// Encode this [`Timestamp`] as a MessagePack extension (see [`Timestamp::write_msgpack()`]).
+    pub fn to_msgpack(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(MSGPACK_SIZE);
+        self.write_msgpack(&mut buf)
+            .expect("writing to a Vec can't fail");
+        buf
+    }
+
+    /// Read a [`Timestamp`] written with [`Timestamp::write_msgpack()`].
+    ///
+    /// If the MessagePack value isn't an extension of type [`Timestamp::MSGPACK_EXT_TYPE`] and of [`KEY_SIZE`] bytes,
+    /// [`MsgpackError::UnexpectedExt`] is returned after reading only the extension's header.
+    pub fn read_msgpack<R: RmpRead>(rd: &mut R) -> Result<Timestamp, MsgpackError<R::Error>> {
+        let meta = rmp::decode::read_ext_meta(rd).map_err(MsgpackError::Read)?;
+        if meta.typeid != Self::MSGPACK_EXT_TYPE || meta.size != KEY_SIZE as u32 {
+            return Err(MsgpackError::UnexpectedExt {
+                typeid: meta.typeid,
+                size: meta.size,
+            });
+        }
+        let mut key = [0u8; KEY_SIZE];
+        rd.read_exact_buf(&mut key)
+            .map_err(|e| MsgpackError::Read(ValueReadError::InvalidDataRead(e)))?;
+        Timestamp::from_key(&key).map_err(MsgpackError::Decode)
+    }
+}
+
+/// An error returned by [`Timestamp::read_msgpack()`].
+#[derive(Debug)]
+pub enum MsgpackError<E: RmpReadErr> {
+    /// The MessagePack value failed to be read, or isn't an extension.
+    Read(ValueReadError<E>),
+    /// The MessagePack value is an extension, but not of a [`Timestamp`].
+    UnexpectedExt { typeid: i8, size: u32 },
+    /// The extension's data isn't a valid [`Timestamp`] (e.g. with a zero [`crate::ID`]).
+    Decode(DecodeError),
+}
+
+impl<E: RmpReadErr> fmt::Display for MsgpackError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MsgpackError::Read(ValueReadError::InvalidMarkerRead(e)) => {
+                write!(f, "Failed to read MessagePack marker: {}", e)
+            }
+            MsgpackError::Read(ValueReadError::InvalidDataRead(e)) => {
+                write!(f, "Failed to read MessagePack data: {}", e)
+            }
+            MsgpackError::Read(ValueReadError::TypeMismatch(marker)) => write!(
+                f,
+                "Expected a MessagePack extension, found marker {:?}",
+                marker
+            ),
+            MsgpackError::UnexpectedExt { typeid, size } => write!(
+                f,
+                "Expected a MessagePack extension of type {} and {} bytes, found type {} and {} bytes",
+                Timestamp::MSGPACK_EXT_TYPE,
+                KEY_SIZE,
+                typeid,
+                size
+            ),
+            MsgpackError::Decode(e) => fmt::Display::fmt(e, f),
+        }
+    }
+}
+
+impl<E: RmpReadErr> core::error::Error for MsgpackError<E> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn msgpack_ext() {
+        let hlc = HLC::default();
+        let ts = hlc.new_timestamp();
+        let buf = ts.to_msgpack();
+        assert_eq!(buf.len(), MSGPACK_SIZE);
+        assert_eq!(&buf[3..], &ts.to_key());
+
+        // several timestamps in a MessagePack array
+        let mut array = alloc::vec::Vec::new();
+        rmp::encode::write_array_len(&mut array, 2).unwrap();
+        ts.write_msgpack(&mut array).unwrap();
+        hlc.new_timestamp().write_msgpack(&mut array).unwrap();
+        let mut rd = array.as_slice();
+        assert_eq!(rmp::decode::read_array_len(&mut rd).unwrap(), 2);
+        let first = Timestamp::read_msgpack(&mut rd).unwrap();
+        let second = Timestamp::read_msgpack(&mut rd).unwrap();
+        assert_eq!(first, ts);
+        assert!(second > first);
+        assert!(rd.is_empty());
+
+        // errors
+        let mut other = alloc::vec::Vec::new();
+        rmp::encode::write_ext_meta(&mut other, 24, -1).unwrap();
+        other.extend_from_slice(&[0; 24]);
+        assert!(matches!(
+            Timestamp::read_msgpack(&mut other.as_slice()),
+            Err(MsgpackError::UnexpectedExt {
+                typeid: -1,
+                size: 24
+            })
+        ));
+        let mut zero_id = buf.clone();
+        zero_id[3 + 8..].fill(0);
+        assert!(matches!(
+            Timestamp::read_msgpack(&mut zero_id.as_slice()),
+            Err(MsgpackError::Decode(_))
+        ));
+        assert!(matches!(
+            Timestamp::read_msgpack(&mut &buf[..20]),
+            Err(MsgpackError::Read(_))
+        ));
+        assert!(Timestamp::read_msgpack(&mut &[0x01u8][..])
+            .unwrap_err()
+            .to_string()
+            .contains("Expected a MessagePack extension"));
+    }
+}
@@ -8,15 +8,16 @@
 //
 // SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
 //
-use alloc::string::String;
-use core::fmt;
-use core::ops::{Add, AddAssign, Sub, SubAssign};
-use core::time::Duration;
+use super::{CMASK, LMASK};
+use alloc::{format, string::String};
+use core::fmt::{self, Write};
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
+use core::{str::FromStr, time::Duration};
 use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "std")]
 use {
-    core::str::FromStr,
+    crate::UtcOffset,
     humantime::format_rfc3339_nanos,
     std::time::{SystemTime, UNIX_EPOCH},
 };
@@ -48,14 +49,15 @@ const NANO_PER_SEC: u64 = 1_000_000_000;
 /// and the 2nd 32-bits part is the fraction of second.  
 /// In case it's part of a [`crate::Timestamp`] generated by an [`crate::HLC`] the last few bits
 /// of the Fraction part are replaced by the HLC logical counter.
-/// The size of this counter is currently hard-coded as [`crate::CSIZE`].
+/// The size of this counter is fixed at compile time as [`crate::CSIZE`] (see the `csize-*` features),
+/// and its bits are selected by [`crate::CMASK`] (see [`NTP64::counter()`] and [`NTP64::strip_counter()`]).
 ///
 /// ## Conversion to/from String
 /// 2 different String representations are supported:
 /// 1. **as an unsigned integer in decimal format**
 ///   - Such conversion is lossless and thus bijective.
 ///   - NTP64 to String: use [`std::fmt::Display::fmt()`] or [`std::string::ToString::to_string()`].
-///   - String to NTP64: use [`std::str::FromStr::from_str()`]
+///   - String to NTP64: use [`std::str::FromStr::from_str()`] or [`NTP64::from_decimal_str()`]
 /// 2. **as a [RFC3339](https://www.rfc-editor.org/rfc/rfc3339.html#section-5.8) (human readable) format**:
 ///   - Such conversion loses some precision because of rounding when conferting the fraction part to nanoseconds
 ///   - As a consequence it's not bijective: a NTP64 converted to RFC3339 String and then converted back to NTP64 might result to a different time.
@@ -67,16 +69,61 @@ const NANO_PER_SEC: u64 = 1_000_000_000;
 /// Only [`NTP64::to_system_time()`], [`NTP64::to_string_rfc3339_lossy()`] and [`std::fmt::Display::fmt()`] (when using `{:#}` alternate flag)
 /// operations assume that it's relative to UNIX_EPOCH (1st Jan 1970) to display the timestamp in RFC-3339 format.
 #[derive(Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Default, Deserialize, Serialize)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "bytemuck", derive(bytemuck::Pod, bytemuck::Zeroable))]
+#[repr(transparent)]
 pub struct NTP64(pub u64);
 
 impl NTP64 {
+    /// Create a NTP64 from its 32-bits Seconds part and its 32-bits Fraction part.
+    ///
+    /// # Examples
+    /// ```
+    /// use uhlc::NTP64;
+    ///
+    /// // 1.5 seconds after the EPOCH, usable as a constant
+    /// const T: NTP64 = NTP64::new(1, 1 << 31);
+    /// assert_eq!(T.as_secs_f64(), 1.5);
+    /// ```
+    #[inline]
+    pub const fn new(seconds: u32, fraction: u32) -> NTP64 {
+        NTP64(((seconds as u64) << 32) | fraction as u64)
+    }
+
     /// Returns this NTP64 as a u64.
     #[inline]
-    pub fn as_u64(&self) -> u64 {
+    pub const fn as_u64(&self) -> u64 {
         self.0
     }
 
+    /// Returns the HLC logical counter part: the [`crate::CSIZE`] lowest bits (see [`CMASK`]).
+    ///
+    /// # Examples
+    /// ```
+    /// use uhlc::NTP64;
+    ///
+    /// let t = NTP64::new(1, 0).with_counter(3);
+    /// assert_eq!(t.counter(), 3);
+    /// assert_eq!(t.strip_counter(), NTP64::new(1, 0));
+    /// ```
+    #[inline]
+    pub const fn counter(&self) -> u16 {
+        (self.0 & CMASK) as u16
+    }
+
+    /// Returns this NTP64 with its HLC logical counter part set to 0: the physical time,
+    /// as an [`crate::HLC`] reads it from its clock (see [`LMASK`]).
+    #[inline]
+    pub const fn strip_counter(self) -> NTP64 {
+        NTP64(self.0 & LMASK)
+    }
+
+    /// Returns this NTP64 with its HLC logical counter part set to `counter`,
+    /// truncated to its [`crate::CSIZE`] lowest bits (see [`CMASK`]).
+    #[inline]
+    pub const fn with_counter(self, counter: u16) -> NTP64 {
+        NTP64((self.0 & LMASK) | (counter as u64 & CMASK))
+    }
+
     /// Returns this NTP64 as a f64 in seconds.
     ///
     /// The integer part of the f64 is the NTP64's Seconds part.  
@@ -90,23 +137,107 @@ impl NTP64 {
         secs + subsec
     }
 
+    /// Returns the ratio of this NTP64 to `other` (i.e. `self / other`) as a f64.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use uhlc::NTP64;
+    ///
+    /// let inter_arrival = NTP64::from(Duration::from_millis(250));
+    /// let period = NTP64::from(Duration::from_secs(1));
+    /// assert_eq!((inter_arrival.ratio(&period) * 100.0).round(), 25.0);
+    /// ```
+    #[inline]
+    pub fn ratio(&self, other: &NTP64) -> f64 {
+        self.0 as f64 / other.0 as f64
+    }
+
     /// Returns the 32-bits seconds part.
     #[inline]
-    pub fn as_secs(&self) -> u32 {
+    pub const fn as_secs(&self) -> u32 {
         (self.0 >> 32) as u32
     }
 
-    /// Returns the 32-bits fraction of second part converted to nanoseconds.
+    /// Returns the 32-bits fraction of second part converted to nanoseconds,
+    /// rounded to the nearest nanosecond (half to even).
+    ///
+    /// The fractions closer to the next second than to 999,999,999 nanoseconds return 999,999,999,
+    /// to remain within the seconds part (while [`NTP64::to_duration()`] rounds them to the next second).
+    #[inline]
+    pub const fn subsec_nanos(&self) -> u32 {
+        let nanos = frac_to_nanos(self.0 & FRAC_MASK);
+        if nanos < NANO_PER_SEC {
+            nanos as u32
+        } else {
+            (NANO_PER_SEC - 1) as u32
+        }
+    }
+
+    /// Convert to a [`Duration`], rounded to the nearest nanosecond (half to even).
+    ///
+    /// Converting back the [`Duration`] with [`NTP64::from()`] and again to a [`Duration`] gives the same [`Duration`].
+    #[inline]
+    pub const fn to_duration(self) -> Duration {
+        Duration::new(
+            self.as_secs() as u64,
+            frac_to_nanos(self.0 & FRAC_MASK) as u32,
+        )
+    }
+
+    /// Returns this NTP64 as a number of nanoseconds, rounded to the nearest nanosecond (half to even).
+    ///
+    /// Such conversion loses the precision of the Fraction part that is lower than a nanosecond
+    /// (including the HLC logical counter).
     #[inline]
-    pub fn subsec_nanos(&self) -> u32 {
-        let frac = self.0 & FRAC_MASK;
-        ((frac * NANO_PER_SEC) / FRAC_PER_SEC) as u32
+    pub fn as_nanos(&self) -> u64 {
+        u64::from(self.as_secs()) * NANO_PER_SEC + frac_to_nanos(self.0 & FRAC_MASK)
     }
 
-    /// Convert to a [`Duration`].
+    /// Create a NTP64 from a number of nanoseconds.
+    ///
+    /// # Panics
+    /// If the number of seconds exceeds the 32-bits Seconds part (i.e. `nanos` is greater than ~136 years).
     #[inline]
-    pub fn to_duration(self) -> Duration {
-        Duration::new(self.as_secs().into(), self.subsec_nanos())
+    pub fn from_nanos(nanos: u64) -> NTP64 {
+        NTP64::from(Duration::from_nanos(nanos))
+    }
+
+    /// Returns a human-friendly representation of the signed difference between this NTP64 and `other`
+    /// (i.e. `self - other`), e.g.: `"+1.254s"`, `"-3ms"`, `"+12.5µs"`, `"-800ns"` or `"0s"`.
+    ///
+    /// The unit is the largest one (`s`, `ms`, `µs` or `ns`) where the difference is at least 1,
+    /// with up to 3 decimals (truncated, and without trailing zeros).
+    ///
+    /// # Examples
+    /// ```
+    /// use uhlc::NTP64;
+    /// use std::time::Duration;
+    ///
+    /// let t = NTP64::from(Duration::from_secs(1000));
+    /// assert_eq!((t + NTP64::from(Duration::from_millis(1254))).format_diff(&t), "+1.254s");
+    /// assert_eq!(t.format_diff(&(t + NTP64::from(Duration::from_millis(3)))), "-3ms");
+    /// ```
+    pub fn format_diff(&self, other: &NTP64) -> String {
+        let (sign, diff) = match self.cmp(other) {
+            core::cmp::Ordering::Equal => return "0s".into(),
+            core::cmp::Ordering::Greater => ('+', *self - *other),
+            core::cmp::Ordering::Less => ('-', *other - *self),
+        };
+        let nanos = diff.to_duration().as_nanos();
+        let (unit, name) = match nanos {
+            n if n >= 1_000_000_000 => (1_000_000_000, "s"),
+            n if n >= 1_000_000 => (1_000_000, "ms"),
+            n if n >= 1_000 => (1_000, "µs"),
+            _ => (1, "ns"),
+        };
+        let decimals = (nanos % unit) * 1000 / unit;
+        let mut s = format!("{sign}{}", nanos / unit);
+        if decimals > 0 {
+            s.push_str(format!(".{decimals:03}").trim_end_matches('0'));
+        }
+        s.push_str(name);
+        s
     }
 
     /// Convert to a [`SystemTime`] (making the assumption that this NTP64 is relative to [`UNIX_EPOCH`]).
@@ -123,18 +254,79 @@ impl NTP64 {
         format_rfc3339_nanos(self.to_system_time()).to_string()
     }
 
+    /// Parse a time representation into a NTP64, auto-detecting its format:
+    ///  * as an unsigned integer in decimal format if `s` is made of decimal digits only (see [`NTP64::from_decimal_str()`])
+    ///  * as a RFC3339 time representation otherwise (see [`NTP64::parse_rfc3339()`])
+    ///
+    /// In case of failure, the error's cause reports which of those formats failed to be parsed.
+    ///
+    /// # Examples
+    /// ```
+    /// use uhlc::NTP64;
+    ///
+    /// let t = NTP64::parse_any("7386690599959157260").unwrap();
+    /// assert_eq!(t, NTP64(7386690599959157260));
+    /// let t = NTP64::parse_any("2024-07-01T15:32:06.860479000Z").unwrap();
+    /// assert_eq!(t.as_secs(), 1719847926);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn parse_any(s: &str) -> Result<Self, ParseNTP64Error> {
+        if !s.is_empty() && s.bytes().all(|c| c.is_ascii_digit()) {
+            NTP64::from_decimal_str(s).map_err(|e| ParseNTP64Error {
+                cause: format!("Decimal format: {}", e.cause),
+                range: e.range,
+            })
+        } else {
+            NTP64::parse_rfc3339(s).map_err(|e| ParseNTP64Error {
+                cause: format!("RFC3339 format: {}", e.cause),
+                range: e.range,
+            })
+        }
+    }
+
     /// Parse a RFC3339 time representation into a NTP64.
+    ///
+    /// The time can be in UTC (i.e. with a `"Z"` offset) or in a local time (e.g. with a `"+02:00"` offset).
+    ///
+    /// A well-formed time that a NTP64 can't represent (i.e. before 1970 or after 2106) is rejected
+    /// with a [`TimeRangeError`] in the error's `range`.
+    ///
+    /// # Examples
+    /// ```
+    /// use uhlc::{NTP64, TimeRangeError};
+    ///
+    /// let e = NTP64::parse_rfc3339("1969-12-31T23:59:59Z").unwrap_err();
+    /// assert_eq!(e.range, Some(TimeRangeError::BeforeEpoch));
+    /// let e = NTP64::parse_rfc3339("2106-02-07T06:28:16Z").unwrap_err();
+    /// assert_eq!(e.range, Some(TimeRangeError::AfterEraEnd));
+    /// ```
     #[cfg(feature = "std")]
     pub fn parse_rfc3339(s: &str) -> Result<Self, ParseNTP64Error> {
-        match humantime::parse_rfc3339(s) {
-            Ok(time) => time
+        // humantime only supports the "Z" offset: parse a local time with another offset as UTC, and then shift it
+        let (parsed, offset) = match UtcOffset::split_rfc3339(s) {
+            Some((local, offset)) => (humantime::parse_rfc3339(&format!("{local}Z")), offset),
+            None => (humantime::parse_rfc3339(s), UtcOffset::UTC),
+        };
+        match parsed {
+            Ok(time) => offset
+                .to_utc(time)
                 .duration_since(UNIX_EPOCH)
-                .map(NTP64::from)
+                .map_err(|_| TimeRangeError::BeforeEpoch)
+                .and_then(NTP64::try_from_duration)
                 .map_err(|e| ParseNTP64Error {
                     cause: format!("Failed to parse '{s}' : {e}"),
+                    range: Some(e),
                 }),
+            // humantime rejects the years before 1970 as any other invalid format
+            Err(_) if matches!(s.get(..4), Some(y) if y.bytes().all(|c| c.is_ascii_digit()) && y < "1970") => {
+                Err(ParseNTP64Error {
+                    cause: format!("Failed to parse '{s}' : {}", TimeRangeError::BeforeEpoch),
+                    range: Some(TimeRangeError::BeforeEpoch),
+                })
+            }
             Err(_) => Err(ParseNTP64Error {
                 cause: format!("Failed to parse '{s}' : invalid RFC3339 format"),
+                range: None,
             }),
         }
     }
@@ -149,7 +341,7 @@ impl Add for NTP64 {
     }
 }
 
-impl<'a> Add<NTP64> for &'a NTP64 {
+impl Add<NTP64> for &NTP64 {
     type Output = <NTP64 as Add<NTP64>>::Output;
 
     #[inline]
@@ -201,7 +393,7 @@ impl Sub for NTP64 {
     }
 }
 
-impl<'a> Sub<NTP64> for &'a NTP64 {
+impl Sub<NTP64> for &NTP64 {
     type Output = <NTP64 as Sub<NTP64>>::Output;
 
     #[inline]
@@ -244,54 +436,268 @@ impl SubAssign<u64> for NTP64 {
     }
 }
 
+impl Mul<u32> for NTP64 {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, other: u32) -> Self {
+        Self(self.0 * other as u64)
+    }
+}
+
+impl MulAssign<u32> for NTP64 {
+    #[inline]
+    fn mul_assign(&mut self, other: u32) {
+        *self = Self(self.0 * other as u64);
+    }
+}
+
+impl Div<u32> for NTP64 {
+    type Output = Self;
+
+    #[inline]
+    fn div(self, other: u32) -> Self {
+        Self(self.0 / other as u64)
+    }
+}
+
+impl DivAssign<u32> for NTP64 {
+    #[inline]
+    fn div_assign(&mut self, other: u32) {
+        *self = Self(self.0 / other as u64);
+    }
+}
+
 impl fmt::Display for NTP64 {
     /// By default formats the value as an unsigned integer in decimal format.  
     /// If the alternate flag `{:#}` is used, formats the value with RFC3339 representation with nanoseconds precision.
+    /// With the alternate flag, a precision (e.g. `{:#.3}`) sets the number of digits of the fraction of second (from 0 to 9).
+    /// Without the `std` feature, the alternate flag is ignored: the value is always formatted in decimal format,
+    /// so it can be parsed back with [`core::str::FromStr`] on both sides of a `no_std` / `std` boundary.
+    ///
+    /// The width, fill and alignment flags are honored (e.g. `{:>30}`).
     ///
     /// # Examples
     /// ```
     ///   use uhlc::NTP64;
     ///
     ///   let t = NTP64(7386690599959157260);
-    ///   println!("{t}");    // displays: 7386690599959157260
-    ///   println!("{t:#}");  // displays: 2024-07-01T15:32:06.860479000Z
+    ///   println!("{t}");      // displays: 7386690599959157260
+    ///   println!("{t:#}");    // displays: 2024-07-01T15:32:06.860479000Z
+    ///   println!("{t:#.3}");  // displays: 2024-07-01T15:32:06.860Z
+    ///   assert_eq!(format!("{t:>22}"), "   7386690599959157260");
     /// ```
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         // if "{:#}" flag is specified, use RFC3339 representation
         if f.alternate() {
             #[cfg(feature = "std")]
-            return write!(f, "{}", format_rfc3339_nanos(self.to_system_time()));
+            return pad(f, &self.to_string_rfc3339_with_precision(f.precision()));
             #[cfg(not(feature = "std"))]
-            return write!(f, "{}", self.0);
+            return fmt::Display::fmt(&self.0, f);
         } else {
-            write!(f, "{}", self.0)
+            fmt::Display::fmt(&self.0, f)
         }
     }
 }
 
+impl NTP64 {
+    // Convert to a RFC3339 time representation with `precision` digits for the fraction of second (9 if None).
+    #[cfg(feature = "std")]
+    fn to_string_rfc3339_with_precision(self, precision: Option<usize>) -> String {
+        let mut s = self.to_string_rfc3339_lossy();
+        match precision {
+            // the nanoseconds representation ends with ".nnnnnnnnnZ"
+            Some(p) if p < 9 => {
+                let dot = s.len() - 11;
+                let end = if p == 0 { dot } else { dot + 1 + p };
+                s.replace_range(end.., "Z");
+                s
+            }
+            _ => s,
+        }
+    }
+}
+
+// Writes `s` into `f`, honoring its width, fill and alignment flags (left-aligned by default),
+// but ignoring its precision flag (unlike fmt::Formatter::pad())
+pub(crate) fn pad(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    let padding = f.width().unwrap_or(0).saturating_sub(s.chars().count());
+    let (before, after) = match f.align() {
+        Some(fmt::Alignment::Right) => (padding, 0),
+        Some(fmt::Alignment::Center) => (padding / 2, padding - padding / 2),
+        _ => (0, padding),
+    };
+    let fill = f.fill();
+    for _ in 0..before {
+        f.write_char(fill)?;
+    }
+    f.write_str(s)?;
+    for _ in 0..after {
+        f.write_char(fill)?;
+    }
+    Ok(())
+}
+
 impl fmt::Debug for NTP64 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.0)
     }
 }
 
-impl From<Duration> for NTP64 {
-    fn from(duration: Duration) -> NTP64 {
+/// Formats the raw `u64` value in hexadecimal, honoring all the formatting flags
+/// (e.g. `{:#018x}`).
+///
+/// # Examples
+/// ```
+///   use uhlc::NTP64;
+///
+///   let t = NTP64(0x66a2_b4c8_0000_0003);
+///   assert_eq!(format!("{t:x}"), "66a2b4c800000003");
+///   assert_eq!(format!("{t:#X}"), "0x66A2B4C800000003");
+/// ```
+impl fmt::LowerHex for NTP64 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.0, f)
+    }
+}
+
+impl fmt::UpperHex for NTP64 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::UpperHex::fmt(&self.0, f)
+    }
+}
+
+/// Formats the raw `u64` value in binary, honoring all the formatting flags.
+/// Useful to see the counter bits (i.e. the [`crate::CSIZE`] least significant bits) of a [`crate::Timestamp`]'s time.
+///
+/// # Examples
+/// ```
+///   use uhlc::NTP64;
+///
+///   let t = NTP64(0x66a2_b4c8_0000_0003);
+///   assert!(format!("{t:b}").ends_with("0011"));
+///   assert_eq!(format!("{t:#066b}").len(), 66);
+/// ```
+impl fmt::Binary for NTP64 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Binary::fmt(&self.0, f)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for NTP64 {
+    /// Formats the value as a compact hexadecimal `u64` (e.g. `64f1c2b0a3d70a3d`),
+    /// leaving its conversion to a human readable time to the host.
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{=u64:x}", self.0)
+    }
+}
+
+// Divide `n` by `d`, rounding to the nearest integer (half to even)
+const fn div_round_half_even(n: u64, d: u64) -> u64 {
+    let (q, r) = (n / d, n % d);
+    if r * 2 > d || (r * 2 == d && q % 2 == 1) {
+        q + 1
+    } else {
+        q
+    }
+}
+
+// Convert a fraction of second to nanoseconds (up to NANO_PER_SEC, for the fractions rounded to the next second)
+const fn frac_to_nanos(frac: u64) -> u64 {
+    div_round_half_even(frac * NANO_PER_SEC, FRAC_PER_SEC)
+}
+
+impl NTP64 {
+    /// Converts a [`Duration`] to the nearest [`NTP64`] (rounding half to even), or returns
+    /// [`TimeRangeError::AfterEraEnd`] if its number of seconds exceeds the 32-bits Seconds part.
+    pub fn try_from_duration(duration: Duration) -> Result<NTP64, TimeRangeError> {
         let secs = duration.as_secs();
-        assert!(secs <= MAX_NB_SEC);
+        if secs > MAX_NB_SEC {
+            return Err(TimeRangeError::AfterEraEnd);
+        }
         let nanos: u64 = duration.subsec_nanos().into();
-        NTP64((secs << 32) + ((nanos * FRAC_PER_SEC) / NANO_PER_SEC) + 1)
+        // never rounded to the next second: 999,999,999ns is ~4.3 fractions below 1s
+        Ok(NTP64(
+            (secs << 32) + div_round_half_even(nanos * FRAC_PER_SEC, NANO_PER_SEC),
+        ))
+    }
+}
+
+/// Converts a [`Duration`] to the nearest [`NTP64`] (rounding half to even).
+///
+/// # Panics
+/// If the number of seconds exceeds the 32-bits Seconds part (see [`NTP64::try_from_duration()`]).
+impl From<Duration> for NTP64 {
+    fn from(duration: Duration) -> NTP64 {
+        match NTP64::try_from_duration(duration) {
+            Ok(t) => t,
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
+
+impl NTP64 {
+    /// Parse a NTP64 formatted as an unsigned integer in decimal format (i.e. by [`fmt::Display`]
+    /// without the alternate flag), made of decimal digits only.
+    ///
+    /// Unlike the RFC3339 representation, it's available in `no_std` environments.
+    ///
+    /// # Examples
+    /// ```
+    /// use uhlc::NTP64;
+    ///
+    /// let t = NTP64(7386690599959157260);
+    /// assert_eq!(NTP64::from_decimal_str(&t.to_string()), Ok(t));
+    /// assert!(NTP64::from_decimal_str("+1").is_err());
+    /// assert!(NTP64::from_decimal_str("2024-07-01T15:32:06.860479000Z").is_err());
+    /// ```
+    pub fn from_decimal_str(s: &str) -> Result<Self, ParseNTP64Error> {
+        let digits_only = !s.is_empty() && s.bytes().all(|c| c.is_ascii_digit());
+        match s.parse::<u64>() {
+            Ok(t) if digits_only => Ok(NTP64(t)),
+            _ => Err(ParseNTP64Error {
+                cause: format!("Invalid NTP64 time : '{s}' (must be a u64)"),
+                range: None,
+            }),
+        }
+    }
+
+    /// Parse a NTP64 in decimal format (see [`NTP64::from_decimal_str()`]) at the start of `s`,
+    /// returning it with the remaining input. E.g. to parse a time embedded in a larger line
+    /// without splitting it first.
+    ///
+    /// # Examples
+    /// ```
+    /// use uhlc::NTP64;
+    ///
+    /// let (t, rest) = NTP64::parse_prefix("7386690599959157260 INFO started").unwrap();
+    /// assert_eq!(t, NTP64(7386690599959157260));
+    /// assert_eq!(rest, " INFO started");
+    /// assert!(NTP64::parse_prefix("INFO started").is_err());
+    /// ```
+    pub fn parse_prefix(s: &str) -> Result<(Self, &str), ParseNTP64Error> {
+        let end = s
+            .bytes()
+            .position(|c| !c.is_ascii_digit())
+            .unwrap_or(s.len());
+        if end == 0 {
+            return Err(ParseNTP64Error {
+                cause: format!("No decimal digits at the start of '{s}'"),
+                range: None,
+            });
+        }
+        let (digits, rest) = s.split_at(end);
+        NTP64::from_decimal_str(digits).map(|t| (t, rest))
     }
 }
 
-#[cfg(feature = "std")]
 impl FromStr for NTP64 {
     type Err = ParseNTP64Error;
 
+    /// Parse a NTP64 formatted as an unsigned integer in decimal format (see [`NTP64::from_decimal_str()`]).
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        u64::from_str(s).map(NTP64).map_err(|_| ParseNTP64Error {
-            cause: format!("Invalid NTP64 time : '{s}' (must be a u64)"),
-        })
+        NTP64::from_decimal_str(s)
     }
 }
 
@@ -299,8 +705,53 @@ impl FromStr for NTP64 {
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct ParseNTP64Error {
     pub cause: String,
+    /// Set if the parsed time is well-formed, but out of the range of a [`NTP64`].
+    pub range: Option<TimeRangeError>,
+}
+
+impl fmt::Display for ParseNTP64Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid NTP64: {}", self.cause)
+    }
+}
+
+impl core::error::Error for ParseNTP64Error {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        self.range
+            .as_ref()
+            .map(|e| e as &(dyn core::error::Error + 'static))
+    }
 }
 
+/// The error returned when a time can't be represented as a [`NTP64`], whose 32-bits seconds part
+/// counts from the UNIX EPOCH (1970-01-01T00:00:00Z) up to 2106-02-07T06:28:15Z.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TimeRangeError {
+    /// The time is before the UNIX EPOCH.
+    BeforeEpoch,
+    /// The time is after the last second of a [`NTP64`] (2106-02-07T06:28:15Z).
+    AfterEraEnd,
+}
+
+impl fmt::Display for TimeRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeRangeError::BeforeEpoch => {
+                write!(f, "time before the UNIX EPOCH (1970-01-01T00:00:00Z)")
+            }
+            TimeRangeError::AfterEraEnd => {
+                write!(
+                    f,
+                    "time after the end of the NTP64 range (2106-02-07T06:28:15Z)"
+                )
+            }
+        }
+    }
+}
+
+impl core::error::Error for TimeRangeError {}
+
 mod tests {
 
     #[test]
@@ -320,9 +771,113 @@ mod tests {
             "Time precision = {} ns",
             epoch_plus_counter_max.as_secs_f64() * (ntp64::NANO_PER_SEC as f64)
         );
+        #[cfg(not(any(feature = "csize-8", feature = "csize-16")))]
         assert!(epoch_plus_counter_max.as_secs_f64() < 0.0000000035f64);
     }
 
+    #[test]
+    fn counter() {
+        use crate::*;
+
+        let t = NTP64::new(1_000_000, 0x1234_5678);
+        assert_eq!(t.strip_counter().0, t.0 & LMASK);
+        assert_eq!(t.counter() as u64, t.0 & CMASK);
+        let max = t.with_counter(u16::MAX);
+        assert_eq!(max.counter() as u64, CMASK);
+        assert_eq!(max.strip_counter(), t.strip_counter());
+        assert_eq!(t.with_counter(0), t.strip_counter());
+        assert_eq!(t.strip_counter().with_counter(t.counter()), t);
+
+        // the HLC strips the counter of its physical clock
+        let hlc = HLCBuilder::new()
+            .with_clock(|| NTP64(0x1234_5678_9abc_def0 | CMASK))
+            .build();
+        let ts = hlc.new_timestamp();
+        assert_eq!(
+            ts.get_time().strip_counter(),
+            NTP64(0x1234_5678_9abc_def0 & LMASK)
+        );
+        assert_eq!(ts.get_time().counter(), 0);
+    }
+
+    #[test]
+    fn nanos_conversion() {
+        use crate::*;
+
+        let t = NTP64::from(Duration::new(1_719_842_272, 129_693_000));
+        assert_eq!(t.as_nanos(), 1_719_842_272_129_693_000);
+        assert_eq!(NTP64::from_nanos(t.as_nanos()), t);
+        assert_eq!(NTP64::from_nanos(0).as_nanos(), 0);
+    }
+
+    #[test]
+    fn duration_rounding() {
+        use super::{FRAC_MASK, MAX_NB_SEC};
+        use crate::*;
+        use rand::prelude::*;
+
+        // round trips of all the nanoseconds values (by steps of a prime number, and around the bounds)
+        let round_trip = |nanos: u32| {
+            let d = Duration::new(7, nanos);
+            assert_eq!(NTP64::from(d).to_duration(), d, "for {:?}", d);
+        };
+        (0..1_000_000_000).step_by(997).for_each(round_trip);
+        (0..1000)
+            .chain(999_999_000..1_000_000_000)
+            .for_each(round_trip);
+
+        // the fractions are rounded half to even
+        assert_eq!(NTP64(1 << 22).subsec_nanos(), 976_562); // 976,562.5
+        assert_eq!(NTP64(3 << 22).subsec_nanos(), 2_929_688); // 2,929,687.5
+        assert_eq!(NTP64(1).subsec_nanos(), 0);
+        assert_eq!(NTP64(3).subsec_nanos(), 1);
+        // the last fractions are rounded to the next second
+        assert_eq!(NTP64(FRAC_MASK).to_duration(), Duration::from_secs(1));
+        assert_eq!(NTP64(FRAC_MASK).as_nanos(), 1_000_000_000);
+        assert_eq!(NTP64(FRAC_MASK).subsec_nanos(), 999_999_999);
+
+        // NTP64 -> Duration -> NTP64 is stable across the full range
+        let mut rng = rand::thread_rng();
+        for _ in 0..100_000 {
+            let t = NTP64(rng.gen());
+            let d = t.to_duration();
+            if d.as_secs() <= MAX_NB_SEC {
+                let back = NTP64::from(d);
+                assert_eq!(back.to_duration(), d, "for {:?}", t);
+                assert!(back.0.abs_diff(t.0) <= 3, "for {:?}", t);
+            }
+        }
+        assert_eq!(NTP64::from(Duration::ZERO), NTP64(0));
+        assert_eq!(NTP64::from(Duration::from_secs(1)), NTP64(1 << 32));
+    }
+
+    // The seconds are carried unchanged in both directions, so the rounding is fully covered by
+    // all the nanoseconds and all the fractions of a second. This takes a few seconds in release
+    // mode but minutes in debug mode: run it with `cargo test --release`.
+    #[test]
+    #[cfg_attr(
+        debug_assertions,
+        ignore = "exhaustive: run with `cargo test --release`"
+    )]
+    fn duration_rounding_exhaustive() {
+        use crate::*;
+
+        // Duration -> NTP64 -> Duration is the identity for all the nanoseconds
+        for nanos in 0..1_000_000_000 {
+            let d = Duration::new(7, nanos);
+            assert_eq!(NTP64::from(d).to_duration(), d, "for {:?}", d);
+        }
+
+        // NTP64 -> Duration -> NTP64 is stable for all the fractions
+        for frac in 0..=u32::MAX {
+            let t = NTP64((7 << 32) | u64::from(frac));
+            let d = t.to_duration();
+            let back = NTP64::from(d);
+            assert_eq!(back.to_duration(), d, "for {:?}", t);
+            assert!(back.0.abs_diff(t.0) <= 3, "for {:?}", t);
+        }
+    }
+
     #[test]
     fn bijective_to_string() {
         use crate::*;
@@ -333,6 +888,15 @@ mod tests {
         for _ in 0u64..10000 {
             let t = NTP64(rng.gen());
             assert_eq!(t, NTP64::from_str(&t.to_string()).unwrap());
+            assert_eq!(t, NTP64::from_decimal_str(&t.to_string()).unwrap());
+
+            // formatted as the time part of a Timestamp, in both modes
+            let ts = Timestamp::new(t, ID::MAX);
+            assert_eq!(format!("{ts}"), format!("{t}/{}", ts.get_id()));
+            assert_eq!(format!("{ts:#}"), format!("{t:#}/{}", ts.get_id()));
+        }
+        for s in ["", "+1", "-1", " 1", "1.0", "18446744073709551616"] {
+            assert!(NTP64::from_decimal_str(s).is_err(), "for {:?}", s);
         }
     }
 
@@ -356,5 +920,74 @@ mod tests {
         let rfc3339_2 = format!("{t:#}");
         assert_eq!(rfc3339_2, humantime::format_rfc3339_nanos(now).to_string());
         assert!(rfc3339_regex.is_match(&rfc3339_2));
+
+        // the bounds of the NTP64 range, and beyond it without panicking
+        let t = NTP64::parse_rfc3339("2106-02-07T06:28:15.999999999Z").unwrap();
+        assert_eq!(t.as_secs(), u32::MAX);
+        assert_eq!(NTP64::parse_rfc3339("1970-01-01T00:00:00Z"), Ok(NTP64(0)));
+        for (s, range) in [
+            (
+                "1969-12-31T23:59:59.999999999Z",
+                TimeRangeError::BeforeEpoch,
+            ),
+            ("1970-01-01T00:30:00+01:00", TimeRangeError::BeforeEpoch),
+            ("2106-02-07T06:28:16Z", TimeRangeError::AfterEraEnd),
+            ("9999-12-31T23:59:59Z", TimeRangeError::AfterEraEnd),
+        ] {
+            assert_eq!(NTP64::parse_rfc3339(s).unwrap_err().range, Some(range));
+            assert_eq!(NTP64::parse_any(s).unwrap_err().range, Some(range));
+            assert!(Timestamp::parse_rfc3339(&format!("{s}/33")).is_err());
+        }
+        assert_eq!(NTP64::parse_rfc3339("not a date").unwrap_err().range, None);
+    }
+
+    #[test]
+    fn format_diff() {
+        use crate::*;
+
+        let t = NTP64::from(Duration::from_secs(1_719_842_272));
+        let diff = |nanos: u64| NTP64::from(Duration::from_nanos(nanos));
+        assert_eq!(t.format_diff(&t), "0s");
+        assert_eq!((t + diff(1_254_000_000)).format_diff(&t), "+1.254s");
+        assert_eq!((t + diff(120_000_000_000)).format_diff(&t), "+120s");
+        assert_eq!(t.format_diff(&(t + diff(3_000_000))), "-3ms");
+        assert_eq!(t.format_diff(&(t + diff(3_500_999))), "-3.5ms");
+        assert_eq!((t + diff(12_500)).format_diff(&t), "+12.5µs");
+        assert_eq!(t.format_diff(&(t + diff(800))), "-800ns");
+
+        let id = ID::rand();
+        let ts = Timestamp::new(t, id);
+        let other = Timestamp::new(t + diff(42_000_000), ID::rand());
+        assert_eq!(ts.format_diff(&other), "-42ms");
+    }
+
+    #[test]
+    fn format_flags() {
+        use crate::*;
+
+        let t = NTP64(7386690599959157260);
+        assert_eq!(format!("{t:#.0}"), "2024-07-01T15:32:06Z");
+        assert_eq!(format!("{t:#.3}"), "2024-07-01T15:32:06.860Z");
+        assert_eq!(format!("{t:#.9}"), "2024-07-01T15:32:06.860479000Z");
+        assert_eq!(format!("{t:#.12}"), "2024-07-01T15:32:06.860479000Z");
+        assert_eq!(format!("{t:>#32}"), "  2024-07-01T15:32:06.860479000Z");
+        assert_eq!(format!("{t:*^#26.3}"), "*2024-07-01T15:32:06.860Z*");
+        assert_eq!(format!("{t:<21}|"), "7386690599959157260  |");
+        assert_eq!(format!("{t:021}"), "007386690599959157260");
+    }
+
+    #[test]
+    fn scalar_ops() {
+        use crate::*;
+
+        let delta = NTP64::from(Duration::from_millis(500));
+        assert_eq!(delta / 2 * 2, NTP64(delta.0 & !1));
+        assert_eq!((delta * 4).as_secs(), 2);
+        let mut t = delta;
+        t *= 3;
+        t /= 3;
+        assert_eq!(t, delta);
+        assert_eq!((delta * 3).ratio(&delta), 3.0);
+        assert_eq!(delta.ratio(&(delta * 4)), 0.25);
     }
 }
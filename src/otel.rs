@@ -0,0 +1,139 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{Timestamp, CMASK, HLC};
+use alloc::{borrow::Cow, string::ToString, vec::Vec};
+use opentelemetry::{trace::Span, KeyValue};
+use std::time::SystemTime;
+
+/// The OpenTelemetry attribute key for the [`crate::ID`] of a [`Timestamp`] (as a hexadecimal string).
+pub const OTEL_ID_KEY: &str = "uhlc.id";
+/// The OpenTelemetry attribute key for the HLC logical counter of a [`Timestamp`].
+pub const OTEL_COUNTER_KEY: &str = "uhlc.counter";
+
+impl Timestamp {
+    /// Returns the time of this [`Timestamp`] as an OpenTelemetry event time
+    /// (making the assumption that its [`crate::NTP64`] is relative to [`std::time::UNIX_EPOCH`]).
+    #[inline]
+    pub fn to_otel_time(&self) -> SystemTime {
+        self.get_time().to_system_time()
+    }
+
+    /// Returns the OpenTelemetry attributes of this [`Timestamp`]:
+    ///  * [`OTEL_ID_KEY`]: the [`crate::ID`] as a hexadecimal string
+    ///  * [`OTEL_COUNTER_KEY`]: the HLC logical counter (i.e. the last [`crate::CSIZE`] bits of the time)
+    pub fn to_otel_attributes(&self) -> Vec<KeyValue> {
+        vec![
+            KeyValue::new(OTEL_ID_KEY, self.get_id().to_string()),
+            KeyValue::new(OTEL_COUNTER_KEY, (self.get_time().as_u64() & CMASK) as i64),
+        ]
+    }
+}
+
+/// An extension of OpenTelemetry [`Span`]s to record HLC [`Timestamp`]s on them.
+///
+/// # Examples
+///
+/// ```
+/// use opentelemetry::trace::{noop::NoopTracer, Tracer};
+/// use uhlc::{SpanStamper, HLC};
+///
+/// let hlc = HLC::default();
+/// let mut span = NoopTracer::new().start("operation");
+/// let ts = span.stamp_event(&hlc, "message sent");
+/// println!("message sent at {ts}");
+/// ```
+pub trait SpanStamper: Span {
+    /// Add an event on this span at the time of `timestamp`, with its attributes
+    /// (see [`Timestamp::to_otel_attributes()`]).
+    fn add_timestamp_event<T>(&mut self, name: T, timestamp: &Timestamp)
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.add_event_with_timestamp(
+            name,
+            timestamp.to_otel_time(),
+            timestamp.to_otel_attributes(),
+        );
+    }
+
+    /// Generate a new [`Timestamp`] with `hlc` and add it as an event on this span.
+    fn stamp_event<T>(&mut self, hlc: &HLC, name: T) -> Timestamp
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        let timestamp = hlc.new_timestamp();
+        self.add_timestamp_event(name, &timestamp);
+        timestamp
+    }
+}
+
+impl<S: Span> SpanStamper for S {}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use opentelemetry::trace::{Span, SpanContext, Status};
+    use opentelemetry::{KeyValue, Value};
+    use std::borrow::Cow;
+    use std::time::SystemTime;
+
+    #[derive(Default)]
+    struct RecordingSpan {
+        events: Vec<(Cow<'static, str>, SystemTime, Vec<KeyValue>)>,
+    }
+
+    impl Span for RecordingSpan {
+        fn add_event_with_timestamp<T>(
+            &mut self,
+            name: T,
+            timestamp: SystemTime,
+            attributes: Vec<KeyValue>,
+        ) where
+            T: Into<Cow<'static, str>>,
+        {
+            self.events.push((name.into(), timestamp, attributes));
+        }
+        fn span_context(&self) -> &SpanContext {
+            &SpanContext::NONE
+        }
+        fn is_recording(&self) -> bool {
+            true
+        }
+        fn set_attribute(&mut self, _: KeyValue) {}
+        fn set_status(&mut self, _: Status) {}
+        fn update_name<T>(&mut self, _: T)
+        where
+            T: Into<Cow<'static, str>>,
+        {
+        }
+        fn add_link(&mut self, _: SpanContext, _: Vec<KeyValue>) {}
+        fn end_with_timestamp(&mut self, _: SystemTime) {}
+    }
+
+    #[test]
+    fn span_stamper() {
+        let hlc = HLC::default();
+        let mut span = RecordingSpan::default();
+        let ts = span.stamp_event(&hlc, "event");
+
+        assert_eq!(span.events.len(), 1);
+        let (name, time, attributes) = &span.events[0];
+        assert_eq!(name, "event");
+        assert_eq!(*time, ts.get_time().to_system_time());
+        assert_eq!(attributes[0].key.as_str(), OTEL_ID_KEY);
+        assert_eq!(attributes[0].value, Value::from(ts.get_id().to_string()));
+        assert_eq!(attributes[1].key.as_str(), OTEL_COUNTER_KEY);
+        assert_eq!(
+            attributes[1].value,
+            Value::I64((ts.get_time().as_u64() & CMASK) as i64)
+        );
+    }
+}
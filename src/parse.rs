@@ -0,0 +1,123 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+//! The parsers are generic over the winnow error type `E`, so they can be used with
+//! [`winnow::error::ContextError`] as well as with [`winnow::error::ErrMode`] (i.e. in [`winnow::ModalResult`] grammars).
+//! Their failures are reported with [`winnow::error::FromExternalError`], from the errors of the corresponding `parse_prefix()` operation.
+//!
+//! # Examples
+//! ```
+//! use uhlc::{parse, Timestamp};
+//! use winnow::{combinator::separated, error::ContextError, Parser};
+//!
+//! let line = "7386690599959157260/33,7386690599959157261/34";
+//! let stamps: Vec<Timestamp> = separated(1.., parse::timestamp::<ContextError>(), ',')
+//!     .parse(line)
+//!     .unwrap();
+//! assert_eq!(stamps.len(), 2);
+//! ```
+use super::{
+    DecodeError, ParseIDError, ParseNTP64Error, ParseTimestampError, Timestamp, ID, KEY_SIZE, NTP64,
+};
+use winnow::{
+    error::{FromExternalError, ParserError},
+    token::take,
+    Parser,
+};
+
+// Adapt a `parse_prefix()` operation into a parser
+fn prefix<'i, O, P, E>(
+    parse_prefix: fn(&'i str) -> Result<(O, &'i str), P>,
+) -> impl Parser<&'i str, O, E>
+where
+    E: FromExternalError<&'i str, P>,
+{
+    move |input: &mut &'i str| {
+        let (o, rest) = parse_prefix(input).map_err(|e| E::from_external_error(input, e))?;
+        *input = rest;
+        Ok(o)
+    }
+}
+
+/// Parses a [`NTP64`] in decimal format (see [`NTP64::parse_prefix()`]).
+pub fn ntp64<'i, E>() -> impl Parser<&'i str, NTP64, E>
+where
+    E: ParserError<&'i str> + FromExternalError<&'i str, ParseNTP64Error>,
+{
+    prefix(NTP64::parse_prefix)
+}
+
+/// Parses an [`ID`] in hexadecimal format (see [`ID::parse_prefix()`]).
+pub fn id<'i, E>() -> impl Parser<&'i str, ID, E>
+where
+    E: ParserError<&'i str> + FromExternalError<&'i str, ParseIDError>,
+{
+    prefix(ID::parse_prefix)
+}
+
+/// Parses a [`Timestamp`] formatted as `"<ntp64_time>/<hlc_id_hexadecimal>"` (see [`Timestamp::parse_prefix()`]).
+pub fn timestamp<'i, E>() -> impl Parser<&'i str, Timestamp, E>
+where
+    E: ParserError<&'i str> + FromExternalError<&'i str, ParseTimestampError>,
+{
+    prefix(Timestamp::parse_prefix)
+}
+
+/// Parses a [`Timestamp`] from the [`KEY_SIZE`] bytes of its key encoding (see [`Timestamp::to_key()`]),
+/// e.g. within a binary protocol.
+pub fn timestamp_key<'i, E>() -> impl Parser<&'i [u8], Timestamp, E>
+where
+    E: ParserError<&'i [u8]> + FromExternalError<&'i [u8], DecodeError>,
+{
+    take(KEY_SIZE).try_map(Timestamp::from_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use winnow::{
+        combinator::{delimited, separated_pair},
+        error::{ContextError, EmptyError},
+        ModalResult, Parser,
+    };
+
+    #[test]
+    fn combinators() {
+        let ts = Timestamp::new(NTP64(7386690599959157260), ID::rand());
+
+        // composed into a text grammar
+        fn entry(input: &mut &str) -> ModalResult<(Timestamp, NTP64)> {
+            separated_pair(delimited('[', parse::timestamp(), ']'), ' ', parse::ntp64())
+                .parse_next(input)
+        }
+        let line = format!("[{ts}] 42");
+        assert_eq!(entry.parse(&line).unwrap(), (ts, NTP64(42)));
+        assert!(entry.parse("[7386690599959157260] 42").is_err());
+
+        let s = format!("{}/{}", ts.get_time(), ts.get_id());
+        let mut input = s.as_str();
+        let time = parse::ntp64::<EmptyError>().parse_next(&mut input).unwrap();
+        assert_eq!(time, *ts.get_time());
+        assert_eq!('/'.parse_next(&mut input), Ok::<_, EmptyError>('/'));
+        assert_eq!(parse::id::<EmptyError>().parse(input), Ok(*ts.get_id()));
+
+        // in a binary protocol
+        let mut bytes = ts.to_key().to_vec();
+        bytes.push(0xff);
+        let mut input = bytes.as_slice();
+        let parsed = parse::timestamp_key::<ContextError>()
+            .parse_next(&mut input)
+            .unwrap();
+        assert_eq!((parsed, input), (ts, &[0xff][..]));
+        assert!(parse::timestamp_key::<ContextError>()
+            .parse_next(&mut &bytes[..KEY_SIZE - 1])
+            .is_err());
+    }
+}
@@ -0,0 +1,193 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{HLCBuilder, LastTime, Mutex, SizeError, HLC, ID, NTP64};
+use alloc::{collections::BTreeMap, sync::Arc};
+use core::{cmp, time::Duration};
+
+struct Tenant {
+    hlc: HLC,
+    // the tick of the latest access
+    last_used: u64,
+}
+
+struct PoolState {
+    tenants: BTreeMap<u32, Tenant>,
+    tick: u64,
+    // the latest time of all the evicted HLCs
+    evicted_time: NTP64,
+}
+
+/// A pool of [`HLC`]s, one per tenant, lazily created and sharing the same physical clock.
+///
+/// The [`ID`] of a tenant's [`HLC`] is the base [`ID`] of the pool followed by the tenant number
+/// (i.e. `base << 32 | tenant`), so the base [`ID`] must be 12 bytes at most.
+///
+/// The pool keeps at most `capacity` inactive [`HLC`]s (i.e. not referenced outside the pool),
+/// evicting the least recently used ones. A tenant's [`HLC`] that is created again after its eviction
+/// starts from the latest time of the evicted [`HLC`]s, so its [`crate::Timestamp`]s remain monotonic.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use uhlc::{HlcPool, ID};
+///
+/// let pool = HlcPool::new(ID::try_from([0xab]).unwrap(), 100).unwrap();
+/// let ts = pool.get(7).new_timestamp();
+/// assert_eq!(ts.get_id().to_string(), "ab00000007");
+/// assert!(pool.get(7).new_timestamp() > ts);
+/// ```
+pub struct HlcPool {
+    base: ID,
+    capacity: usize,
+    clock: Option<fn() -> NTP64>,
+    max_delta: Option<Duration>,
+    state: Mutex<PoolState>,
+}
+
+impl HlcPool {
+    /// The maximum size in bytes of the base [`ID`] of a pool.
+    pub const MAX_BASE_SIZE: usize = ID::MAX_SIZE - 4;
+
+    /// Create a pool of [`HLC`]s, keeping at most `capacity` inactive ones.
+    ///
+    /// The [`HLC`]s are created with the default configuration of [`HLCBuilder::new()`],
+    /// except their [`ID`]s.
+    ///
+    /// Returns a [`SizeError`] if `base` is longer than [`HlcPool::MAX_BASE_SIZE`].
+    pub fn new(base: ID, capacity: usize) -> Result<HlcPool, SizeError> {
+        if base.size() > Self::MAX_BASE_SIZE {
+            return Err(SizeError(base.size() + 4));
+        }
+        Ok(HlcPool {
+            base,
+            capacity,
+            clock: None,
+            max_delta: None,
+            state: Mutex::new(PoolState {
+                tenants: BTreeMap::new(),
+                tick: 0,
+                evicted_time: NTP64(0),
+            }),
+        })
+    }
+
+    /// Configure the physical clock shared by all the [`HLC`]s of the pool (see [`HLCBuilder::with_clock()`]).
+    pub fn with_clock(mut self, clock: fn() -> NTP64) -> HlcPool {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Configure the maximum delta of all the [`HLC`]s of the pool (see [`HLCBuilder::with_max_delta()`]).
+    pub fn with_max_delta(mut self, delta: Duration) -> HlcPool {
+        self.max_delta = Some(delta);
+        self
+    }
+
+    /// Returns the [`ID`] of the [`HLC`] of `tenant`.
+    pub fn tenant_id(&self, tenant: u32) -> ID {
+        let base = u128::from_le_bytes(self.base.to_le_bytes());
+        ID::from_bytes(&(base << 32 | tenant as u128).to_le_bytes())
+            .expect("the base ID is not zero")
+    }
+
+    /// Returns the [`HLC`] of `tenant`, creating it if it's not in the pool.
+    pub fn get(&self, tenant: u32) -> HLC {
+        let mut state = lock!(self.state);
+        self.evict(&mut state);
+        state.tick += 1;
+        let tick = state.tick;
+        if let Some(entry) = state.tenants.get_mut(&tenant) {
+            entry.last_used = tick;
+            return entry.hlc.clone();
+        }
+        let hlc = self.build(tenant, state.evicted_time);
+        state.tenants.insert(
+            tenant,
+            Tenant {
+                hlc: hlc.clone(),
+                last_used: tick,
+            },
+        );
+        hlc
+    }
+
+    /// Returns the number of [`HLC`]s in the pool.
+    pub fn len(&self) -> usize {
+        lock!(self.state).tenants.len()
+    }
+
+    /// Returns `true` if the pool is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn build(&self, tenant: u32, last_time: NTP64) -> HLC {
+        let mut builder = HLCBuilder::new().with_id(self.tenant_id(tenant));
+        if let Some(clock) = self.clock {
+            builder = builder.with_clock(clock);
+        }
+        if let Some(delta) = self.max_delta {
+            builder = builder.with_max_delta(delta);
+        }
+        let mut hlc = builder.build();
+        hlc.last_time = Arc::new(LastTime::new(last_time));
+        hlc
+    }
+
+    // Evict the least recently used inactive HLCs beyond the capacity
+    // (the HLCs returned by get() become inactive only once dropped, so it's done before each access)
+    fn evict(&self, state: &mut PoolState) {
+        loop {
+            let inactive = state.tenants.iter().filter(|(_, t)| !t.hlc.is_shared());
+            if inactive.clone().count() <= self.capacity {
+                return;
+            }
+            let lru = match inactive.min_by_key(|(_, t)| t.last_used) {
+                Some((tenant, _)) => *tenant,
+                None => return,
+            };
+            if let Some(evicted) = state.tenants.remove(&lru) {
+                state.evicted_time = cmp::max(state.evicted_time, evicted.hlc.last_time.get());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use core::convert::TryFrom;
+
+    #[test]
+    fn hlc_pool() {
+        let pool = HlcPool::new(ID::try_from([0x01]).unwrap(), 1)
+            .unwrap()
+            .with_clock(zero_clock);
+        assert_eq!(pool.tenant_id(0).to_string(), "100000000");
+
+        // an active HLC is never evicted
+        let active = pool.get(1);
+        let ts = active.new_timestamp();
+        assert_eq!(ts.get_id(), &pool.tenant_id(1));
+        // only 1 inactive HLC is kept
+        let last = pool.get(2).new_timestamp();
+        pool.get(3).new_timestamp();
+        assert!(pool.get(1).new_timestamp() > ts);
+        assert_eq!(pool.len(), 2);
+
+        // an evicted HLC is created again with a greater time
+        assert!(pool.get(2).new_timestamp() > last);
+
+        let too_long = ID::try_from([0xff; 13]).unwrap();
+        assert!(HlcPool::new(too_long, 1).is_err());
+    }
+}
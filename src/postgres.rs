@@ -0,0 +1,65 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{Timestamp, KEY_SIZE};
+use sqlx::encode::IsNull;
+use sqlx::error::BoxDynError;
+use sqlx::postgres::{PgArgumentBuffer, PgHasArrayType, PgTypeInfo, PgValueRef, Postgres};
+use sqlx::{Decode, Encode, Type};
+
+/// A [`Timestamp`] is stored in Postgres as a 24 bytes `bytea` (see [`Timestamp::to_key()`]).
+/// With such layout, Postgres orders the `bytea` values in the same way as [`Timestamp`]s are ordered,
+/// meaning that `ORDER BY` and range queries on the column respect the HLC ordering.
+impl Type<Postgres> for Timestamp {
+    fn type_info() -> PgTypeInfo {
+        <[u8] as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <[u8] as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl PgHasArrayType for Timestamp {
+    fn array_type_info() -> PgTypeInfo {
+        <&[u8] as PgHasArrayType>::array_type_info()
+    }
+}
+
+impl Encode<'_, Postgres> for Timestamp {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+        <[u8; KEY_SIZE] as Encode<Postgres>>::encode(self.to_key(), buf)
+    }
+
+    fn size_hint(&self) -> usize {
+        KEY_SIZE
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for Timestamp {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        let key = <[u8; KEY_SIZE] as Decode<Postgres>>::decode(value)?;
+        Ok(Timestamp::from_key(&key)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn bytea_encoding() {
+        let ts = HLC::default().new_timestamp();
+        let mut buf = PgArgumentBuffer::default();
+        assert!(matches!(ts.encode_by_ref(&mut buf), Ok(IsNull::No)));
+        assert_eq!(&buf[..], &ts.to_key()[..]);
+    }
+}
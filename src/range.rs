@@ -0,0 +1,185 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{Timestamp, ID, LMASK, NTP64};
+use core::{cmp, fmt, time::Duration};
+use serde::{Deserialize, Serialize};
+
+/// A half-open range of [`Timestamp`]s: `start..end`, with `start` included and `end` excluded.
+///
+/// As a [`Timestamp`] is ordered by its time and then by its [`ID`], the range of all the [`Timestamp`]s
+/// with a time within `start..end` is built with [`TimestampRange::from_times()`] (using [`ID::MIN`]).
+/// A range with `start >= end` is empty.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use uhlc::{TimestampRange, HLC};
+///
+/// let hlc = HLC::default();
+/// let (ts1, ts2, ts3) = (hlc.new_timestamp(), hlc.new_timestamp(), hlc.new_timestamp());
+/// let a = TimestampRange::new(ts1, ts2);
+/// let b = TimestampRange::new(ts2, ts3);
+/// assert!(a.contains(&ts1) && !a.contains(&ts2));
+/// assert!(a.intersect(&b).is_none());
+/// assert_eq!(a.union_adjacent(&b), Some(TimestampRange::new(ts1, ts3)));
+///
+/// // split the range in buckets of 1 second
+/// let range = TimestampRange::from_times(*ts1.get_time(), *ts3.get_time());
+/// assert!(range.buckets(Duration::from_secs(1)).count() <= 2);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TimestampRange {
+    /// The lower bound of the range (inclusive).
+    pub start: Timestamp,
+    /// The upper bound of the range (exclusive).
+    pub end: Timestamp,
+}
+
+impl TimestampRange {
+    /// Create the range `start..end`.
+    #[inline]
+    pub const fn new(start: Timestamp, end: Timestamp) -> TimestampRange {
+        TimestampRange { start, end }
+    }
+
+    /// Create the range of all the [`Timestamp`]s with a time in `start..end`, whatever their [`ID`].
+    #[inline]
+    pub const fn from_times(start: NTP64, end: NTP64) -> TimestampRange {
+        TimestampRange {
+            start: Timestamp::new(start, ID::MIN),
+            end: Timestamp::new(end, ID::MIN),
+        }
+    }
+
+    /// Returns `true` if the range contains no [`Timestamp`].
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+
+    /// Returns `true` if `timestamp` is within the range.
+    #[inline]
+    pub fn contains(&self, timestamp: &Timestamp) -> bool {
+        self.start <= *timestamp && *timestamp < self.end
+    }
+
+    /// Returns the intersection of the 2 ranges, or `None` if it's empty.
+    pub fn intersect(&self, other: &TimestampRange) -> Option<TimestampRange> {
+        let range = TimestampRange::new(
+            cmp::max(self.start, other.start),
+            cmp::min(self.end, other.end),
+        );
+        if range.is_empty() {
+            None
+        } else {
+            Some(range)
+        }
+    }
+
+    /// Returns the union of the 2 ranges if they overlap or are adjacent (i.e. it's a single range),
+    /// or `None` otherwise.
+    pub fn union_adjacent(&self, other: &TimestampRange) -> Option<TimestampRange> {
+        if self.is_empty() {
+            return Some(*other);
+        }
+        if other.is_empty() {
+            return Some(*self);
+        }
+        if self.start > other.end || other.start > self.end {
+            return None;
+        }
+        Some(TimestampRange::new(
+            cmp::min(self.start, other.start),
+            cmp::max(self.end, other.end),
+        ))
+    }
+
+    /// Returns an iterator over the non-empty sub-ranges of this range, split at the boundaries of
+    /// time buckets of `width` (aligned on multiples of `width` since the epoch, as in [`crate::RangeDigest`]).
+    ///
+    /// # Panics
+    ///
+    /// If `width` is lower than the resolution of the [`Timestamp`]s without their counter part.
+    pub fn buckets(&self, width: Duration) -> impl Iterator<Item = TimestampRange> {
+        let width = NTP64::from(width).0 & LMASK;
+        assert!(width != 0, "TimestampRange's bucket width is too small");
+        let end = self.end;
+        let mut next = if self.is_empty() {
+            None
+        } else {
+            Some(self.start)
+        };
+        core::iter::from_fn(move || {
+            let start = next?;
+            let bucket_start = (start.get_time().0 & LMASK) / width * width;
+            next = bucket_start
+                .checked_add(width)
+                .map(|t| Timestamp::new(NTP64(t), ID::MIN))
+                .filter(|t| *t < end);
+            Some(TimestampRange::new(start, next.unwrap_or(end)))
+        })
+    }
+}
+
+impl fmt::Display for TimestampRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}, {})", self.start, self.end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use core::convert::TryFrom;
+    use core::time::Duration;
+
+    #[test]
+    fn timestamp_range() {
+        let id = ID::try_from([0x01]).unwrap();
+        let ts = |t: u64| Timestamp::new(NTP64(t), id);
+        let r = TimestampRange::new(ts(10), ts(20));
+        assert!(r.contains(&ts(10)) && r.contains(&ts(19)) && !r.contains(&ts(20)));
+        assert_eq!(
+            r.intersect(&TimestampRange::new(ts(15), ts(30))),
+            Some(TimestampRange::new(ts(15), ts(20)))
+        );
+        assert_eq!(r.intersect(&TimestampRange::new(ts(20), ts(30))), None);
+        assert_eq!(
+            r.union_adjacent(&TimestampRange::new(ts(20), ts(30))),
+            Some(TimestampRange::new(ts(10), ts(30)))
+        );
+        assert_eq!(r.union_adjacent(&TimestampRange::new(ts(21), ts(30))), None);
+
+        // the range of times contains all the IDs
+        let times = TimestampRange::from_times(NTP64(16), NTP64(32));
+        assert!(times.contains(&Timestamp::new(NTP64(16), ID::MIN)));
+        assert!(times.contains(&Timestamp::new(NTP64(31), ID::MAX)));
+        assert!(!times.contains(&Timestamp::new(NTP64(32), ID::MIN)));
+        assert!(ID::MIN < id && id < ID::MAX);
+
+        let sec = |s: u64| NTP64::from(Duration::from_secs(s)).0 & LMASK;
+        let r = TimestampRange::new(ts(sec(5)), ts(sec(25)));
+        let buckets: Vec<_> = r.buckets(Duration::from_secs(10)).collect();
+        assert_eq!(buckets.len(), 3);
+        assert_eq!(buckets[0].start, r.start);
+        assert_eq!(
+            buckets[1],
+            TimestampRange::from_times(NTP64(sec(10)), NTP64(sec(20)))
+        );
+        assert_eq!(buckets[2].end, r.end);
+        assert!(buckets.windows(2).all(|w| w[0].end == w[1].start));
+
+        let json = serde_json::to_string(&r).unwrap();
+        assert_eq!(serde_json::from_str::<TimestampRange>(&json).unwrap(), r);
+    }
+}
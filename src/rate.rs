@@ -0,0 +1,125 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{Timestamp, NTP64};
+use alloc::collections::VecDeque;
+use core::time::Duration;
+
+/// An estimator of the rate of events, fed with their [`Timestamp`]s.
+///
+/// Only the events within a sliding window ending at the latest recorded [`Timestamp`] are considered.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use uhlc::{RateEstimator, Timestamp, HLC, NTP64};
+///
+/// let id = *HLC::default().get_id();
+/// let mut estimator = RateEstimator::new(Duration::from_secs(1));
+/// for i in 0..50u64 {
+///     let time = NTP64::from(Duration::from_millis(1000 + 20 * i));
+///     estimator.record(&Timestamp::new(time, id));
+/// }
+/// assert_eq!(estimator.rate().round(), 50.0);
+/// let mean = estimator.mean_inter_arrival().unwrap();
+/// assert_eq!((mean.as_secs_f64() * 1000.0).round(), 20.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RateEstimator {
+    window: NTP64,
+    times: VecDeque<NTP64>,
+}
+
+impl RateEstimator {
+    /// Create a new [`RateEstimator`] over a sliding `window`.
+    pub fn new(window: Duration) -> RateEstimator {
+        RateEstimator {
+            window: window.into(),
+            times: VecDeque::new(),
+        }
+    }
+
+    /// Record an event occurring at the time of `timestamp`.
+    ///
+    /// The events that are out of the window ending at the latest recorded time are discarded.
+    pub fn record(&mut self, timestamp: &Timestamp) {
+        let time = *timestamp.get_time();
+        let i = self.times.partition_point(|t| *t <= time);
+        self.times.insert(i, time);
+        if let Some(latest) = self.times.back().copied() {
+            while self
+                .times
+                .front()
+                .is_some_and(|t| latest - *t > self.window)
+            {
+                self.times.pop_front();
+            }
+        }
+    }
+
+    /// Returns the number of events within the window.
+    #[inline]
+    pub fn count(&self) -> usize {
+        self.times.len()
+    }
+
+    /// Returns the rate of events within the window, in events per second.
+    pub fn rate(&self) -> f64 {
+        self.times.len() as f64 / self.window.as_secs_f64()
+    }
+
+    /// Returns the mean time between 2 consecutive events within the window,
+    /// or [`None`] if less than 2 events are within the window.
+    pub fn mean_inter_arrival(&self) -> Option<NTP64> {
+        match (self.times.front(), self.times.back()) {
+            (Some(first), Some(last)) if self.times.len() > 1 => {
+                Some((*last - *first) / (self.times.len() - 1) as u32)
+            }
+            _ => None,
+        }
+    }
+
+    /// Discard all the recorded events.
+    pub fn clear(&mut self) {
+        self.times.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use core::time::Duration;
+
+    #[test]
+    fn rate_estimator() {
+        let id = ID::rand();
+        let ts = |ms: u64| Timestamp::new(NTP64::from(Duration::from_millis(ms)), id);
+        let mut estimator = RateEstimator::new(Duration::from_millis(100));
+        assert_eq!(estimator.rate(), 0.0);
+        assert!(estimator.mean_inter_arrival().is_none());
+
+        for ms in [10, 30, 20, 40] {
+            estimator.record(&ts(ms));
+        }
+        assert_eq!(estimator.count(), 4);
+        assert_eq!(estimator.rate().round(), 40.0);
+        let mean = estimator.mean_inter_arrival().unwrap();
+        assert_eq!((mean.as_secs_f64() * 1000.0).round(), 10.0);
+
+        // the events older than 100ms before the latest one are discarded
+        estimator.record(&ts(125));
+        assert_eq!(estimator.count(), 3);
+        estimator.record(&ts(1000));
+        assert_eq!(estimator.count(), 1);
+        estimator.clear();
+        assert_eq!(estimator.count(), 0);
+    }
+}
@@ -0,0 +1,148 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{DecodeError, Timestamp, ID, NTP64};
+use core::convert::TryFrom;
+use core::fmt;
+
+/// The raw memory representation of a [`Timestamp`], with the same 24 bytes layout than
+/// [`Timestamp::to_le_bytes()`]: the time as little endian, followed by the le-encoded [`ID`].
+///
+/// It implements the `zerocopy` traits (with the `zerocopy` feature) and the `bytemuck` traits
+/// (with the `bytemuck` feature), allowing to reinterpret a buffer (e.g. received from the network
+/// or in shared memory) as a [`RawTimestamp`] or a slice of [`RawTimestamp`]s without copy.
+/// As any bytes are a valid [`RawTimestamp`], it must be converted into a [`Timestamp`] with [`TryFrom`],
+/// that checks the [`ID`] is not zero.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "zerocopy")] {
+/// use std::convert::TryFrom;
+/// use uhlc::{RawTimestamp, Timestamp, HLC};
+/// use zerocopy::{FromBytes, IntoBytes};
+///
+/// let hlc = HLC::default();
+/// let stamps = [hlc.new_timestamp(), hlc.new_timestamp()];
+/// let mut buf = Vec::new();
+/// for ts in &stamps {
+///     buf.extend_from_slice(RawTimestamp::from(ts).as_bytes());
+/// }
+///
+/// let raws = <[RawTimestamp]>::ref_from_bytes(&buf).unwrap();
+/// assert_eq!(Timestamp::try_from(&raws[1]).unwrap(), stamps[1]);
+/// # }
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "zerocopy",
+    derive(
+        zerocopy::FromBytes,
+        zerocopy::IntoBytes,
+        zerocopy::Unaligned,
+        zerocopy::KnownLayout,
+        zerocopy::Immutable
+    )
+)]
+#[cfg_attr(feature = "bytemuck", derive(bytemuck::Pod, bytemuck::Zeroable))]
+#[repr(C)]
+pub struct RawTimestamp {
+    time: [u8; 8],
+    id: [u8; ID::MAX_SIZE],
+}
+
+impl RawTimestamp {
+    /// Returns the raw time.
+    #[inline]
+    pub fn get_time(&self) -> NTP64 {
+        NTP64(u64::from_le_bytes(self.time))
+    }
+}
+
+impl From<&Timestamp> for RawTimestamp {
+    fn from(ts: &Timestamp) -> Self {
+        RawTimestamp {
+            time: ts.get_time().as_u64().to_le_bytes(),
+            id: ts.get_id().to_le_bytes(),
+        }
+    }
+}
+
+impl From<Timestamp> for RawTimestamp {
+    fn from(ts: Timestamp) -> Self {
+        RawTimestamp::from(&ts)
+    }
+}
+
+impl TryFrom<&RawTimestamp> for Timestamp {
+    type Error = DecodeError;
+
+    fn try_from(raw: &RawTimestamp) -> Result<Self, Self::Error> {
+        match ID::from_le_bytes_checked(raw.id) {
+            Some(id) => Ok(Timestamp::new(raw.get_time(), id)),
+            None => Err(DecodeError::ZeroId),
+        }
+    }
+}
+
+impl TryFrom<RawTimestamp> for Timestamp {
+    type Error = DecodeError;
+
+    fn try_from(raw: RawTimestamp) -> Result<Self, Self::Error> {
+        Timestamp::try_from(&raw)
+    }
+}
+
+impl fmt::Debug for RawTimestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match Timestamp::try_from(self) {
+            Ok(ts) => fmt::Debug::fmt(&ts, f),
+            Err(_) => write!(f, "{:?}/<zero id>", self.get_time()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use core::convert::TryFrom;
+
+    #[cfg(feature = "zerocopy")]
+    #[test]
+    fn raw_timestamp() {
+        use zerocopy::{FromBytes, IntoBytes};
+
+        let ts = HLC::default().new_timestamp();
+        let raw = RawTimestamp::from(&ts);
+        assert_eq!(raw.as_bytes(), &ts.to_le_bytes());
+        assert_eq!(Timestamp::try_from(raw).unwrap(), ts);
+
+        let zero_id = [0u8; 24];
+        let raw = RawTimestamp::read_from_bytes(&zero_id).unwrap();
+        assert_eq!(Timestamp::try_from(raw), Err(DecodeError::ZeroId));
+        assert!(RawTimestamp::ref_from_bytes(&zero_id[1..]).is_err());
+    }
+
+    #[cfg(feature = "bytemuck")]
+    #[test]
+    fn bytemuck_cast() {
+        let hlc = HLC::default();
+        let stamps: Vec<RawTimestamp> = (0..4).map(|_| hlc.new_timestamp().into()).collect();
+        let bytes: &[u8] = bytemuck::cast_slice(&stamps);
+        assert_eq!(bytes.len(), 4 * 24);
+        let raws: &[RawTimestamp] = bytemuck::cast_slice(bytes);
+        assert_eq!(raws, &stamps[..]);
+        assert!(Timestamp::try_from(<RawTimestamp as bytemuck::Zeroable>::zeroed()).is_err());
+
+        let times = [NTP64(1), NTP64(2)];
+        let words: &[u64] = bytemuck::cast_slice(&times);
+        assert_eq!(words, [1, 2]);
+    }
+}
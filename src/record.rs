@@ -0,0 +1,448 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{Mutex, Timestamp, HLC, NTP64};
+use alloc::{collections::VecDeque, sync::Arc, vec::Vec};
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "std")]
+use {
+    alloc::{format, string::String},
+    core::str::FromStr,
+    std::io::Write,
+};
+
+/// An event of an [`HLC`], recorded by a [`Recorder`].
+///
+/// Each event comes with the reading of the HLC's physical clock (`now`) at the time of the event,
+/// allowing to replay it deterministically with a [`Replayer`].
+///
+/// With the `std` feature, an event is formatted to (and parsed from) a String as such:
+/// `"<kind> <now> <timestamp>"`, where `<kind>` is one of `issued`, `accepted` or `rejected`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Event {
+    /// A [`Timestamp`] was issued by [`HLC::new_timestamp()`].
+    Issued { now: NTP64, timestamp: Timestamp },
+    /// An update with a [`Timestamp`] was accepted by [`HLC::update_with_timestamp()`].
+    Accepted { now: NTP64, timestamp: Timestamp },
+    /// An update with a [`Timestamp`] was rejected by [`HLC::update_with_timestamp()`].
+    Rejected { now: NTP64, timestamp: Timestamp },
+}
+
+impl Event {
+    /// Returns the reading of the HLC's physical clock at the time of this event.
+    pub fn get_now(&self) -> &NTP64 {
+        match self {
+            Event::Issued { now, .. }
+            | Event::Accepted { now, .. }
+            | Event::Rejected { now, .. } => now,
+        }
+    }
+
+    /// Returns the [`Timestamp`] that was issued, or used for an update.
+    pub fn get_timestamp(&self) -> &Timestamp {
+        match self {
+            Event::Issued { timestamp, .. }
+            | Event::Accepted { timestamp, .. }
+            | Event::Rejected { timestamp, .. } => timestamp,
+        }
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kind = match self {
+            Event::Issued { .. } => "issued",
+            Event::Accepted { .. } => "accepted",
+            Event::Rejected { .. } => "rejected",
+        };
+        write!(f, "{} {} {}", kind, self.get_now(), self.get_timestamp())
+    }
+}
+
+#[cfg(feature = "std")]
+impl FromStr for Event {
+    type Err = ParseEventError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(' ');
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(kind), Some(snow), Some(stimestamp), None) => {
+                let now = NTP64::from_str(snow).map_err(|e| ParseEventError { cause: e.cause })?;
+                let timestamp = Timestamp::from_str(stimestamp)
+                    .map_err(|e| ParseEventError { cause: e.cause })?;
+                match kind {
+                    "issued" => Ok(Event::Issued { now, timestamp }),
+                    "accepted" => Ok(Event::Accepted { now, timestamp }),
+                    "rejected" => Ok(Event::Rejected { now, timestamp }),
+                    _ => Err(ParseEventError {
+                        cause: format!("Invalid event kind: '{kind}'"),
+                    }),
+                }
+            }
+            _ => Err(ParseEventError {
+                cause: format!("Invalid event: '{s}'"),
+            }),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ParseEventError {
+    pub cause: String,
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for ParseEventError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid HLC event: {}", self.cause)
+    }
+}
+
+#[cfg(feature = "std")]
+impl core::error::Error for ParseEventError {}
+
+/// A recorder of the [`Event`]s of an [`HLC`] (see [`crate::HLCBuilder::with_recorder()`]).
+///
+/// The events are recorded in the order the HLC's state changed: the recorded operations of the HLC
+/// (and of its clones) are serialized, so [`Recorder::record()`] must not call the HLC back.
+pub trait Recorder: Send + Sync {
+    /// Record an [`Event`].
+    fn record(&self, event: Event);
+}
+
+/// A [`Recorder`] keeping the latest [`Event`]s in a ring buffer.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use uhlc::{HLCBuilder, RingRecorder};
+///
+/// let recorder = Arc::new(RingRecorder::new(1024));
+/// let hlc = HLCBuilder::new().with_recorder(recorder.clone()).build();
+/// let ts = hlc.new_timestamp();
+/// assert_eq!(recorder.events()[0].get_timestamp(), &ts);
+/// ```
+pub struct RingRecorder {
+    capacity: usize,
+    events: Mutex<VecDeque<Event>>,
+}
+
+impl RingRecorder {
+    /// Create a [`RingRecorder`] keeping at most the `capacity` latest [`Event`]s.
+    pub fn new(capacity: usize) -> RingRecorder {
+        RingRecorder {
+            capacity,
+            events: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// Returns the recorded [`Event`]s, from the oldest to the latest.
+    pub fn events(&self) -> Vec<Event> {
+        lock!(self.events).iter().copied().collect()
+    }
+}
+
+impl Recorder for RingRecorder {
+    fn record(&self, event: Event) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut events = lock!(self.events);
+        if events.len() == self.capacity {
+            events.pop_front();
+        }
+        events.push_back(event);
+    }
+}
+
+/// A [`Recorder`] writing each [`Event`] as a line to a [`Write`]r (e.g. a file).
+///
+/// The lines can be parsed back with [`Event::from_str()`].
+#[cfg(feature = "std")]
+pub struct WriterRecorder<W> {
+    writer: Mutex<W>,
+}
+
+#[cfg(feature = "std")]
+impl<W: Write + Send> WriterRecorder<W> {
+    /// Create a [`WriterRecorder`] writing to `writer`.
+    pub fn new(writer: W) -> WriterRecorder<W> {
+        WriterRecorder {
+            writer: Mutex::new(writer),
+        }
+    }
+
+    /// Consumes this [`WriterRecorder`], returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write + Send> Recorder for WriterRecorder<W> {
+    fn record(&self, event: Event) {
+        if let Err(e) = writeln!(lock!(self.writer), "{event}") {
+            log::warn!("failed to record HLC event '{}': {}", event, e);
+        }
+    }
+}
+
+// The Recorder of an HLC (shared with its clones), with the lock serializing the recorded operations:
+// the change of the HLC's state and the recording of its event are atomic, so that concurrent operations
+// are recorded in the order the state changed, and replayed the same.
+pub(crate) struct Recording {
+    recorder: Arc<dyn Recorder>,
+    lock: Mutex<()>,
+}
+
+impl Recording {
+    pub(crate) fn new(recorder: Arc<dyn Recorder>) -> Recording {
+        Recording {
+            recorder,
+            lock: Mutex::new(()),
+        }
+    }
+}
+
+impl HLC {
+    // Serializes the recorded operations while the returned guard is alive, if the HLC has a Recorder
+    pub(crate) fn recording(&self) -> Option<impl Sized + '_> {
+        self.recorder
+            .as_ref()
+            .map(|recording| lock!(recording.lock))
+    }
+
+    pub(crate) fn record(&self, event: Event) {
+        if let Some(recording) = &self.recorder {
+            recording.recorder.record(event);
+        }
+    }
+}
+
+/// A replayer of recorded [`Event`]s into an [`HLC`].
+///
+/// For each replayed [`Event`], the replayer performs the same operation on its HLC, using the recorded
+/// reading of the physical clock instead of the HLC's clock. Thus, replaying a recording into a fresh HLC
+/// with the same [`crate::ID`] and configuration than the recorded one reproduces the same events.
+/// A [`ReplayError`] is returned at the first event that diverges from the recording.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use uhlc::{HLCBuilder, Replayer, RingRecorder, HLC};
+///
+/// let recorder = Arc::new(RingRecorder::new(1024));
+/// let hlc = HLCBuilder::new().with_recorder(recorder.clone()).build();
+/// hlc.new_timestamp();
+/// hlc.update_with_timestamp(&HLC::default().new_timestamp()).unwrap();
+/// hlc.new_timestamp();
+///
+/// let replayer = Replayer::new(HLCBuilder::new().with_id(*hlc.get_id()).build());
+/// assert!(replayer.replay(recorder.events()).is_ok());
+/// ```
+pub struct Replayer {
+    hlc: HLC,
+}
+
+impl Replayer {
+    /// Create a [`Replayer`] into `hlc`.
+    pub fn new(hlc: HLC) -> Replayer {
+        Replayer { hlc }
+    }
+
+    /// Returns the [`HLC`] the events are replayed into.
+    pub fn get_hlc(&self) -> &HLC {
+        &self.hlc
+    }
+
+    /// Consumes this [`Replayer`], returning the [`HLC`] the events were replayed into.
+    pub fn into_hlc(self) -> HLC {
+        self.hlc
+    }
+
+    /// Replay an [`Event`], returning a [`ReplayError`] if it diverges from the recorded one.
+    pub fn replay_event(&self, event: &Event) -> Result<(), ReplayError> {
+        let replayed = match *event {
+            Event::Issued { now, .. } => Event::Issued {
+                now,
+                timestamp: self.hlc.new_timestamp_at(now),
+            },
+            Event::Accepted { now, timestamp } | Event::Rejected { now, timestamp } => {
+                match self.hlc.update_with_timestamp_at(&timestamp, now) {
+                    Ok(()) => Event::Accepted { now, timestamp },
+                    Err(_) => Event::Rejected { now, timestamp },
+                }
+            }
+        };
+        if replayed == *event {
+            Ok(())
+        } else {
+            Err(ReplayError {
+                expected: *event,
+                replayed,
+            })
+        }
+    }
+
+    /// Replay [`Event`]s in order, stopping at the first one that diverges from the recording.
+    pub fn replay<I>(&self, events: I) -> Result<(), ReplayError>
+    where
+        I: IntoIterator<Item = Event>,
+    {
+        events
+            .into_iter()
+            .try_for_each(|event| self.replay_event(&event))
+    }
+}
+
+/// The error returned by a [`Replayer`] when a replayed [`Event`] diverges from the recorded one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ReplayError {
+    /// The recorded [`Event`].
+    pub expected: Event,
+    /// The [`Event`] that occurred when replaying it.
+    pub replayed: Event,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Replay diverged: expected '{}' but got '{}'",
+            self.expected, self.replayed
+        )
+    }
+}
+
+impl core::error::Error for ReplayError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use alloc::sync::Arc;
+    use core::time::Duration;
+
+    #[test]
+    fn record_and_replay() {
+        let recorder = Arc::new(RingRecorder::new(100));
+        let hlc = HLCBuilder::new().with_recorder(recorder.clone()).build();
+        let other = HLC::default();
+        for _ in 0..10 {
+            hlc.new_timestamp();
+            hlc.update_with_timestamp(&other.new_timestamp()).unwrap();
+        }
+        let future_time = other.new_timestamp().get_time() + NTP64::from(Duration::from_secs(10));
+        let future_ts = Timestamp::new(future_time, *other.get_id());
+        assert!(hlc.update_with_timestamp(&future_ts).is_err());
+        hlc.new_timestamp();
+
+        let events = recorder.events();
+        assert_eq!(events.len(), 22);
+        assert!(matches!(events[20], Event::Rejected { .. }));
+
+        // the recording is reproduced by an HLC with the same ID
+        let replayer = Replayer::new(HLCBuilder::new().with_id(*hlc.get_id()).build());
+        replayer.replay(events.iter().copied()).unwrap();
+
+        // but diverges with another ID
+        let replayer = Replayer::new(HLC::default());
+        let err = replayer.replay(events.iter().copied()).unwrap_err();
+        assert_eq!(err.expected, events[0]);
+
+        // the ring buffer only keeps the latest events
+        let recorder = RingRecorder::new(2);
+        for e in events.iter() {
+            recorder.record(*e);
+        }
+        assert_eq!(recorder.events(), &events[20..]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn concurrent_record_and_replay() {
+        use std::sync::Barrier;
+
+        // a recorder yielding before recording, widening the window for another event to be recorded first
+        struct SlowRecorder(RingRecorder);
+        impl Recorder for SlowRecorder {
+            fn record(&self, event: Event) {
+                std::thread::yield_now();
+                self.0.record(event);
+            }
+        }
+
+        // with a still clock, each event increments the logical counter: recording 2 events in another order
+        // than their changes of the state makes the replay diverge
+        let recorder = Arc::new(SlowRecorder(RingRecorder::new(20_000)));
+        let hlc = HLCBuilder::new()
+            .with_clock(zero_clock)
+            .with_recorder(recorder.clone())
+            .build();
+        let barrier = Arc::new(Barrier::new(4));
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let (hlc, barrier) = (hlc.clone(), barrier.clone());
+                let other = HLCBuilder::new().with_clock(zero_clock).build();
+                std::thread::spawn(move || {
+                    barrier.wait();
+                    for _ in 0..2000 {
+                        hlc.new_timestamp();
+                        hlc.update_with_timestamp(&other.new_timestamp()).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let events = recorder.0.events();
+        assert_eq!(events.len(), 16_000);
+        let replayer = Replayer::new(HLCBuilder::new().with_id(*hlc.get_id()).build());
+        replayer.replay(events).unwrap();
+        assert_eq!(
+            replayer.get_hlc().new_timestamp_at(NTP64(0)),
+            hlc.new_timestamp_at(NTP64(0))
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn writer_recorder() {
+        use std::str::FromStr;
+
+        let recorder = Arc::new(WriterRecorder::new(Vec::new()));
+        let hlc = HLCBuilder::new().with_recorder(recorder.clone()).build();
+        let ts = hlc.new_timestamp();
+        hlc.update_with_timestamp(&HLC::default().new_timestamp())
+            .unwrap();
+        drop(hlc);
+
+        let output =
+            String::from_utf8(Arc::try_unwrap(recorder).ok().unwrap().into_inner()).unwrap();
+        let events = output
+            .lines()
+            .map(Event::from_str)
+            .collect::<Result<Vec<Event>, _>>()
+            .unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].get_timestamp(), &ts);
+        assert!(matches!(events[1], Event::Accepted { .. }));
+        assert!(Event::from_str("issued 0").is_err());
+    }
+}
@@ -0,0 +1,199 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{CounterExhausted, Timestamp, TimestampRange, CMASK, HLC, ID, LMASK, NTP64};
+
+#[cfg(feature = "record")]
+use super::Event;
+
+/// A batch of consecutive [`Timestamp`]s reserved at once by [`HLC::reserve()`].
+///
+/// Its [`Timestamp`]s share the same physical time and differ by their logical counter only:
+/// no other [`Timestamp`] of the [`HLC`] is in between.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TimestampRangeHandle {
+    first: NTP64,
+    len: u8,
+    id: ID,
+}
+
+impl TimestampRangeHandle {
+    /// Returns the number of reserved [`Timestamp`]s.
+    #[inline]
+    pub fn len(&self) -> u8 {
+        self.len
+    }
+
+    /// Returns `true` if no [`Timestamp`] was reserved.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the `index`-th reserved [`Timestamp`], if `index` is lower than [`Self::len()`].
+    pub fn get(&self, index: u8) -> Option<Timestamp> {
+        (index < self.len).then(|| Timestamp::new(self.first + u64::from(index), self.id))
+    }
+
+    /// Returns an iterator over the reserved [`Timestamp`]s, in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = Timestamp> + '_ {
+        (0..self.len).filter_map(move |index| self.get(index))
+    }
+
+    /// Returns the range of the reserved [`Timestamp`]s.
+    pub fn range(&self) -> TimestampRange {
+        TimestampRange::new(
+            Timestamp::new(self.first, self.id),
+            Timestamp::new(self.first + u64::from(self.len), self.id),
+        )
+    }
+}
+
+impl HLC {
+    /// Reserve `n` consecutive [`Timestamp`]s at once, e.g. for the records of a transaction: they share the same
+    /// physical time and their order is known, with no other [`Timestamp`] of this [`HLC`] in between.
+    ///
+    /// Returns [`CounterExhausted`] if the logical counter of the current physical time can't hold `n` more
+    /// [`Timestamp`]s, or if the last one would exceed the maximum logical lead
+    /// (see [`crate::HLCBuilder::with_max_logical_lead()`]): the reservation may succeed later,
+    /// once the physical time advanced, unless `n` exceeds the `2^CSIZE` values of the counter
+    /// (see [`crate::CSIZE`]) or the values within the maximum logical lead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use uhlc::HLC;
+    ///
+    /// let hlc = HLC::default();
+    /// let batch = hlc.reserve(3).unwrap();
+    /// let stamps: Vec<_> = batch.iter().collect();
+    /// assert_eq!(stamps.len(), 3);
+    /// assert!(stamps[0] < stamps[1] && stamps[1] < stamps[2]);
+    /// assert!(hlc.new_timestamp() > stamps[2]);
+    /// ```
+    pub fn reserve(&self, n: u8) -> Result<TimestampRangeHandle, CounterExhausted> {
+        #[cfg(feature = "record")]
+        let _recording = self.recording();
+        let mut handle = TimestampRangeHandle {
+            first: self.last_time.get(),
+            len: n,
+            id: self.id,
+        };
+        if n == 0 {
+            return Ok(handle);
+        }
+        let clock_time = self.read_clock();
+        let now = self.physical_time(clock_time);
+        // the time of the 1st Timestamp as in `new_timestamp_at()`, and the number of counter values left from it
+        // within the maximum logical lead, if any
+        let first_time = |last_time: NTP64| {
+            let first = if now.0 > (last_time.0 & LMASK) {
+                now
+            } else {
+                NTP64(last_time.0.checked_add(1)?)
+            };
+            let mut available = CMASK - (first.0 & CMASK) + 1;
+            if let Some(max) = self.max_lead {
+                let limit = (clock_time.0 & LMASK).saturating_add(max.0);
+                available = available.min(limit.saturating_add(1).saturating_sub(first.0));
+            }
+            Some((first, available))
+        };
+        let count = u64::from(n);
+        match self.last_time.try_update(|last_time| {
+            let (first, available) = first_time(last_time)?;
+            (count <= available).then(|| first + (count - 1))
+        }) {
+            Some(last) => handle.first = last - (count - 1),
+            None => {
+                return Err(CounterExhausted {
+                    requested: n,
+                    available: first_time(self.last_time.get())
+                        .map_or(0, |(_, available)| available),
+                })
+            }
+        }
+        #[cfg(feature = "record")]
+        for timestamp in handle.iter() {
+            self.record(Event::Issued {
+                now: clock_time,
+                timestamp,
+            });
+        }
+        Ok(handle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn reserve() {
+        let hlc = HLCBuilder::new().with_clock(zero_clock).build();
+        let batch = hlc.reserve(3).unwrap();
+        let stamps: Vec<Timestamp> = batch.iter().collect();
+        let times: Vec<u64> = stamps.iter().map(|ts| ts.get_time().0).collect();
+        assert_eq!(times, [1, 2, 3]);
+        assert_eq!(batch.get(3), None);
+        assert!(batch.range().contains(&stamps[2]));
+        assert_eq!(hlc.new_timestamp().get_time().0, 4);
+
+        // the last counter value of the physical time
+        hlc.advance_frontier(NTP64(CMASK - 1));
+        assert_eq!(
+            hlc.reserve(2),
+            Err(CounterExhausted {
+                requested: 2,
+                available: 1
+            })
+        );
+        assert_eq!(hlc.reserve(1).unwrap().get(0).unwrap().get_time().0, CMASK);
+        // then, the counter of the next physical time
+        let batch = hlc.reserve(2).unwrap();
+        assert_eq!(batch.get(0).unwrap().get_time().0, CMASK + 1);
+        assert_eq!(batch.get(1).unwrap().get_time().0, CMASK + 2);
+
+        let hlc = HLCBuilder::new().with_clock(system_time_clock).build();
+        let batch = hlc.reserve(2).unwrap();
+        let (first, second) = (batch.get(0).unwrap(), batch.get(1).unwrap());
+        assert_eq!(first.get_time().0 & LMASK, second.get_time().0 & LMASK);
+        assert_eq!(second.get_time().0, first.get_time().0 + 1);
+        assert!(hlc.reserve(0).unwrap().is_empty());
+
+        // the reservations are within the maximum logical lead: 2 values left before exceeding it
+        let max_lead = core::time::Duration::from_micros(1);
+        let lead = NTP64::from(max_lead);
+        let hlc = HLCBuilder::new()
+            .with_clock(zero_clock)
+            .with_max_logical_lead(max_lead)
+            .build();
+        hlc.advance_frontier(NTP64(lead.0 - 2));
+        assert_eq!(
+            hlc.reserve(3),
+            Err(CounterExhausted {
+                requested: 3,
+                available: 2
+            })
+        );
+        assert_eq!(hlc.reserve(2).unwrap().get(1).unwrap().get_time().0, lead.0);
+        assert!(hlc.try_new_timestamp().is_err());
+
+        // a maximum-range lead saturates its limit
+        let hlc = HLCBuilder::new()
+            .with_clock(system_time_clock)
+            .with_max_logical_lead(core::time::Duration::from_secs(u64::from(u32::MAX)))
+            .build();
+        assert_eq!(hlc.reserve(3).unwrap().len(), 3);
+        assert!(hlc.try_new_timestamp().is_ok());
+    }
+}
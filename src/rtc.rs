@@ -0,0 +1,152 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{set_global_clock, zero_clock, Mutex, NTP64};
+
+impl NTP64 {
+    /// Convert a number of `ticks` of a counter running at `frequency_hz` into a [`NTP64`].
+    ///
+    /// The conversion is made in fixed-point arithmetic without any 128 bits operation,
+    /// and is rounded to the nearest NTP64 fraction (i.e. its error is less than ~117 picoseconds).
+    ///
+    /// # Panics
+    ///
+    /// If `frequency_hz` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use uhlc::NTP64;
+    /// use std::time::Duration;
+    ///
+    /// // 1.5s with a 32768Hz crystal
+    /// let t = NTP64::from_ticks(49152, 32768);
+    /// assert_eq!(t.to_duration(), Duration::from_millis(1500));
+    /// ```
+    pub fn from_ticks(ticks: u64, frequency_hz: u32) -> NTP64 {
+        let frequency = frequency_hz as u64;
+        let secs = ticks / frequency;
+        // rem < frequency <= u32::MAX, so shifting it by 32 bits (and rounding) doesn't overflow
+        let frac = (((ticks % frequency) << 32) + frequency / 2) / frequency;
+        NTP64((secs << 32) + frac)
+    }
+}
+
+/// A physical clock anchored to a wall time reading (e.g. from an I2C RTC such as a DS3231 or a PCF8563 at boot),
+/// and advancing with a monotonic tick source (e.g. a hardware timer or the systick of a MCU).
+///
+/// It returns the anchored wall time, plus the time elapsed since the anchoring as counted by the tick source.
+///
+/// # Examples
+///
+/// ```
+/// use uhlc::{install_anchored_clock, AnchoredClock, HLC, NTP64};
+/// use std::time::Duration;
+///
+/// fn timer_ticks() -> u64 {
+///     // read the 32768Hz timer...
+///     # 0
+/// }
+///
+/// // the wall time read from the RTC at boot, relative to UNIX_EPOCH
+/// let rtc_time = NTP64::from(Duration::from_secs(1_700_000_000));
+/// install_anchored_clock(AnchoredClock::new(rtc_time, timer_ticks, 32768));
+///
+/// let hlc = HLC::default();
+/// assert_eq!(hlc.new_timestamp().get_time().as_secs(), rtc_time.as_secs());
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct AnchoredClock {
+    wall_time: NTP64,
+    anchor_ticks: u64,
+    ticks: fn() -> u64,
+    frequency_hz: u32,
+}
+
+impl AnchoredClock {
+    /// Create an [`AnchoredClock`] anchoring the current reading of `ticks` to `wall_time`.
+    ///
+    /// The `ticks` function must return the value of a monotonic counter running at `frequency_hz`.
+    /// This counter is allowed to wrap around u64.
+    ///
+    /// # Panics
+    ///
+    /// If `frequency_hz` is 0.
+    pub fn new(wall_time: NTP64, ticks: fn() -> u64, frequency_hz: u32) -> AnchoredClock {
+        assert!(frequency_hz > 0, "The tick frequency must not be 0");
+        AnchoredClock {
+            wall_time,
+            anchor_ticks: ticks(),
+            ticks,
+            frequency_hz,
+        }
+    }
+
+    /// Returns the current time of this clock.
+    pub fn now(&self) -> NTP64 {
+        let elapsed = (self.ticks)().wrapping_sub(self.anchor_ticks);
+        self.wall_time + NTP64::from_ticks(elapsed, self.frequency_hz)
+    }
+}
+
+// The clock installed with install_anchored_clock()
+static ANCHORED_CLOCK: Mutex<Option<AnchoredClock>> = Mutex::new(None);
+
+/// Install an [`AnchoredClock`] as the clock used by [`anchored_clock()`],
+/// and install [`anchored_clock()`] as the global clock (see [`set_global_clock()`]).
+pub fn install_anchored_clock(clock: AnchoredClock) {
+    *lock!(ANCHORED_CLOCK) = Some(clock);
+    set_global_clock(anchored_clock);
+}
+
+/// A physical clock calling the [`AnchoredClock`] installed with [`install_anchored_clock()`].
+///
+/// If no [`AnchoredClock`] was installed, it calls [`zero_clock()`].
+pub fn anchored_clock() -> NTP64 {
+    match *lock!(ANCHORED_CLOCK) {
+        Some(clock) => clock.now(),
+        None => zero_clock(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use core::sync::atomic::{AtomicU64, Ordering};
+    use core::time::Duration;
+
+    #[test]
+    fn anchored_clock() {
+        for frequency in [1, 1000, 32768, 48_000_000, u32::MAX] {
+            let f = frequency as u64;
+            assert_eq!(NTP64::from_ticks(0, frequency), NTP64(0));
+            assert_eq!(NTP64::from_ticks(f, frequency), NTP64(1 << 32));
+            assert_eq!(NTP64::from_ticks(3 * f / 2, frequency).as_secs(), 1);
+            assert_eq!(
+                NTP64::from_ticks(u64::MAX, frequency).as_secs(),
+                (u64::MAX / f) as u32
+            );
+        }
+
+        static TICKS: AtomicU64 = AtomicU64::new(u64::MAX - 1000);
+        fn ticks() -> u64 {
+            TICKS.load(Ordering::Relaxed)
+        }
+
+        let wall_time = NTP64::from(Duration::from_secs(1_700_000_000));
+        let clock = AnchoredClock::new(wall_time, ticks, 1000);
+        assert_eq!(clock.now(), wall_time);
+        // the tick counter wraps around
+        TICKS.store(1500, Ordering::Relaxed);
+        assert_eq!(clock.now(), wall_time + NTP64::from_ticks(2501, 1000));
+        let elapsed = (clock.now() - wall_time).to_duration();
+        assert!(elapsed.as_nanos().abs_diff(2_501_000_000) <= 1);
+    }
+}
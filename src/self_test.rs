@@ -0,0 +1,132 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{HLC, NTP64};
+use core::time::Duration;
+use std::time::Instant;
+
+// The number of readings of the clock timed by HLC::self_test()
+const SELF_TEST_SAMPLES: usize = 10_000;
+// How long HLC::self_test() keeps reading a clock that didn't advance during the timed readings
+const SELF_TEST_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// The result of [`HLC::self_test()`]: how the physical clock of an [`HLC`] behaved over consecutive readings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SelfTestReport {
+    /// The number of readings of the clock.
+    pub samples: usize,
+    /// The estimated resolution of the clock: the smallest step between 2 consecutive increasing readings,
+    /// or `None` if the clock never advanced.
+    pub resolution: Option<Duration>,
+    /// The number of readings lower than the previous one.
+    pub regressions: usize,
+    /// The largest step back between 2 consecutive readings.
+    pub max_regression: Duration,
+    /// The mean time to read the clock.
+    pub cost_per_read: Duration,
+}
+
+impl SelfTestReport {
+    /// Returns `true` if the clock advanced and never went back.
+    pub fn is_sane(&self) -> bool {
+        self.resolution.is_some() && self.regressions == 0
+    }
+
+    // Account for a reading `now` following `previous`
+    fn add(&mut self, previous: NTP64, now: NTP64) {
+        self.samples += 1;
+        if now > previous {
+            let step = (now - previous).to_duration();
+            self.resolution = Some(self.resolution.map_or(step, |r| r.min(step)));
+        } else if now < previous {
+            self.regressions += 1;
+            self.max_regression = self.max_regression.max((previous - now).to_duration());
+        }
+    }
+}
+
+impl HLC {
+    /// Exercise the physical clock of this [`HLC`] (see [`crate::HLCBuilder::with_clock()`]) and report
+    /// an estimate of its resolution, its monotonicity and the cost of its readings, e.g. to check
+    /// at the startup of a service that the chosen clock source is sane before serving traffic.
+    ///
+    /// The clock is read 10,000 times in a row. If it didn't advance meanwhile (e.g. a coarse clock),
+    /// it's read again for up to 100ms until it does. Note that this function blocks the calling thread meanwhile.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use uhlc::{monotonic_clock, zero_clock, HLCBuilder};
+    ///
+    /// let report = HLCBuilder::new().with_clock(monotonic_clock).build().self_test();
+    /// assert!(report.is_sane());
+    ///
+    /// let report = HLCBuilder::new().with_clock(zero_clock).build().self_test();
+    /// assert_eq!(report.resolution, None);
+    /// assert!(!report.is_sane());
+    /// ```
+    pub fn self_test(&self) -> SelfTestReport {
+        let mut report = SelfTestReport {
+            samples: 1,
+            resolution: None,
+            regressions: 0,
+            max_regression: Duration::ZERO,
+            cost_per_read: Duration::ZERO,
+        };
+        let start = Instant::now();
+        let mut previous = self.read_clock();
+        for _ in 1..SELF_TEST_SAMPLES {
+            let now = self.read_clock();
+            report.add(previous, now);
+            previous = now;
+        }
+        report.cost_per_read = start.elapsed() / SELF_TEST_SAMPLES as u32;
+
+        while report.resolution.is_none() && start.elapsed() < SELF_TEST_TIMEOUT {
+            let now = self.read_clock();
+            report.add(previous, now);
+            previous = now;
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use core::sync::atomic::{AtomicU64, Ordering};
+    use core::time::Duration;
+
+    #[test]
+    fn self_test() {
+        // a clock advancing by 1ms at each reading, but going back by 10ms every 1000 readings
+        static READS: AtomicU64 = AtomicU64::new(0);
+        fn flaky_clock() -> NTP64 {
+            let n = READS.fetch_add(1, Ordering::SeqCst);
+            NTP64::from(Duration::from_millis(1_000_000 + n - 11 * (n / 1000)))
+        }
+        let hlc = HLCBuilder::new().with_clock(flaky_clock).build();
+        READS.store(0, Ordering::SeqCst);
+        let report = hlc.self_test();
+        assert_eq!(report.samples, 10_000);
+        assert_eq!(report.resolution, Some(Duration::from_millis(1)));
+        assert_eq!(report.regressions, 9);
+        assert_eq!(report.max_regression, Duration::from_millis(10));
+        assert!(!report.is_sane());
+
+        let report = HLCBuilder::new()
+            .with_clock(monotonic_clock)
+            .build()
+            .self_test();
+        assert!(report.is_sane());
+        assert!(report.resolution.unwrap() < Duration::from_millis(100));
+        assert!(report.cost_per_read < Duration::from_millis(1));
+    }
+}
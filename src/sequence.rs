@@ -0,0 +1,213 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{Timestamp, ID};
+use alloc::collections::BTreeMap;
+
+/// How a [`Timestamp`] observed by a [`SequenceTracker`] relates to the previous ones of its [`ID`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SequenceStatus {
+    /// The first [`Timestamp`] of its [`ID`].
+    First,
+    /// The [`Timestamp`] (and sequence number, if any) follows the latest one of its [`ID`].
+    InOrder,
+    /// The sequence number skips `missing` numbers after the latest one of its [`ID`]: some stamps were lost,
+    /// or will arrive later (as [`SequenceStatus::Reordered`]).
+    Gap {
+        /// The number of skipped sequence numbers.
+        missing: u64,
+    },
+    /// The sequence number is lower than the latest one of its [`ID`]: the stamp arrived late.
+    Reordered,
+    /// The [`Timestamp`] (or sequence number, if any) is the same as the latest one of its [`ID`].
+    Duplicate,
+    /// The [`Timestamp`] is lower than the latest one of its [`ID`], while its sequence number (if any) is ahead:
+    /// the clock of its [`crate::HLC`] went backward (e.g. restarted without its latest time).
+    Regression {
+        /// The latest [`Timestamp`] of its [`ID`].
+        latest: Timestamp,
+    },
+}
+
+// The latest stamp observed from an ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Latest {
+    timestamp: Timestamp,
+    seq: Option<u64>,
+}
+
+/// A tracker of the [`Timestamp`]s received from several [`ID`]s, detecting per [`ID`] the regressions of
+/// their [`Timestamp`]s, and the gaps and reorderings of their sequence numbers if the stamps carry some
+/// (see [`SequenceTracker::observe_seq()`]).
+///
+/// # Examples
+///
+/// ```
+/// use uhlc::{SequenceStatus, SequenceTracker, HLC};
+///
+/// let hlc = HLC::default();
+/// let (ts1, ts2, ts3) = (hlc.new_timestamp(), hlc.new_timestamp(), hlc.new_timestamp());
+///
+/// let mut tracker = SequenceTracker::new();
+/// assert_eq!(tracker.observe_seq(&ts1, 1), SequenceStatus::First);
+/// assert_eq!(tracker.observe_seq(&ts3, 3), SequenceStatus::Gap { missing: 1 });
+/// assert_eq!(tracker.observe_seq(&ts2, 2), SequenceStatus::Reordered);
+/// assert_eq!(tracker.latest(hlc.get_id()), Some(&ts3));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SequenceTracker {
+    peers: BTreeMap<ID, Latest>,
+}
+
+impl SequenceTracker {
+    /// Create an empty [`SequenceTracker`].
+    pub fn new() -> SequenceTracker {
+        SequenceTracker::default()
+    }
+
+    /// Observe a [`Timestamp`] without sequence number: returns [`SequenceStatus::First`], [`SequenceStatus::InOrder`],
+    /// [`SequenceStatus::Duplicate`] or [`SequenceStatus::Regression`], comparing it with the latest [`Timestamp`]
+    /// of its [`ID`], which is updated if lower.
+    pub fn observe(&mut self, timestamp: &Timestamp) -> SequenceStatus {
+        let latest = match self.peers.get_mut(timestamp.get_id()) {
+            Some(latest) => latest,
+            None => {
+                self.insert(timestamp, None);
+                return SequenceStatus::First;
+            }
+        };
+        if *timestamp > latest.timestamp {
+            latest.timestamp = *timestamp;
+            SequenceStatus::InOrder
+        } else if *timestamp == latest.timestamp {
+            SequenceStatus::Duplicate
+        } else {
+            SequenceStatus::Regression {
+                latest: latest.timestamp,
+            }
+        }
+    }
+
+    /// Observe a [`Timestamp`] carrying the sequence number `seq` (incremented by 1 at each stamp of its [`ID`]):
+    /// returns [`SequenceStatus::Gap`], [`SequenceStatus::Reordered`] or [`SequenceStatus::Duplicate`] comparing
+    /// `seq` with the latest sequence number of its [`ID`], and [`SequenceStatus::Regression`] if `seq` is ahead
+    /// but not the [`Timestamp`]. The latest sequence number and [`Timestamp`] of its [`ID`] are updated if lower.
+    pub fn observe_seq(&mut self, timestamp: &Timestamp, seq: u64) -> SequenceStatus {
+        let latest = match self.peers.get_mut(timestamp.get_id()) {
+            Some(latest) => latest,
+            None => {
+                self.insert(timestamp, Some(seq));
+                return SequenceStatus::First;
+            }
+        };
+        let latest_seq = match latest.seq {
+            Some(latest_seq) => latest_seq,
+            // the previous stamps had no sequence number
+            None => {
+                latest.seq = Some(seq);
+                return self.observe(timestamp);
+            }
+        };
+        if seq <= latest_seq {
+            return if seq == latest_seq {
+                SequenceStatus::Duplicate
+            } else {
+                SequenceStatus::Reordered
+            };
+        }
+        latest.seq = Some(seq);
+        if *timestamp <= latest.timestamp {
+            return SequenceStatus::Regression {
+                latest: latest.timestamp,
+            };
+        }
+        latest.timestamp = *timestamp;
+        match seq - latest_seq - 1 {
+            0 => SequenceStatus::InOrder,
+            missing => SequenceStatus::Gap { missing },
+        }
+    }
+
+    /// Returns the latest [`Timestamp`] observed from `id`, if any.
+    pub fn latest(&self, id: &ID) -> Option<&Timestamp> {
+        self.peers.get(id).map(|latest| &latest.timestamp)
+    }
+
+    /// Returns the latest sequence number observed from `id`, if any.
+    pub fn latest_seq(&self, id: &ID) -> Option<u64> {
+        self.peers.get(id).and_then(|latest| latest.seq)
+    }
+
+    /// Stop tracking `id` (e.g. when the peer left), returning its latest [`Timestamp`], if any.
+    pub fn forget(&mut self, id: &ID) -> Option<Timestamp> {
+        self.peers.remove(id).map(|latest| latest.timestamp)
+    }
+
+    fn insert(&mut self, timestamp: &Timestamp, seq: Option<u64>) {
+        self.peers.insert(
+            *timestamp.get_id(),
+            Latest {
+                timestamp: *timestamp,
+                seq,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use core::convert::TryFrom;
+
+    #[test]
+    fn sequence_tracker() {
+        let ts = |t: u64, n: u8| Timestamp::new(NTP64(t), ID::try_from([n]).unwrap());
+        let mut tracker = SequenceTracker::new();
+
+        // without sequence numbers, for several IDs
+        assert_eq!(tracker.observe(&ts(10, 1)), SequenceStatus::First);
+        assert_eq!(tracker.observe(&ts(5, 2)), SequenceStatus::First);
+        assert_eq!(tracker.observe(&ts(11, 1)), SequenceStatus::InOrder);
+        assert_eq!(tracker.observe(&ts(11, 1)), SequenceStatus::Duplicate);
+        assert_eq!(
+            tracker.observe(&ts(9, 1)),
+            SequenceStatus::Regression { latest: ts(11, 1) }
+        );
+        assert_eq!(
+            tracker.latest(&ID::try_from([1]).unwrap()),
+            Some(&ts(11, 1))
+        );
+        assert_eq!(tracker.forget(&ID::try_from([2]).unwrap()), Some(ts(5, 2)));
+        assert_eq!(tracker.observe(&ts(1, 2)), SequenceStatus::First);
+
+        // with sequence numbers
+        assert_eq!(tracker.observe_seq(&ts(20, 3), 7), SequenceStatus::First);
+        assert_eq!(tracker.observe_seq(&ts(21, 3), 8), SequenceStatus::InOrder);
+        assert_eq!(
+            tracker.observe_seq(&ts(25, 3), 11),
+            SequenceStatus::Gap { missing: 2 }
+        );
+        assert_eq!(
+            tracker.observe_seq(&ts(23, 3), 9),
+            SequenceStatus::Reordered
+        );
+        assert_eq!(
+            tracker.observe_seq(&ts(25, 3), 11),
+            SequenceStatus::Duplicate
+        );
+        assert_eq!(
+            tracker.observe_seq(&ts(2, 3), 12),
+            SequenceStatus::Regression { latest: ts(25, 3) }
+        );
+        assert_eq!(tracker.latest_seq(&ID::try_from([3]).unwrap()), Some(12));
+        assert_eq!(tracker.observe_seq(&ts(26, 3), 13), SequenceStatus::InOrder);
+    }
+}
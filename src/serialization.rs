@@ -0,0 +1,234 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+
+/// Serializes a [`Timestamp`](crate::Timestamp) as 2 fields: `time` (its [`NTP64`](crate::NTP64) as a `u64`)
+/// and `id` (its [`ID`](crate::ID) as an hexadecimal string, see [`ID::from_str()`](crate::ID)).
+///
+/// Unlike the default representation, it can be `#[serde(flatten)]`ed into a struct, e.g. for JSON APIs
+/// or databases with flat schemas (the struct must then not have other `time` or `id` fields).
+///
+/// # Examples
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use uhlc::{Timestamp, ID, NTP64};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Event {
+///     name: String,
+///     #[serde(flatten, with = "uhlc::serialization::split")]
+///     timestamp: Timestamp,
+/// }
+///
+/// let event = Event {
+///     name: "started".into(),
+///     timestamp: Timestamp::new(NTP64(7386690599959157260), "33".parse().unwrap()),
+/// };
+/// let json = serde_json::to_string(&event).unwrap();
+/// assert_eq!(json, r#"{"name":"started","time":7386690599959157260,"id":"33"}"#);
+/// let event: Event = serde_json::from_str(&json).unwrap();
+/// assert_eq!(event.timestamp.to_string(), "7386690599959157260/33");
+/// ```
+pub mod split {
+    use crate::{Timestamp, ID, NTP64};
+    use alloc::string::String;
+    use core::str::FromStr;
+    use serde::{de, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
+
+    // An ID serialized as its hexadecimal string
+    struct HexId<'a>(&'a ID);
+
+    impl Serialize for HexId<'_> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_str(self.0)
+        }
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename = "Timestamp")]
+    struct Split {
+        time: NTP64,
+        id: String,
+    }
+
+    /// Serializes a [`Timestamp`] as its `time` and `id` fields.
+    pub fn serialize<S: Serializer>(ts: &Timestamp, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("Timestamp", 2)?;
+        s.serialize_field("time", ts.get_time())?;
+        s.serialize_field("id", &HexId(ts.get_id()))?;
+        s.end()
+    }
+
+    /// Deserializes a [`Timestamp`] from its `time` and `id` fields.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Timestamp, D::Error> {
+        let split = Split::deserialize(deserializer)?;
+        let id = ID::from_str(&split.id).map_err(de::Error::custom)?;
+        Ok(Timestamp::new(split.time, id))
+    }
+}
+
+/// Serializes a [`Timestamp`](crate::Timestamp) as a tuple of [`KEY_SIZE`](crate::KEY_SIZE) bytes
+/// (see [`Timestamp::encode_fixed()`](crate::Timestamp::encode_fixed)).
+///
+/// Unlike the default representation whose time is a variable-length integer with some formats
+/// (e.g. [postcard](https://docs.rs/postcard)), it always has the same size with such formats:
+/// e.g. 24 bytes with postcard, allowing to size the buffers at compile time.
+///
+/// # Examples
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use uhlc::Timestamp;
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Frame {
+///     #[serde(with = "uhlc::serialization::fixed")]
+///     timestamp: Timestamp,
+///     payload: [u8; 8],
+/// }
+/// ```
+pub mod fixed {
+    use crate::{Timestamp, KEY_SIZE};
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+    /// Serializes a [`Timestamp`] as a tuple of [`KEY_SIZE`] bytes.
+    pub fn serialize<S: Serializer>(ts: &Timestamp, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut buf = [0u8; KEY_SIZE];
+        ts.encode_fixed(&mut buf);
+        buf.serialize(serializer)
+    }
+
+    /// Deserializes a [`Timestamp`] from a tuple of [`KEY_SIZE`] bytes.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Timestamp, D::Error> {
+        let buf = <[u8; KEY_SIZE]>::deserialize(deserializer)?;
+        Timestamp::decode_fixed(&buf).map_err(de::Error::custom)
+    }
+}
+
+/// Serializes a [`Timestamp`](crate::Timestamp) as its time only (its [`NTP64`](crate::NTP64) as a `u64`),
+/// and deserializes it with the [`ID`](crate::ID) of its writer supplied out-of-band with [`time_only::BindId`]
+/// (see [`Timestamp::bind_id()`](crate::Timestamp::bind_id)).
+///
+/// It halves the size of the logs written by a single trusted writer, whose [`ID`](crate::ID) is known
+/// by the readers (e.g. from the identity of the connection).
+///
+/// # Examples
+/// ```
+/// use serde::de::DeserializeSeed;
+/// use uhlc::{serialization::time_only::BindId, HLC};
+///
+/// let hlc = HLC::default();
+/// let ts = hlc.new_timestamp();
+/// let mut json = Vec::new();
+/// uhlc::serialization::time_only::serialize(&ts, &mut serde_json::Serializer::new(&mut json)).unwrap();
+/// assert_eq!(json, ts.get_time().to_string().as_bytes());
+///
+/// let mut deserializer = serde_json::Deserializer::from_slice(&json);
+/// assert_eq!(BindId(*hlc.get_id()).deserialize(&mut deserializer).unwrap(), ts);
+/// ```
+pub mod time_only {
+    use crate::{Timestamp, ID, NTP64};
+    use serde::{de::DeserializeSeed, Deserialize, Deserializer, Serialize, Serializer};
+
+    /// Serializes the time of a [`Timestamp`], without its [`ID`].
+    pub fn serialize<S: Serializer>(ts: &Timestamp, serializer: S) -> Result<S::Ok, S::Error> {
+        ts.get_time().serialize(serializer)
+    }
+
+    /// A [`DeserializeSeed`] deserializing a [`Timestamp`] from its time only, bound to this [`ID`]
+    /// (see [`Timestamp::bind_id()`]).
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct BindId(pub ID);
+
+    impl<'de> DeserializeSeed<'de> for BindId {
+        type Value = Timestamp;
+
+        fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Timestamp, D::Error> {
+            let time = NTP64::deserialize(deserializer)?;
+            Ok(Timestamp::bind_id(time, self.0))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Row {
+        key: u32,
+        #[serde(flatten, with = "crate::serialization::split")]
+        timestamp: Timestamp,
+    }
+
+    #[test]
+    fn split() {
+        let hlc = HLC::default();
+        for _ in 0..100 {
+            let row = Row {
+                key: 42,
+                timestamp: hlc.new_timestamp(),
+            };
+            let json = serde_json::to_value(&row).unwrap();
+            assert_eq!(json["time"], row.timestamp.get_time().0);
+            assert_eq!(json["id"], row.timestamp.get_id().to_string());
+            assert_eq!(serde_json::from_value::<Row>(json).unwrap(), row);
+        }
+
+        let err = serde_json::from_str::<Row>(r#"{"key":1,"time":1,"id":"0"}"#).unwrap_err();
+        assert!(err.to_string().contains("Leading 0s are not valid"));
+        assert!(serde_json::from_str::<Row>(r#"{"key":1,"time":1}"#).is_err());
+    }
+
+    #[test]
+    fn time_only() {
+        use serde::de::DeserializeSeed;
+
+        let hlc = HLC::default();
+        for _ in 0..100 {
+            let ts = hlc.new_timestamp();
+            let json =
+                serialization::time_only::serialize(&ts, serde_json::value::Serializer).unwrap();
+            assert_eq!(json, ts.get_time().0);
+            let bound = serialization::time_only::BindId(*hlc.get_id())
+                .deserialize(json)
+                .unwrap();
+            assert_eq!(bound, ts);
+            assert_eq!(Timestamp::bind_id(*ts.get_time(), *ts.get_id()), ts);
+        }
+        let id = ID::rand();
+        assert!(serialization::time_only::BindId(id)
+            .deserialize(serde_json::json!("1/33"))
+            .is_err());
+    }
+
+    #[test]
+    fn fixed() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Frame(#[serde(with = "crate::serialization::fixed")] Timestamp);
+
+        let hlc = HLC::default();
+        let mut buf = [0u8; KEY_SIZE];
+        for _ in 0..100 {
+            let frame = Frame(hlc.new_timestamp());
+            frame.0.encode_fixed(&mut buf);
+            assert_eq!(Timestamp::decode_fixed(&buf), Ok(frame.0));
+
+            let json = serde_json::to_value(&frame).unwrap();
+            assert_eq!(json.as_array().unwrap().len(), KEY_SIZE);
+            assert_eq!(serde_json::from_value::<Frame>(json).unwrap(), frame);
+        }
+
+        buf[8..].fill(0);
+        assert_eq!(Timestamp::decode_fixed(&buf), Err(DecodeError::ZeroId));
+        let json = serde_json::to_string(&buf).unwrap();
+        assert!(serde_json::from_str::<Frame>(&json).is_err());
+    }
+}
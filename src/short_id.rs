@@ -0,0 +1,147 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{Timestamp, ID, NTP64};
+use core::{convert::TryFrom, fmt};
+use serde::{
+    de::{self, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+// An ID serialized as its significant le-encoded bytes only
+struct ShortId(ID);
+
+impl Serialize for ShortId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self.0.significant_bytes())
+    }
+}
+
+impl<'de> Deserialize<'de> for ShortId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ShortIdVisitor;
+
+        impl<'de> Visitor<'de> for ShortIdVisitor {
+            type Value = ShortId;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "1 to {} le-encoded bytes of a non-zero ID", ID::MAX_SIZE)
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<ShortId, E> {
+                ID::try_from(v)
+                    .map(ShortId)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Bytes(v), &self))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<ShortId, A::Error> {
+                let mut bytes = [0u8; ID::MAX_SIZE];
+                let mut size = 0;
+                while let Some(byte) = seq.next_element::<u8>()? {
+                    if size == ID::MAX_SIZE {
+                        return Err(de::Error::invalid_length(size + 1, &self));
+                    }
+                    bytes[size] = byte;
+                    size += 1;
+                }
+                self.visit_bytes(&bytes[..size])
+            }
+        }
+
+        deserializer.deserialize_bytes(ShortIdVisitor)
+    }
+}
+
+impl Timestamp {
+    /// Serialize this [`Timestamp`] in the "short ID" wire mode: as a tuple of its [`NTP64`] time
+    /// and of only the significant bytes of its le-encoded [`ID`] (i.e. [`ID::size()`] bytes),
+    /// instead of the 16 bytes of the default [`Serialize`] implementation.
+    ///
+    /// Such a [`Timestamp`] must be deserialized with [`Timestamp::deserialize_short()`].
+    /// Both can be used with serde's field attributes:
+    /// `#[serde(serialize_with = "Timestamp::serialize_short", deserialize_with = "Timestamp::deserialize_short")]`.
+    ///
+    /// ## On collisions
+    /// This encoding is lossless: only the zero padding of the [`ID`] is omitted, and it's reconstructed at deserialization.
+    /// Thus, it doesn't create any collision by itself. However, it only saves space for HLCs configured with small IDs
+    /// (see [`crate::HLCBuilder::with_id()`]), as the IDs generated by [`ID::rand()`] almost always have 16 significant bytes.
+    /// And the smaller the IDs, the more likely 2 HLCs end up with the same one if they are picked at random:
+    /// with `n` HLCs picking IDs of `b` significant bytes at random, the probability of a collision is about `n² / 2^(8b+1)`
+    /// (e.g. ~1.2% for 10,000 HLCs with 4 bytes IDs, see [`crate::id::collision_probability()`]). Since the uniqueness of the [`Timestamp`]s relies on the uniqueness
+    /// of the IDs, small IDs should rather be assigned in a coordinated way.
+    ///
+    /// # Examples
+    /// ```
+    /// use serde::{Deserialize, Serialize};
+    /// use uhlc::Timestamp;
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// struct Message {
+    ///     #[serde(
+    ///         serialize_with = "Timestamp::serialize_short",
+    ///         deserialize_with = "Timestamp::deserialize_short"
+    ///     )]
+    ///     timestamp: Timestamp,
+    ///     payload: Vec<u8>,
+    /// }
+    /// ```
+    pub fn serialize_short<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self.get_time(), ShortId(*self.get_id())).serialize(serializer)
+    }
+
+    /// Deserialize a [`Timestamp`] serialized with [`Timestamp::serialize_short()`].
+    pub fn deserialize_short<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Timestamp, D::Error> {
+        let (time, id) = <(NTP64, ShortId)>::deserialize(deserializer)?;
+        Ok(Timestamp::new(time, id.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use core::convert::TryFrom;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Message {
+        #[serde(
+            serialize_with = "Timestamp::serialize_short",
+            deserialize_with = "Timestamp::deserialize_short"
+        )]
+        timestamp: Timestamp,
+    }
+
+    #[test]
+    fn short_id_wire_mode() {
+        let ts = Timestamp::new(NTP64(42), ID::try_from([0x01, 0x02]).unwrap());
+        let json = serde_json::to_string(&Message { timestamp: ts }).unwrap();
+        assert_eq!(json, r#"{"timestamp":[42,[1,2]]}"#);
+        let msg: Message = serde_json::from_str(&json).unwrap();
+        assert_eq!(msg.timestamp, ts);
+
+        for _ in 0..100 {
+            let timestamp = Timestamp::new(NTP64(42), ID::rand());
+            let json = serde_json::to_string(&Message { timestamp }).unwrap();
+            assert_eq!(
+                serde_json::from_str::<Message>(&json).unwrap().timestamp,
+                timestamp
+            );
+        }
+
+        assert!(serde_json::from_str::<Message>(r#"{"timestamp":[42,[]]}"#).is_err());
+        assert!(serde_json::from_str::<Message>(r#"{"timestamp":[42,[0,0]]}"#).is_err());
+        assert!(serde_json::from_str::<Message>(
+            r#"{"timestamp":[42,[1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16,17]]}"#
+        )
+        .is_err());
+    }
+}
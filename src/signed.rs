@@ -0,0 +1,123 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{Timestamp, HLC, KEY_SIZE};
+use core::fmt;
+use ed25519_dalek::{Signature, SignatureError, Signer, SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+// Context prefixed to the signed message, to prevent the signature from being valid for another purpose
+const SIGNING_CONTEXT: &[u8] = b"uhlc-signed-timestamp-v1";
+
+/// A [`Timestamp`] signed with an ed25519 key.
+///
+/// The signature covers the canonical encoding of the [`Timestamp`] (see [`Timestamp::to_key()`]),
+/// prefixed with the `"uhlc-signed-timestamp-v1"` context string.
+///
+/// # Examples
+///
+/// ```
+/// use ed25519_dalek::SigningKey;
+/// use uhlc::HLC;
+///
+/// let key = SigningKey::from_bytes(&[0x2a; 32]);
+/// let hlc = HLC::default();
+/// let signed_ts = hlc.new_signed_timestamp(&key);
+/// assert!(signed_ts.verify(&key.verifying_key()).is_ok());
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SignedTimestamp {
+    timestamp: Timestamp,
+    signature: Signature,
+}
+
+impl SignedTimestamp {
+    /// Sign a [`Timestamp`] with `key`.
+    pub fn sign(timestamp: Timestamp, key: &SigningKey) -> SignedTimestamp {
+        let signature = key.sign(&signed_message(&timestamp));
+        SignedTimestamp {
+            timestamp,
+            signature,
+        }
+    }
+
+    /// Returns the signed [`Timestamp`].
+    ///
+    /// **NOTE: the signature is not verified by this operation.** Use [`SignedTimestamp::verify()`]
+    /// before trusting the returned [`Timestamp`].
+    #[inline]
+    pub fn get_timestamp(&self) -> &Timestamp {
+        &self.timestamp
+    }
+
+    /// Returns the signature.
+    #[inline]
+    pub fn get_signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    /// Verify the signature with the public key of the signer.
+    ///
+    /// A [`SignatureError`] is returned if the [`Timestamp`] was not signed with the private key of `key`.
+    pub fn verify(&self, key: &VerifyingKey) -> Result<(), SignatureError> {
+        key.verify_strict(&signed_message(&self.timestamp), &self.signature)
+    }
+}
+
+fn signed_message(timestamp: &Timestamp) -> [u8; SIGNING_CONTEXT.len() + KEY_SIZE] {
+    let mut msg = [0u8; SIGNING_CONTEXT.len() + KEY_SIZE];
+    msg[..SIGNING_CONTEXT.len()].copy_from_slice(SIGNING_CONTEXT);
+    msg[SIGNING_CONTEXT.len()..].copy_from_slice(&timestamp.to_key());
+    msg
+}
+
+impl fmt::Display for SignedTimestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.timestamp, f)
+    }
+}
+
+impl fmt::Debug for SignedTimestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}/{:?}", self.timestamp, self.signature)
+    }
+}
+
+impl HLC {
+    /// Generate a new [`Timestamp`] (see [`HLC::new_timestamp()`]) and sign it with `key`.
+    pub fn new_signed_timestamp(&self, key: &SigningKey) -> SignedTimestamp {
+        SignedTimestamp::sign(self.new_timestamp(), key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use ed25519_dalek::SigningKey;
+
+    #[test]
+    fn signed_timestamp() {
+        let key = SigningKey::from_bytes(&[0x01; 32]);
+        let other_key = SigningKey::from_bytes(&[0x02; 32]);
+        let hlc = HLC::default();
+
+        let signed_ts = hlc.new_signed_timestamp(&key);
+        assert!(signed_ts.verify(&key.verifying_key()).is_ok());
+        assert!(signed_ts.verify(&other_key.verifying_key()).is_err());
+
+        // a forged timestamp re-using a valid signature is rejected
+        let forged_time = *signed_ts.get_timestamp().get_time() + 1;
+        let forged_ts = SignedTimestamp {
+            timestamp: Timestamp::new(forged_time, *hlc.get_id()),
+            signature: *signed_ts.get_signature(),
+        };
+        assert!(forged_ts.verify(&key.verifying_key()).is_err());
+    }
+}
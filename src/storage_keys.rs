@@ -0,0 +1,495 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{SizeError, Timestamp, ID, NTP64};
+use alloc::vec::Vec;
+use core::{
+    convert::{TryFrom, TryInto},
+    fmt,
+};
+
+/// The size in bytes of a [`Timestamp`] key (see [`Timestamp::to_key()`]).
+pub const KEY_SIZE: usize = 8 + ID::MAX_SIZE;
+// The size in bytes of the time part of a key
+const KEY_TIME_SIZE: usize = 8;
+
+impl Timestamp {
+    /// Encode this [`Timestamp`] as a key for byte-ordered storages (e.g. embedded key-value stores).
+    ///
+    /// The key is made of the [`NTP64`] time as big endian (8 bytes), followed by the le-encoded [`ID`] (16 bytes).
+    /// Comparing 2 keys byte per byte gives the same ordering than comparing the 2 [`Timestamp`]s.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use uhlc::{Timestamp, HLC};
+    ///
+    /// let hlc = HLC::default();
+    /// let ts1 = hlc.new_timestamp();
+    /// let ts2 = hlc.new_timestamp();
+    /// assert!(ts1.to_key() < ts2.to_key());
+    /// assert_eq!(Timestamp::from_key(&ts1.to_key()).unwrap(), ts1);
+    /// ```
+    pub fn to_key(&self) -> [u8; KEY_SIZE] {
+        let mut key = [0u8; KEY_SIZE];
+        key[..KEY_TIME_SIZE].copy_from_slice(&self.get_time().as_u64().to_be_bytes());
+        key[KEY_TIME_SIZE..].copy_from_slice(&self.get_id().to_le_bytes());
+        key
+    }
+
+    /// Encode this [`Timestamp`] as a key like [`Timestamp::to_key()`], but truncating the
+    /// zero padding of the le-encoded [`ID`] (i.e. keeping only [`ID::size()`] bytes).
+    ///
+    /// The truncated keys preserve the ordering of the [`Timestamp`]s, and can be decoded with [`Timestamp::from_key()`].
+    /// They are shorter when the HLCs identifiers are small.
+    pub fn to_truncated_key(&self) -> Vec<u8> {
+        let key = self.to_key();
+        key[..KEY_TIME_SIZE + self.get_id().size()].to_vec()
+    }
+
+    /// Decode a [`Timestamp`] from a key encoded with [`Timestamp::to_key()`] or [`Timestamp::to_truncated_key()`].
+    pub fn from_key(key: &[u8]) -> Result<Timestamp, DecodeError> {
+        if key.len() <= KEY_TIME_SIZE || key.len() > KEY_SIZE {
+            return Err(DecodeError::InvalidLength(key.len()));
+        }
+        let (time, id) = key.split_at(KEY_TIME_SIZE);
+        // unwrap: time is exactly KEY_TIME_SIZE bytes
+        let time = NTP64(u64::from_be_bytes(time.try_into().unwrap()));
+        let id = ID::try_from(id).map_err(|_| DecodeError::ZeroId)?;
+        Ok(Timestamp::new(time, id))
+    }
+
+    /// Encode this [`Timestamp`] into a fixed-size buffer of [`KEY_SIZE`] bytes, with the layout of [`Timestamp::to_key()`].
+    ///
+    /// This encoding never fails, panics or allocates: e.g. for `no_std` radio protocols whose buffers are sized
+    /// at compile time. It's decoded with [`Timestamp::decode_fixed()`].
+    /// To serialize a [`Timestamp`] with a fixed size with serde (e.g. with postcard),
+    /// see [`crate::serialization::fixed`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::TryInto;
+    /// use uhlc::{Timestamp, HLC, KEY_SIZE};
+    ///
+    /// let ts = HLC::default().new_timestamp();
+    /// let mut frame = [0u8; 2 + KEY_SIZE];
+    /// frame[0] = 0xaa; // some header
+    /// let (_, payload) = frame.split_at_mut(2);
+    /// ts.encode_fixed(payload.try_into().unwrap());
+    /// assert_eq!(Timestamp::decode_fixed(frame[2..].try_into().unwrap()), Ok(ts));
+    /// ```
+    #[inline]
+    pub fn encode_fixed(&self, buf: &mut [u8; KEY_SIZE]) {
+        *buf = self.to_key();
+    }
+
+    /// Decode a [`Timestamp`] encoded with [`Timestamp::encode_fixed()`] (or [`Timestamp::to_key()`]), without panicking.
+    ///
+    /// Returns [`DecodeError::ZeroId`] if the [`ID`] part is all zeros.
+    pub const fn decode_fixed(buf: &[u8; KEY_SIZE]) -> Result<Timestamp, DecodeError> {
+        let mut time = [0u8; KEY_TIME_SIZE];
+        let mut id = [0u8; ID::MAX_SIZE];
+        let mut i = 0;
+        while i < KEY_SIZE {
+            if i < KEY_TIME_SIZE {
+                time[i] = buf[i];
+            } else {
+                id[i - KEY_TIME_SIZE] = buf[i];
+            }
+            i += 1;
+        }
+        match ID::from_le_bytes_checked(id) {
+            Some(id) => Ok(Timestamp::new(NTP64(u64::from_be_bytes(time)), id)),
+            None => Err(DecodeError::ZeroId),
+        }
+    }
+
+    /// Encode this [`Timestamp`] as a pair of `i64`, e.g. to store it in 2 `INTEGER` columns of SQLite:
+    /// the [`NTP64`] time, then the [`ID`].
+    ///
+    /// Comparing 2 pairs (first the time, then the ID) gives the same ordering than comparing the 2 [`Timestamp`]s.
+    /// To preserve this ordering with signed integers, the values are shifted by `i64::MIN`:
+    /// e.g. the time 0 is encoded as `i64::MIN`.
+    ///
+    /// Only the [`ID`]s of 8 bytes at most fit in an `i64`: a [`SizeError`] is returned for a longer [`ID`]
+    /// (see [`ID::size()`]).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::TryFrom;
+    /// use uhlc::{HLCBuilder, Timestamp, ID};
+    ///
+    /// let hlc = HLCBuilder::new().with_id(ID::try_from(42u64).unwrap()).build();
+    /// let (ts1, ts2) = (hlc.new_timestamp(), hlc.new_timestamp());
+    /// let (pair1, pair2) = (ts1.to_i64_pair().unwrap(), ts2.to_i64_pair().unwrap());
+    /// assert!(pair1 < pair2);
+    /// assert_eq!(Timestamp::from_i64_pair(pair1.0, pair1.1).unwrap(), ts1);
+    ///
+    /// let long_id = Timestamp::new(*ts1.get_time(), ID::MAX);
+    /// assert!(long_id.to_i64_pair().is_err());
+    /// ```
+    pub fn to_i64_pair(&self) -> Result<(i64, i64), SizeError> {
+        let size = self.get_id().size();
+        if size > I64_ID_SIZE {
+            return Err(SizeError(size));
+        }
+        // the IDs are ordered by their le-encoded bytes, so read them as big endian to get the same ordering
+        let mut id = [0u8; I64_ID_SIZE];
+        id.copy_from_slice(&self.get_id().to_le_bytes()[..I64_ID_SIZE]);
+        Ok((
+            to_ordered_i64(self.get_time().as_u64()),
+            to_ordered_i64(u64::from_be_bytes(id)),
+        ))
+    }
+
+    /// Decode a [`Timestamp`] from a pair of `i64` encoded with [`Timestamp::to_i64_pair()`].
+    ///
+    /// Returns [`DecodeError::ZeroId`] if the encoded [`ID`] is zero.
+    pub fn from_i64_pair(time: i64, id: i64) -> Result<Timestamp, DecodeError> {
+        let time = NTP64(from_ordered_i64(time));
+        let id =
+            ID::from_bytes(&from_ordered_i64(id).to_be_bytes()).map_err(|_| DecodeError::ZeroId)?;
+        Ok(Timestamp::new(time, id))
+    }
+}
+
+impl Timestamp {
+    /// Fold this [`Timestamp`] into a single `u64` ordering key, for storages whose key space is a single `u64`:
+    /// its [`NTP64`] time, with the lowest `id_bits` bits replaced by the lowest bits of the stable hash of its [`ID`]
+    /// (see [`ID::stable_hash64()`]). With `id_bits` at 0, the key is the time (see [`Timestamp::as_fencing_token()`]).
+    ///
+    /// The keys are ordered as the times of the [`Timestamp`]s, at the precision of the bits that are kept:
+    /// if a [`Timestamp`] is lower than another one, its key is lower or equal. Beware of the collisions:
+    ///  * with `id_bits` at 0, the keys of the [`Timestamp`]s of different [`crate::HLC`]s with the same time are equal,
+    ///    while the keys of the [`Timestamp`]s of a same [`crate::HLC`] are unique;
+    ///  * with `id_bits` above 0, the keys of the [`Timestamp`]s of different [`crate::HLC`]s with the same kept bits
+    ///    only differ by the hash bits (and are equal if they collide), while the keys of the [`Timestamp`]s of a same
+    ///    [`crate::HLC`] are equal if they only differ by the replaced bits: up to [`crate::CSIZE`] bits, those are
+    ///    the logical counter, beyond, the physical time is truncated as well (e.g. 8 bits is ~60ns).
+    ///
+    /// The time is decoded back (without the replaced bits) with [`Timestamp::from_ordering_key_u64()`].
+    ///
+    /// # Panics
+    ///
+    /// If `id_bits` is above 32, i.e. exceeds the fraction of second part of the time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use uhlc::{Timestamp, HLC};
+    ///
+    /// let hlc = HLC::default();
+    /// let (ts1, ts2) = (hlc.new_timestamp(), hlc.new_timestamp());
+    /// assert!(ts1.to_ordering_key_u64(0) < ts2.to_ordering_key_u64(0));
+    /// assert_eq!(Timestamp::from_ordering_key_u64(ts1.to_ordering_key_u64(0), *hlc.get_id()), ts1);
+    ///
+    /// // the counter bits are replaced by the ID hash
+    /// assert!(ts1.to_ordering_key_u64(uhlc::CSIZE.into()) <= ts2.to_ordering_key_u64(uhlc::CSIZE.into()));
+    /// ```
+    pub fn to_ordering_key_u64(&self, id_bits: u32) -> u64 {
+        assert!(
+            id_bits <= 32,
+            "Can't replace {} bits of the time of a Timestamp by the hash of its ID: 32 bits at most",
+            id_bits
+        );
+        let mask = (1u64 << id_bits) - 1;
+        (self.get_time().as_u64() & !mask) | (self.get_id().stable_hash64() & mask)
+    }
+
+    /// Create a [`Timestamp`] from an ordering key returned by [`Timestamp::to_ordering_key_u64()`],
+    /// with the [`ID`] of the [`crate::HLC`] that issued it.
+    ///
+    /// If the key was built with `id_bits` above 0, the time of the [`Timestamp`] isn't the original one:
+    /// its lowest bits are the hash of the [`ID`], but it's ordered as the key.
+    #[inline]
+    pub const fn from_ordering_key_u64(key: u64, id: ID) -> Timestamp {
+        Timestamp::new(NTP64(key), id)
+    }
+}
+
+impl Timestamp {
+    /// The latest version of the binary encoding of a [`Timestamp`] (see [`Timestamp::encode_v1()`]).
+    pub const ENCODING_VERSION: u8 = 1;
+
+    /// Encode this [`Timestamp`] with the version 1 of the versioned binary encoding: a leading version byte (`1`),
+    /// followed by its truncated key (see [`Timestamp::to_truncated_key()`]).
+    ///
+    /// The leading version byte allows [`Timestamp::decode()`] to keep decoding the data encoded with the
+    /// previous versions if the layout changes (e.g. with a wider counter), instead of silently mis-parsing it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use uhlc::{Timestamp, HLC};
+    ///
+    /// let ts = HLC::default().new_timestamp();
+    /// let bytes = ts.encode_v1();
+    /// assert_eq!(bytes[0], 1);
+    /// assert_eq!(Timestamp::decode(&bytes).unwrap(), ts);
+    /// ```
+    pub fn encode_v1(&self) -> Vec<u8> {
+        let key = self.to_key();
+        let mut bytes = Vec::with_capacity(1 + KEY_TIME_SIZE + self.get_id().size());
+        bytes.push(1);
+        bytes.extend_from_slice(&key[..KEY_TIME_SIZE + self.get_id().size()]);
+        bytes
+    }
+
+    /// Decode a [`Timestamp`] encoded with the versioned binary encoding (e.g. with [`Timestamp::encode_v1()`]),
+    /// dispatching on its leading version byte.
+    ///
+    /// Returns [`DecodeError::UnsupportedVersion`] if the version is unknown.
+    pub fn decode(bytes: &[u8]) -> Result<Timestamp, DecodeError> {
+        match bytes.split_first() {
+            Some((1, key)) => Timestamp::from_key(key).map_err(|e| match e {
+                DecodeError::InvalidLength(len) => DecodeError::InvalidLength(len + 1),
+                e => e,
+            }),
+            Some((version, _)) => Err(DecodeError::UnsupportedVersion(*version)),
+            None => Err(DecodeError::InvalidLength(0)),
+        }
+    }
+}
+
+// The maximum size of an ID encoded as an i64
+const I64_ID_SIZE: usize = 8;
+
+// Map a u64 to an i64 with the same ordering
+const fn to_ordered_i64(value: u64) -> i64 {
+    (value ^ (1 << 63)) as i64
+}
+
+const fn from_ordered_i64(value: i64) -> u64 {
+    (value as u64) ^ (1 << 63)
+}
+
+/// An error returned when decoding a [`Timestamp`] from bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DecodeError {
+    /// The bytes don't have a valid length.
+    InvalidLength(usize),
+    /// The encoded [`ID`] is zero.
+    ZeroId,
+    /// The version of the encoding is unknown (see [`Timestamp::decode()`]).
+    UnsupportedVersion(u8),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::InvalidLength(len) => {
+                write!(f, "Invalid length for an encoded Timestamp: {len} bytes")
+            }
+            DecodeError::ZeroId => write!(f, "Invalid zero ID in an encoded Timestamp"),
+            DecodeError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported version {version} of an encoded Timestamp")
+            }
+        }
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+#[cfg(feature = "redb")]
+mod redb_impl {
+    use super::KEY_SIZE;
+    use crate::Timestamp;
+    use core::cmp::Ordering;
+    use redb::{Key, TypeName, Value};
+
+    /// A [`Timestamp`] is stored in redb as its [`Timestamp::to_key()`] encoding.
+    impl Value for Timestamp {
+        type SelfType<'a> = Timestamp;
+        type AsBytes<'a> = [u8; KEY_SIZE];
+
+        fn fixed_width() -> Option<usize> {
+            Some(KEY_SIZE)
+        }
+
+        fn from_bytes<'a>(data: &'a [u8]) -> Timestamp
+        where
+            Self: 'a,
+        {
+            Timestamp::from_key(data).expect("Invalid Timestamp stored in redb")
+        }
+
+        fn as_bytes<'a, 'b: 'a>(value: &'a Timestamp) -> [u8; KEY_SIZE]
+        where
+            Self: 'b,
+        {
+            value.to_key()
+        }
+
+        fn type_name() -> TypeName {
+            TypeName::new("uhlc::Timestamp")
+        }
+    }
+
+    impl Key for Timestamp {
+        fn compare(data1: &[u8], data2: &[u8]) -> Ordering {
+            data1.cmp(data2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use core::convert::TryFrom;
+
+    #[test]
+    fn ordering_key_u64() {
+        let (id1, id2) = (ID::try_from(1u64).unwrap(), ID::try_from(2u64).unwrap());
+        let time = NTP64::new(1_000_000, 0x1234_5678);
+        let ts1 = Timestamp::new(time, id1);
+        assert_eq!(ts1.to_ordering_key_u64(0), time.0);
+        let key = ts1.to_ordering_key_u64(8);
+        assert_eq!(key >> 8, time.0 >> 8);
+        assert_eq!(key & 0xff, id1.stable_hash64() & 0xff);
+        // the same time from another ID only differs by the hash bits
+        let key2 = Timestamp::new(time, id2).to_ordering_key_u64(8);
+        assert_eq!(key2 >> 8, key >> 8);
+        assert_ne!(key2, key);
+        assert_eq!(
+            Timestamp::from_ordering_key_u64(key, id1).get_time().0 >> 8,
+            time.0 >> 8
+        );
+
+        // ordered as the Timestamps of a same HLC
+        let hlc = HLC::default();
+        let stamps: Vec<Timestamp> = (0..100).map(|_| hlc.new_timestamp()).collect();
+        for bits in [0, CSIZE.into(), 16, 32] {
+            let keys: Vec<u64> = stamps
+                .iter()
+                .map(|ts| ts.to_ordering_key_u64(bits))
+                .collect();
+            assert!(keys.windows(2).all(|pair| pair[0] <= pair[1]));
+        }
+    }
+
+    #[test]
+    fn key_ordering() {
+        let hlc = HLC::default();
+        let time = *hlc.new_timestamp().get_time();
+        let stamps = [
+            Timestamp::new(time, ID::try_from([0x01]).unwrap()),
+            Timestamp::new(time, ID::try_from([0x01, 0x01]).unwrap()),
+            Timestamp::new(time, ID::try_from([0x02]).unwrap()),
+            Timestamp::new(time + 1, ID::try_from([0x01]).unwrap()),
+            Timestamp::new(time + 1, ID::rand()),
+        ];
+        for a in stamps.iter() {
+            assert_eq!(Timestamp::from_key(&a.to_key()).unwrap(), *a);
+            assert_eq!(Timestamp::from_key(&a.to_truncated_key()).unwrap(), *a);
+            for b in stamps.iter() {
+                assert_eq!(a.to_key().cmp(&b.to_key()), a.cmp(b));
+                assert_eq!(a.to_truncated_key().cmp(&b.to_truncated_key()), a.cmp(b));
+            }
+        }
+
+        assert_eq!(
+            Timestamp::from_key(&[0u8; 8]),
+            Err(DecodeError::InvalidLength(8))
+        );
+        assert_eq!(
+            Timestamp::from_key(&[0u8; KEY_SIZE + 1]),
+            Err(DecodeError::InvalidLength(KEY_SIZE + 1))
+        );
+        assert_eq!(
+            Timestamp::from_key(&[0u8; KEY_SIZE]),
+            Err(DecodeError::ZeroId)
+        );
+    }
+
+    #[test]
+    fn versioned_encoding() {
+        let ts = Timestamp::new(NTP64(42), ID::try_from([0x01, 0x02]).unwrap());
+        let bytes = ts.encode_v1();
+        assert_eq!(bytes, [1, 0, 0, 0, 0, 0, 0, 0, 42, 0x01, 0x02]);
+        assert_eq!(Timestamp::decode(&bytes), Ok(ts));
+        let ts = Timestamp::new(NTP64(u64::MAX), ID::MAX);
+        assert_eq!(Timestamp::decode(&ts.encode_v1()), Ok(ts));
+
+        assert_eq!(Timestamp::decode(&[]), Err(DecodeError::InvalidLength(0)));
+        assert_eq!(
+            Timestamp::decode(&bytes[..9]),
+            Err(DecodeError::InvalidLength(9))
+        );
+        let mut future = bytes.clone();
+        future[0] = 2;
+        assert_eq!(
+            Timestamp::decode(&future),
+            Err(DecodeError::UnsupportedVersion(2))
+        );
+    }
+
+    #[test]
+    fn i64_pair_ordering() {
+        let stamps = [
+            Timestamp::new(NTP64(0), ID::try_from([0x01]).unwrap()),
+            Timestamp::new(NTP64(0), ID::try_from([0x01, 0x01]).unwrap()),
+            Timestamp::new(NTP64(0), ID::try_from([0x02]).unwrap()),
+            Timestamp::new(NTP64(1 << 63), ID::try_from(u64::MAX).unwrap()),
+            Timestamp::new(NTP64(u64::MAX), ID::try_from([0x01]).unwrap()),
+        ];
+        for a in stamps.iter() {
+            let (time, id) = a.to_i64_pair().unwrap();
+            assert_eq!(Timestamp::from_i64_pair(time, id).unwrap(), *a);
+            for b in stamps.iter() {
+                assert_eq!(
+                    a.to_i64_pair().unwrap().cmp(&b.to_i64_pair().unwrap()),
+                    a.cmp(b)
+                );
+            }
+        }
+        assert_eq!(stamps[0].to_i64_pair().unwrap().0, i64::MIN);
+        assert_eq!(
+            Timestamp::from_i64_pair(0, i64::MIN),
+            Err(DecodeError::ZeroId)
+        );
+        let long_id = Timestamp::new(NTP64(0), ID::try_from(1u128 << 64).unwrap());
+        assert_eq!(long_id.to_i64_pair(), Err(SizeError(9)));
+    }
+
+    #[cfg(feature = "redb")]
+    #[test]
+    fn redb_table() {
+        use redb::{backends::InMemoryBackend, Database, ReadableDatabase, TableDefinition};
+
+        const TABLE: TableDefinition<Timestamp, u64> = TableDefinition::new("stamps");
+        let db = Database::builder()
+            .create_with_backend(InMemoryBackend::new())
+            .unwrap();
+        let hlc = HLC::default();
+        let stamps: Vec<Timestamp> = (0..10).map(|_| hlc.new_timestamp()).collect();
+
+        let tx = db.begin_write().unwrap();
+        {
+            let mut table = tx.open_table(TABLE).unwrap();
+            // insert in reverse order to check the table's ordering
+            for (i, ts) in stamps.iter().enumerate().rev() {
+                table.insert(ts, i as u64).unwrap();
+            }
+        }
+        tx.commit().unwrap();
+
+        let tx = db.begin_read().unwrap();
+        let table = tx.open_table(TABLE).unwrap();
+        let stored: Vec<Timestamp> = table
+            .range(stamps[2]..stamps[7])
+            .unwrap()
+            .map(|e| e.unwrap().0.value())
+            .collect();
+        assert_eq!(stored, &stamps[2..7]);
+    }
+}
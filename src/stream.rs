@@ -0,0 +1,86 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{Timestamp, HLC};
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use futures_core::Stream;
+
+/// An extension trait for [`Stream`]s, attaching a new [`Timestamp`] to each of their items.
+pub trait StampExt: Stream + Sized {
+    /// Returns a [`Stream`] yielding each item of this stream along with a new [`Timestamp`]
+    /// generated by `hlc` when the item is polled (see [`HLC::new_timestamp()`]).
+    ///
+    /// As the [`Timestamp`]s are generated by the same [`HLC`], they are unique and increasing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::{executor::block_on, stream, StreamExt};
+    /// use uhlc::{StampExt, HLC};
+    ///
+    /// let hlc = HLC::default();
+    /// let stamped: Vec<_> = block_on(stream::iter(["a", "b"]).stamped(&hlc).collect());
+    /// assert!(stamped[0].0 < stamped[1].0);
+    /// ```
+    fn stamped(self, hlc: &HLC) -> Stamped<Self> {
+        Stamped {
+            stream: self,
+            hlc: hlc.clone(),
+        }
+    }
+}
+
+impl<S: Stream> StampExt for S {}
+
+/// The [`Stream`] returned by [`StampExt::stamped()`].
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct Stamped<S> {
+    stream: S,
+    hlc: HLC,
+}
+
+impl<S: Stream> Stream for Stamped<S> {
+    type Item = (Timestamp, S::Item);
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // SAFETY: `stream` is structurally pinned: it's never moved out of a pinned `Stamped`,
+        // and `Stamped` implements neither `Drop` nor `Unpin` manually.
+        let this = unsafe { self.get_unchecked_mut() };
+        let stream = unsafe { Pin::new_unchecked(&mut this.stream) };
+        stream
+            .poll_next(cx)
+            .map(|item| item.map(|item| (this.hlc.new_timestamp(), item)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use futures::{executor::block_on, stream, StreamExt};
+
+    #[test]
+    fn stamped_stream() {
+        let hlc = HLC::default();
+        let before = hlc.new_timestamp();
+        let stamped: Vec<_> = block_on(stream::iter(0..100).stamped(&hlc).collect());
+        assert_eq!(stamped.len(), 100);
+        assert!(stamped[0].0 > before);
+        for (i, pair) in stamped.windows(2).enumerate() {
+            assert_eq!(pair[0].1, i);
+            assert!(pair[1].0 > pair[0].0);
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{Timestamp, ID, NTP64};
+
+/// A [`Timestamp`] is a `slog` value rendered with its [`core::fmt::Display`] representation.
+///
+/// # Examples
+/// ```
+/// use uhlc::HLC;
+///
+/// let hlc = HLC::default();
+/// let logger = slog::Logger::root(slog::Discard, slog::o!("hlc" => *hlc.get_id()));
+/// slog::info!(logger, "message sent"; "timestamp" => hlc.new_timestamp());
+/// ```
+#[cfg(feature = "slog")]
+impl slog::Value for Timestamp {
+    fn serialize(
+        &self,
+        _record: &slog::Record,
+        key: slog::Key,
+        serializer: &mut dyn slog::Serializer,
+    ) -> slog::Result {
+        serializer.emit_arguments(key, &format_args!("{self}"))
+    }
+}
+
+/// An [`ID`] is a `slog` value rendered as its hexadecimal representation.
+#[cfg(feature = "slog")]
+impl slog::Value for ID {
+    fn serialize(
+        &self,
+        _record: &slog::Record,
+        key: slog::Key,
+        serializer: &mut dyn slog::Serializer,
+    ) -> slog::Result {
+        serializer.emit_arguments(key, &format_args!("{self}"))
+    }
+}
+
+/// A [`NTP64`] is a `slog` value emitted as a `u64`.
+#[cfg(feature = "slog")]
+impl slog::Value for NTP64 {
+    fn serialize(
+        &self,
+        _record: &slog::Record,
+        key: slog::Key,
+        serializer: &mut dyn slog::Serializer,
+    ) -> slog::Result {
+        serializer.emit_u64(key, self.as_u64())
+    }
+}
+
+/// A [`Timestamp`] is a `log` key-value, lazily rendered with its [`core::fmt::Display`] representation.
+///
+/// # Examples
+/// ```
+/// use uhlc::HLC;
+///
+/// let hlc = HLC::default();
+/// log::info!(timestamp = hlc.new_timestamp(); "message sent");
+/// ```
+#[cfg(feature = "log-kv")]
+impl log::kv::ToValue for Timestamp {
+    fn to_value(&self) -> log::kv::Value<'_> {
+        log::kv::Value::from_display(self)
+    }
+}
+
+/// An [`ID`] is a `log` key-value, lazily rendered as its hexadecimal representation.
+#[cfg(feature = "log-kv")]
+impl log::kv::ToValue for ID {
+    fn to_value(&self) -> log::kv::Value<'_> {
+        log::kv::Value::from_display(self)
+    }
+}
+
+/// A [`NTP64`] is a `log` key-value captured as a `u64`.
+#[cfg(feature = "log-kv")]
+impl log::kv::ToValue for NTP64 {
+    fn to_value(&self) -> log::kv::Value<'_> {
+        log::kv::Value::from(self.as_u64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use alloc::string::ToString;
+
+    #[cfg(feature = "slog")]
+    #[test]
+    fn slog_values() {
+        use alloc::{format, string::String, vec::Vec};
+        use core::fmt;
+
+        // Captures the serialized key-values as strings
+        #[derive(Default)]
+        struct Capture(Vec<(String, String)>);
+
+        impl slog::Serializer for Capture {
+            fn emit_arguments(&mut self, key: slog::Key, val: &fmt::Arguments) -> slog::Result {
+                self.0.push((key.to_string(), val.to_string()));
+                Ok(())
+            }
+
+            fn emit_u64(&mut self, key: slog::Key, val: u64) -> slog::Result {
+                self.0.push((key.to_string(), format!("u64:{val}")));
+                Ok(())
+            }
+        }
+
+        let hlc = HLC::default();
+        let ts = hlc.new_timestamp();
+        let mut capture = Capture::default();
+        let record = slog::record_static!(slog::Level::Info, "");
+        let kv = slog::o!("ts" => ts, "id" => *ts.get_id(), "time" => *ts.get_time());
+        slog::KV::serialize(
+            &kv,
+            &slog::Record::new(&record, &format_args!(""), slog::b!()),
+            &mut capture,
+        )
+        .unwrap();
+        let mut values = capture.0;
+        values.sort();
+        assert_eq!(
+            values,
+            [
+                ("id".to_string(), ts.get_id().to_string()),
+                ("time".to_string(), format!("u64:{}", ts.get_time().0)),
+                ("ts".to_string(), ts.to_string()),
+            ]
+        );
+    }
+
+    #[cfg(feature = "log-kv")]
+    #[test]
+    fn log_kv_values() {
+        use log::kv::ToValue;
+
+        let ts = HLC::default().new_timestamp();
+        assert_eq!(ts.to_value().to_string(), ts.to_string());
+        assert_eq!(ts.get_id().to_value().to_string(), ts.get_id().to_string());
+        assert_eq!(ts.get_time().to_value().to_u64(), Some(ts.get_time().0));
+    }
+}
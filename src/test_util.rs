@@ -0,0 +1,97 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+//! Internals of the [`HLC`] exposed for deterministic tests (with the `test-util` feature).
+//! They must not be used in production code.
+use super::{HLCBuilder, LastTime, Timestamp, UpdateError, HLC, NTP64};
+use alloc::sync::Arc;
+
+impl HLCBuilder {
+    /// Preset the latest time of the HLC to be created, as if it had already issued
+    /// or received a [`Timestamp`] with this time.
+    ///
+    /// *Only available with the `test-util` feature.*
+    pub fn with_last_time(mut self, time: NTP64) -> HLCBuilder {
+        self.hlc.last_time = Arc::new(LastTime::new(time));
+        self
+    }
+}
+
+impl HLC {
+    /// Generate a new [`Timestamp`] as [`HLC::new_timestamp()`] does, but with `now` as the reading
+    /// of the physical clock instead of calling it. This allows to drive the clock step by step.
+    ///
+    /// *Only available with the `test-util` feature.*
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use uhlc::{HLCBuilder, NTP64};
+    ///
+    /// let hlc = HLCBuilder::new().with_last_time(NTP64(1 << 32)).build();
+    /// assert_eq!(*hlc.tick_physical(NTP64(0)).get_time(), NTP64((1 << 32) + 1));
+    /// assert_eq!(*hlc.tick_physical(NTP64(2 << 32)).get_time(), NTP64(2 << 32));
+    /// assert_eq!(hlc.get_last_time(), NTP64(2 << 32));
+    /// ```
+    pub fn tick_physical(&self, now: NTP64) -> Timestamp {
+        self.new_timestamp_at(now)
+    }
+
+    /// Update this [`HLC`] with a [`Timestamp`] as [`HLC::update_with_timestamp()`] does, but with `now`
+    /// as the reading of the physical clock instead of calling it.
+    ///
+    /// *Only available with the `test-util` feature.*
+    pub fn update_with_timestamp_at_physical(
+        &self,
+        timestamp: &Timestamp,
+        now: NTP64,
+    ) -> Result<(), UpdateError> {
+        self.update_with_timestamp_at(timestamp, now)
+    }
+
+    /// Returns the latest time of this [`HLC`] (i.e. its frontier): the time of the latest
+    /// [`Timestamp`] it issued, or of the latest accepted update if more recent.
+    ///
+    /// *Only available with the `test-util` feature.*
+    pub fn get_last_time(&self) -> NTP64 {
+        self.last_time.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use core::time::Duration;
+
+    #[test]
+    fn deterministic_steps() {
+        let sec = |s: u64| NTP64::from(Duration::from_secs(s)).0 & LMASK;
+        let hlc = HLCBuilder::new()
+            .with_clock(zero_clock)
+            .with_max_delta(Duration::from_secs(1))
+            .build();
+        assert_eq!(hlc.get_last_time(), NTP64(0));
+        assert_eq!(
+            *hlc.tick_physical(NTP64(sec(10))).get_time(),
+            NTP64(sec(10))
+        );
+
+        // a peer ahead within the delta of the driven physical time is accepted
+        let peer = Timestamp::new(NTP64(sec(11)), ID::rand());
+        assert!(hlc
+            .update_with_timestamp_at_physical(&peer, NTP64(sec(10)))
+            .is_ok());
+        assert_eq!(hlc.get_last_time(), NTP64(sec(11) + 1));
+        // but rejected against the real (zero) clock
+        let peer = Timestamp::new(NTP64(sec(12)), ID::rand());
+        assert!(hlc.update_with_timestamp(&peer).is_err());
+        assert_eq!(hlc.get_last_time(), NTP64(sec(11) + 1));
+    }
+}
@@ -0,0 +1,128 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+//! The assertions check slices of [`Timestamp`]s in the order they were observed (e.g. at the end of a pipeline),
+//! and panic with a message locating the first violation.
+//!
+//! # Examples
+//! ```
+//! use std::time::Duration;
+//! use uhlc::{testkit, HLC};
+//!
+//! let (hlc1, hlc2) = (HLC::default(), HLC::default());
+//! let stamps = [hlc1.new_timestamp(), hlc2.new_timestamp(), hlc1.new_timestamp()];
+//! testkit::assert_unique(&stamps);
+//! testkit::assert_monotonic_per_source(&stamps);
+//! testkit::assert_bounded_divergence(&stamps, Duration::from_secs(1));
+//! ```
+use super::{Timestamp, ID, NTP64};
+use alloc::collections::BTreeMap;
+use core::time::Duration;
+
+/// Asserts that all the [`Timestamp`]s of `stamps` are unique.
+///
+/// # Panics
+///
+/// If 2 [`Timestamp`]s are equal.
+#[track_caller]
+pub fn assert_unique(stamps: &[Timestamp]) {
+    let mut seen: BTreeMap<&Timestamp, usize> = BTreeMap::new();
+    for (i, ts) in stamps.iter().enumerate() {
+        if let Some(first) = seen.insert(ts, i) {
+            panic!(
+                "Timestamp {} at index {} is a duplicate of index {}",
+                ts, i, first
+            );
+        }
+    }
+}
+
+/// Asserts that the [`Timestamp`]s of `stamps` are strictly increasing per source (i.e. per [`ID`]).
+///
+/// # Panics
+///
+/// If a [`Timestamp`] isn't greater than the previous one with the same [`ID`].
+#[track_caller]
+pub fn assert_monotonic_per_source(stamps: &[Timestamp]) {
+    let mut latest: BTreeMap<&ID, (usize, &Timestamp)> = BTreeMap::new();
+    for (i, ts) in stamps.iter().enumerate() {
+        if let Some((j, previous)) = latest.insert(ts.get_id(), (i, ts)) {
+            if ts <= previous {
+                panic!(
+                    "Timestamp {} at index {} is not greater than Timestamp {} at index {} of the same source",
+                    ts, i, previous, j
+                );
+            }
+        }
+    }
+}
+
+/// Asserts that no [`Timestamp`] of `stamps` is more than `max` behind a [`Timestamp`] observed before it,
+/// i.e. that the clocks of the sources diverge by at most `max`.
+///
+/// # Panics
+///
+/// If a [`Timestamp`] is more than `max` behind a previous one.
+#[track_caller]
+pub fn assert_bounded_divergence(stamps: &[Timestamp], max: Duration) {
+    let max = NTP64::from(max);
+    let mut latest: Option<(usize, &Timestamp)> = None;
+    for (i, ts) in stamps.iter().enumerate() {
+        match latest {
+            Some((j, previous)) if previous.get_time() > ts.get_time() => {
+                let divergence = *previous.get_time() - *ts.get_time();
+                if divergence > max {
+                    panic!(
+                        "Timestamp {} at index {} is {:?} behind Timestamp {} at index {} (max: {:?})",
+                        ts,
+                        i,
+                        divergence.to_duration(),
+                        previous,
+                        j,
+                        max.to_duration()
+                    );
+                }
+            }
+            _ => latest = Some((i, ts)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use alloc::vec::Vec;
+    use core::{convert::TryFrom, time::Duration};
+
+    #[test]
+    fn conformance() {
+        let hlcs: Vec<HLC> = (0..3).map(|_| HLC::default()).collect();
+        let mut stamps = Vec::new();
+        for i in 0..300 {
+            let ts = hlcs[i % 3].new_timestamp();
+            hlcs[(i + 1) % 3].update_with_timestamp(&ts).unwrap();
+            stamps.push(ts);
+        }
+        testkit::assert_unique(&stamps);
+        testkit::assert_monotonic_per_source(&stamps);
+        testkit::assert_bounded_divergence(&stamps, Duration::from_millis(100));
+    }
+
+    #[test]
+    #[should_panic(expected = "at index 2 is not greater than Timestamp")]
+    fn not_monotonic() {
+        let (id1, id2) = (ID::try_from([1]).unwrap(), ID::try_from([2]).unwrap());
+        testkit::assert_monotonic_per_source(&[
+            Timestamp::new(NTP64(10), id1),
+            Timestamp::new(NTP64(1), id2),
+            Timestamp::new(NTP64(5), id1),
+        ]);
+    }
+}
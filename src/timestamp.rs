@@ -8,13 +8,13 @@
 //
 // SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
 //
-use super::{ID, NTP64};
-use alloc::string::String;
-use core::{fmt, time::Duration};
+use super::{ntp64::pad, DecodeError, ID, NTP64};
+use alloc::{format, string::String};
+use core::{fmt, str::FromStr, time::Duration};
 use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "std")]
-use core::str::FromStr;
+use {core::cmp::Ordering, std::time::SystemTime};
 
 /// A timestamp made of a [`NTP64`] and a [`crate::HLC`]'s unique identifier.
 ///
@@ -38,21 +38,44 @@ pub struct Timestamp {
 }
 
 impl Timestamp {
-    // Create a [`Timestamp`] with a [`NTP64`] and a [`crate::HLC`]'s unique `id`.
+    /// Create a [`Timestamp`] with a [`NTP64`] and a [`crate::HLC`]'s unique `id`.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::num::NonZeroU128;
+    /// use uhlc::{Timestamp, ID, NTP64};
+    ///
+    /// // a well-known Timestamp, usable as a constant
+    /// const EPOCH: Timestamp = Timestamp::new(NTP64(0), ID::from_nonzero_u128(NonZeroU128::MIN));
+    /// assert_eq!(EPOCH.to_string(), "0/1");
+    /// ```
     #[inline]
-    pub fn new(time: NTP64, id: ID) -> Timestamp {
+    pub const fn new(time: NTP64, id: ID) -> Timestamp {
+        invariant!(
+            u128::from_le_bytes(id.to_le_bytes()) != 0,
+            "Timestamp invariant violated: zero ID"
+        );
         Timestamp { time, id }
     }
 
+    /// Create a [`Timestamp`] from a `time` stored without its [`ID`] (see [`crate::serialization::time_only`]),
+    /// binding it to the `id` of its single writer supplied out-of-band (e.g. the identity of the connection).
+    ///
+    /// The `id` is trusted: binding the times of a writer to another [`ID`] breaks the uniqueness of the [`Timestamp`]s.
+    #[inline]
+    pub const fn bind_id(time: NTP64, id: ID) -> Timestamp {
+        Timestamp::new(time, id)
+    }
+
     // Returns the [`NTP64`] time.
     #[inline]
-    pub fn get_time(&self) -> &NTP64 {
+    pub const fn get_time(&self) -> &NTP64 {
         &self.time
     }
 
     // Returns the [`crate::HLC`]'s unique `id`.
     #[inline]
-    pub fn get_id(&self) -> &ID {
+    pub const fn get_id(&self) -> &ID {
         &self.id
     }
 
@@ -62,6 +85,86 @@ impl Timestamp {
         (self.time - other.time).to_duration()
     }
 
+    /// Pack this [`Timestamp`] into 24 bytes: the [`NTP64`] time as little endian (8 bytes),
+    /// followed by the le-encoded [`ID`] (16 bytes). I.e. as a little endian u192 made of
+    /// the [`ID`] as the most significant 128 bits and the time as the least significant 64 bits.
+    ///
+    /// This fixed-size layout is stable and intended for FFI and shared memory.
+    /// Notice that, unlike [`Timestamp::to_key()`], it doesn't preserve the ordering of the [`Timestamp`]s.
+    ///
+    /// # Examples
+    /// ```
+    /// use uhlc::{Timestamp, HLC};
+    ///
+    /// let ts = HLC::default().new_timestamp();
+    /// let bytes = ts.to_le_bytes();
+    /// assert_eq!(Timestamp::from_le_bytes(bytes).unwrap(), ts);
+    /// ```
+    pub const fn to_le_bytes(&self) -> [u8; 24] {
+        let time = self.time.0.to_le_bytes();
+        let id = self.id.to_le_bytes();
+        let mut bytes = [0u8; 24];
+        let mut i = 0;
+        while i < 8 {
+            bytes[i] = time[i];
+            i += 1;
+        }
+        while i < 24 {
+            bytes[i] = id[i - 8];
+            i += 1;
+        }
+        bytes
+    }
+
+    /// Unpack a [`Timestamp`] packed with [`Timestamp::to_le_bytes()`].
+    ///
+    /// Returns [`DecodeError::ZeroId`] if the [`ID`] bytes are all zeros.
+    pub const fn from_le_bytes(bytes: [u8; 24]) -> Result<Timestamp, DecodeError> {
+        let mut time = [0u8; 8];
+        let mut id = [0u8; ID::MAX_SIZE];
+        let mut i = 0;
+        while i < 8 {
+            time[i] = bytes[i];
+            i += 1;
+        }
+        while i < 24 {
+            id[i - 8] = bytes[i];
+            i += 1;
+        }
+        match ID::from_le_bytes_checked(id) {
+            Some(id) => Ok(Timestamp {
+                time: NTP64(u64::from_le_bytes(time)),
+                id,
+            }),
+            None => Err(DecodeError::ZeroId),
+        }
+    }
+
+    /// Returns a human-friendly representation of the signed time difference between this timestamp and `other`
+    /// (i.e. `self - other`), e.g.: `"+1.254s"` or `"-3ms"` (see [`NTP64::format_diff()`]).
+    #[inline]
+    pub fn format_diff(&self, other: &Timestamp) -> String {
+        self.time.format_diff(&other.time)
+    }
+
+    /// Returns `true` if the time of this timestamp is after `time`
+    /// (making the assumption that it's relative to [`std::time::UNIX_EPOCH`]).
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::{Duration, SystemTime};
+    /// use uhlc::HLC;
+    ///
+    /// let ts = HLC::default().new_timestamp();
+    /// assert!(ts.is_after_system_time(SystemTime::now() - Duration::from_secs(1)));
+    /// assert!(ts < SystemTime::now() + Duration::from_secs(1));
+    /// ```
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn is_after_system_time(&self, time: SystemTime) -> bool {
+        *self > time
+    }
+
     /// Convert to a RFC3339 time representation with nanoseconds precision.
     /// e.g.: `"2024-07-01T13:51:12.129693000Z/33"``
     #[cfg(feature = "std")]
@@ -89,12 +192,106 @@ impl Timestamp {
             }),
         }
     }
+
+    /// Parse a Timestamp String representation, auto-detecting the format of its time part:
+    /// either as an unsigned integer in decimal format or as a RFC3339 time representation (see [`NTP64::parse_any()`]).
+    ///
+    /// In case of failure, the error's cause reports which part (and which time format) failed to be parsed.
+    ///
+    /// # Examples
+    /// ```
+    /// use uhlc::Timestamp;
+    ///
+    /// let t1 = Timestamp::parse_any("7386690599959157260/33").unwrap();
+    /// let t2 = Timestamp::parse_any("2024-07-01T15:32:06.860479000Z/33").unwrap();
+    /// assert_eq!(t1.get_id(), t2.get_id());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn parse_any(s: &str) -> Result<Self, ParseTimestampError> {
+        match s.find('/') {
+            Some(i) => {
+                let (stime, srem) = s.split_at(i);
+                let time = NTP64::parse_any(stime).map_err(|e| ParseTimestampError {
+                    cause: format!("Invalid time in '{s}': {}", e.cause),
+                })?;
+                let id = ID::from_str(&srem[1..]).map_err(|e| ParseTimestampError {
+                    cause: format!("Invalid ID in '{s}': {}", e.cause),
+                })?;
+                Ok(Timestamp::new(time, id))
+            }
+            None => Err(ParseTimestampError {
+                cause: "No '/' found in String".into(),
+            }),
+        }
+    }
+
+    /// Encode as a compact, URL-safe, ASCII value suitable for HTTP/gRPC headers.
+    ///
+    /// The value grammar is (in ABNF):
+    /// ```text
+    /// header-value = time "-" id
+    /// time         = 16LHEX            ; the NTP64 time, zero-padded
+    /// id           = NZLHEX *31LHEX    ; the ID, without leading zeros
+    /// LHEX         = DIGIT / %x61-66   ; lowercase hexadecimal digit
+    /// NZLHEX       = %x31-39 / %x61-66 ; non-zero lowercase hexadecimal digit
+    /// ```
+    ///
+    /// # Examples
+    /// ```
+    /// use uhlc::*;
+    /// use std::convert::TryFrom;
+    ///
+    /// let t = Timestamp::new(NTP64(7386690599959157260), ID::try_from([0x33]).unwrap());
+    /// assert_eq!(t.to_header_value(), "6682cbf6dc485a0c-33");
+    /// assert_eq!(Timestamp::from_header_value("6682cbf6dc485a0c-33").unwrap(), t);
+    /// ```
+    pub fn to_header_value(&self) -> String {
+        format!("{:016x}-{}", self.time.as_u64(), self.id)
+    }
+
+    /// Decode a value encoded with [`Timestamp::to_header_value()`].
+    ///
+    /// The parsing is strict: any value that doesn't exactly match the grammar
+    /// (e.g. uppercase digits, missing zero-padding of the time, leading zeros in the ID) is rejected.
+    pub fn from_header_value(s: &str) -> Result<Self, ParseTimestampError> {
+        fn is_lower_hex(s: &str) -> bool {
+            s.bytes().all(|c| matches!(c, b'0'..=b'9' | b'a'..=b'f'))
+        }
+
+        let (stime, sid) = s.split_once('-').ok_or_else(|| ParseTimestampError {
+            cause: format!("Invalid header value '{s}': no '-' found"),
+        })?;
+        if stime.len() != 16 || !is_lower_hex(stime) {
+            return Err(ParseTimestampError {
+                cause: format!("Invalid header value '{s}': time must be 16 lowercase hex digits"),
+            });
+        }
+        if sid.is_empty() || sid.len() > 2 * ID::MAX_SIZE || !is_lower_hex(sid) {
+            return Err(ParseTimestampError {
+                cause: format!(
+                    "Invalid header value '{s}': id must be 1 to {} lowercase hex digits",
+                    2 * ID::MAX_SIZE
+                ),
+            });
+        }
+        // unwrap: stime is made of 16 hex digits
+        let time = NTP64(u64::from_str_radix(stime, 16).unwrap());
+        let id = sid.parse::<ID>().map_err(|e| ParseTimestampError {
+            cause: format!("Invalid header value '{s}': {}", e.cause),
+        })?;
+        Ok(Timestamp::new(time, id))
+    }
 }
 
 impl fmt::Display for Timestamp {
     /// Formats Timestamp as the time part followed by the ID part, with `/` as separator.  
     /// By default the time part is formatted as an unsigned integer in decimal format.  
     /// If the alternate flag `{:#}` is used, the time part is formatted with RFC3339 representation with nanoseconds precision.
+    /// With the alternate flag, a precision (e.g. `{:#.3}`) sets the number of digits of the fraction of second (from 0 to 9).
+    /// Without the `std` feature, the time part is always formatted in decimal format (see [`NTP64`]'s [`fmt::Display`]),
+    /// so it can be parsed back with [`FromStr`] on both sides of a `no_std` / `std` boundary.
+    ///
+    /// The width, fill and alignment flags are honored for the whole Timestamp (e.g. `{:>40}`).
     ///
     /// # Examples
     /// ```
@@ -102,25 +299,96 @@ impl fmt::Display for Timestamp {
     ///   use std::convert::TryFrom;
     ///
     ///   let t =Timestamp::new(NTP64(7386690599959157260), ID::try_from([0x33]).unwrap());
-    ///   println!("{t}");    // displays: 7386690599959157260/33
-    ///   println!("{t:#}");  // displays: 2024-07-01T15:32:06.860479000Z/33
+    ///   println!("{t}");      // displays: 7386690599959157260/33
+    ///   println!("{t:#}");    // displays: 2024-07-01T15:32:06.860479000Z/33
+    ///   println!("{t:#.3}");  // displays: 2024-07-01T15:32:06.860Z/33
+    ///   assert_eq!(format!("{t:>25}"), "   7386690599959157260/33");
     /// ```
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if f.alternate() {
-            write!(f, "{:#}/{}", self.time, self.id)
-        } else {
-            write!(f, "{}/{}", self.time, self.id)
+        match (f.alternate(), f.precision(), f.width()) {
+            (false, _, None) => {
+                write!(f, "{}/", self.time)?;
+                self.id.write_hex(f)
+            }
+            (true, None, None) => {
+                write!(f, "{:#}/", self.time)?;
+                self.id.write_hex(f)
+            }
+            (false, _, Some(_)) => pad(f, &format!("{}/{}", self.time, self.id)),
+            (true, None, Some(_)) => pad(f, &format!("{:#}/{}", self.time, self.id)),
+            (true, Some(p), None) => write!(f, "{:#.*}/{}", p, self.time, self.id),
+            (true, Some(p), Some(_)) => pad(f, &format!("{:#.*}/{}", p, self.time, self.id)),
         }
     }
 }
 
+/// Compares the time of a [`Timestamp`] with a [`SystemTime`]
+/// (making the assumption that it's relative to [`std::time::UNIX_EPOCH`]).
+/// The [`ID`] is not considered.
+#[cfg(feature = "std")]
+impl PartialEq<SystemTime> for Timestamp {
+    fn eq(&self, other: &SystemTime) -> bool {
+        self.time.to_system_time() == *other
+    }
+}
+
+#[cfg(feature = "std")]
+impl PartialOrd<SystemTime> for Timestamp {
+    fn partial_cmp(&self, other: &SystemTime) -> Option<Ordering> {
+        self.time.to_system_time().partial_cmp(other)
+    }
+}
+
+/// Formats the [`Timestamp`] with its time as 16 lowercase hexadecimal digits:
+/// `"<ntp64_time_hexadecimal>/<hlc_id_hexadecimal>"` (see [`crate::HexFormatter`]).
+///
+/// # Examples
+/// ```
+/// use std::convert::TryFrom;
+/// use uhlc::{Timestamp, ID, NTP64};
+///
+/// let t = Timestamp::new(NTP64(7386690599959157260), ID::try_from([0x33]).unwrap());
+/// assert_eq!(format!("{t:x}"), "6682cbf6dc485a0c/33");
+/// ```
+impl fmt::LowerHex for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:016x}/", self.time.as_u64())?;
+        self.id.write_hex(f)
+    }
+}
+
 impl fmt::Debug for Timestamp {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{:?}/{:?}", self.time, self.id)
     }
 }
 
-#[cfg(feature = "std")]
+impl Timestamp {
+    /// Parse a [`Timestamp`] formatted as `"<ntp64_time>/<hlc_id_hexadecimal>"`, with the time in decimal format,
+    /// at the start of `s` and returning it with the remaining input (see [`NTP64::parse_prefix()`] and [`ID::parse_prefix()`]).
+    /// E.g. to parse a [`Timestamp`] embedded in a log line or a protocol message without splitting it first.
+    ///
+    /// # Examples
+    /// ```
+    /// use uhlc::{Timestamp, NTP64};
+    ///
+    /// let (ts, rest) = Timestamp::parse_prefix("7386690599959157260/33 INFO started").unwrap();
+    /// assert_eq!(*ts.get_time(), NTP64(7386690599959157260));
+    /// assert_eq!(rest, " INFO started");
+    /// assert!(Timestamp::parse_prefix("7386690599959157260 INFO started").is_err());
+    /// ```
+    pub fn parse_prefix(s: &str) -> Result<(Timestamp, &str), ParseTimestampError> {
+        let (time, rest) =
+            NTP64::parse_prefix(s).map_err(|e| ParseTimestampError { cause: e.cause })?;
+        let rest = rest.strip_prefix('/').ok_or_else(|| ParseTimestampError {
+            cause: format!("No '/' found after the time in '{s}'"),
+        })?;
+        let (id, rest) =
+            ID::parse_prefix(rest).map_err(|e| ParseTimestampError { cause: e.cause })?;
+        Ok((Timestamp::new(time, id), rest))
+    }
+}
+
 impl FromStr for Timestamp {
     type Err = ParseTimestampError;
 
@@ -142,11 +410,19 @@ impl FromStr for Timestamp {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-#[cfg_attr(deature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct ParseTimestampError {
     pub cause: String,
 }
 
+impl fmt::Display for ParseTimestampError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid Timestamp: {}", self.cause)
+    }
+}
+
+impl core::error::Error for ParseTimestampError {}
+
 #[cfg(test)]
 mod tests {
     use crate::*;
@@ -191,17 +467,119 @@ mod tests {
             assert!(ts2_epoch < ts2_now);
         }
 
-        #[cfg(feature = "std")]
-        {
-            // We do not care about parsing human-readable timestamps in no_std
-            let s = ts1_now.to_string();
-            assert_eq!(ts1_now, s.parse().unwrap());
-        }
+        // the decimal representation is parsed back, with or without std
+        let s = ts1_now.to_string();
+        assert_eq!(ts1_now, s.parse::<Timestamp>().unwrap());
+        assert_eq!(
+            format!("{ts1_now:x}"),
+            ts1_now.format_with(&HexFormatter).to_string()
+        );
 
         let diff = ts1_now.get_diff_duration(&ts2_now);
         assert_eq!(diff, Duration::from_secs(0));
     }
 
+    #[test]
+    fn header_value() {
+        let hlc = HLCBuilder::new().with_id(ID::rand()).build();
+        for _ in 1..1000 {
+            let ts = hlc.new_timestamp();
+            let header = ts.to_header_value();
+            assert!(header
+                .bytes()
+                .all(|c| c.is_ascii_alphanumeric() || c == b'-'));
+            assert_eq!(ts, Timestamp::from_header_value(&header).unwrap());
+        }
+
+        for invalid in [
+            "",
+            "668245ecdc4eb40c",
+            "668245ecdc4eb40c-",
+            "668245ECDC4EB40C-33",
+            "668245ecdc4eb40c-3F",
+            "68245ecdc4eb40c-33",
+            "06682cbf6dc485a0c-33",
+            "668245ecdc4eb40c-033",
+            "668245ecdc4eb40c-0",
+            "6682cbf6dc485a0c-33-1",
+            "+68245ecdc4eb40c-33",
+            "668245ecdc4eb40c-123456789abcdef0123456789abcdef01",
+        ] {
+            assert!(
+                Timestamp::from_header_value(invalid).is_err(),
+                "'{}' should be rejected",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn le_bytes_packing() {
+        use rand::prelude::*;
+
+        let mut rng = rand::thread_rng();
+        let mut times = vec![0, 1, u64::MAX];
+        let mut ids = vec![1, u128::MAX];
+        for i in 0..64 {
+            times.push(1 << i);
+        }
+        for i in 0..128 {
+            ids.push(1 << i);
+        }
+        for _ in 0..64 {
+            times.push(rng.gen());
+            ids.push(rng.gen_range(1..=u128::MAX));
+        }
+        for time in times.iter() {
+            for id in ids.iter() {
+                let ts = Timestamp::new(NTP64(*time), ID::try_from(*id).unwrap());
+                let bytes = ts.to_le_bytes();
+                assert_eq!(&bytes[..8], &time.to_le_bytes());
+                assert_eq!(&bytes[8..], &id.to_le_bytes());
+                assert_eq!(Timestamp::from_le_bytes(bytes), Ok(ts));
+            }
+        }
+        let mut zero_id = [0xff; 24];
+        zero_id[8..].fill(0);
+        assert_eq!(Timestamp::from_le_bytes(zero_id), Err(DecodeError::ZeroId));
+
+        // usable in const contexts
+        const PACKED: [u8; 24] = [
+            1, 0, 0, 0, 0, 0, 0, 0, 0x33, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        const UNPACKED: Result<Timestamp, DecodeError> = Timestamp::from_le_bytes(PACKED);
+        assert_eq!(UNPACKED.unwrap().to_string(), "1/33");
+    }
+
+    #[test]
+    fn format_flags() {
+        let t = Timestamp::new(NTP64(7386690599959157260), ID::try_from([0x33]).unwrap());
+        assert_eq!(format!("{t:#.3}"), "2024-07-01T15:32:06.860Z/33");
+        assert_eq!(format!("{t:#.0}"), "2024-07-01T15:32:06Z/33");
+        assert_eq!(format!("{t:>25}"), "   7386690599959157260/33");
+        assert_eq!(format!("{t:-<#30.3}|"), "2024-07-01T15:32:06.860Z/33---|");
+        assert_eq!(format!("{t:.3}"), "7386690599959157260/33");
+    }
+
+    #[test]
+    fn parse_any() {
+        let hlc = HLCBuilder::new().with_id(ID::rand()).build();
+        let ts = hlc.new_timestamp();
+        assert_eq!(Timestamp::parse_any(&ts.to_string()).unwrap(), ts);
+        assert_eq!(
+            Timestamp::parse_any(&ts.to_string_rfc3339_lossy()).unwrap(),
+            Timestamp::parse_rfc3339(&ts.to_string_rfc3339_lossy()).unwrap()
+        );
+
+        let err = Timestamp::parse_any("18446744073709551616/33").unwrap_err();
+        assert!(err.cause.contains("Decimal format"), "{}", err.cause);
+        let err = Timestamp::parse_any("2024-07-01 15:32:06/33").unwrap_err();
+        assert!(err.cause.contains("RFC3339 format"), "{}", err.cause);
+        let err = Timestamp::parse_any("7386690599959157260/xyz").unwrap_err();
+        assert!(err.cause.contains("Invalid ID"), "{}", err.cause);
+        assert!(Timestamp::parse_any("7386690599959157260").is_err());
+    }
+
     #[test]
     fn bijective_to_string() {
         use crate::*;
@@ -213,4 +591,48 @@ mod tests {
             assert_eq!(now_ts, Timestamp::from_str(&now_ts.to_string()).unwrap());
         }
     }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn system_time_comparison() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let id = ID::try_from([0x01]).unwrap();
+        let time = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let ts = Timestamp::new(NTP64::from(Duration::from_secs(1_700_000_000)), id);
+        assert!(ts == time);
+        assert!(ts < time + Duration::from_millis(1));
+        assert!(ts > time - Duration::from_millis(1));
+        assert!(ts.is_after_system_time(time - Duration::from_millis(1)));
+        assert!(!ts.is_after_system_time(time));
+        assert!(ts > UNIX_EPOCH - Duration::from_secs(1));
+    }
+
+    #[test]
+    fn parse_prefix() {
+        let ts = Timestamp::new(NTP64(7386690599959157260), ID::rand());
+        let line = alloc::format!("[{ts}] INFO started at {ts}/");
+        let (parsed, rest) = Timestamp::parse_prefix(&line[1..]).unwrap();
+        assert_eq!(parsed, ts);
+        assert!(rest.starts_with("] INFO"));
+        let (parsed, rest) =
+            Timestamp::parse_prefix(&line[line.len() - 1 - ts.to_string().len()..]).unwrap();
+        assert_eq!((parsed, rest), (ts, "/"));
+
+        // errors, without panicking
+        for s in [
+            "",
+            "/",
+            "1",
+            "1/",
+            "1/0",
+            "1/g",
+            "/33",
+            "99999999999999999999/33",
+            "1/é",
+            "1/111111111111111111111111111111111",
+        ] {
+            assert!(Timestamp::parse_prefix(s).is_err(), "for {:?}", s);
+        }
+    }
 }
@@ -0,0 +1,182 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::Timestamp;
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::{iter::FromIterator, ops::Bound};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A log of values ordered by their [`Timestamp`].
+///
+/// It's serialized as a sequence of `(Timestamp, T)` pairs, in [`Timestamp`] order.
+///
+/// # Examples
+///
+/// ```
+/// use uhlc::{TimestampedLog, HLC};
+///
+/// let hlc = HLC::default();
+/// let mut log = TimestampedLog::new();
+/// log.insert(hlc.new_timestamp(), "a");
+/// let ts_b = hlc.new_timestamp();
+/// log.insert(ts_b, "b");
+/// log.insert(hlc.new_timestamp(), "c");
+///
+/// // the values inserted after "b"
+/// let since_b: Vec<_> = log.range_since(&ts_b).map(|(_, v)| *v).collect();
+/// assert_eq!(since_b, ["c"]);
+///
+/// // discard the values before "b"
+/// assert_eq!(log.prune_before(&ts_b), 1);
+/// assert_eq!(log.len(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimestampedLog<T> {
+    entries: BTreeMap<Timestamp, T>,
+}
+
+impl<T> TimestampedLog<T> {
+    /// Create an empty [`TimestampedLog`].
+    pub fn new() -> TimestampedLog<T> {
+        TimestampedLog {
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Insert a `value` at `timestamp`, returning the value that was previously at this timestamp, if any.
+    pub fn insert(&mut self, timestamp: Timestamp, value: T) -> Option<T> {
+        self.entries.insert(timestamp, value)
+    }
+
+    /// Returns the value at `timestamp`, if any.
+    pub fn get(&self, timestamp: &Timestamp) -> Option<&T> {
+        self.entries.get(timestamp)
+    }
+
+    /// Remove the value at `timestamp`, returning it if any.
+    pub fn remove(&mut self, timestamp: &Timestamp) -> Option<T> {
+        self.entries.remove(timestamp)
+    }
+
+    /// Returns the number of values in the log.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the log contains no value.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the entry with the greatest [`Timestamp`], if any.
+    pub fn latest(&self) -> Option<(&Timestamp, &T)> {
+        self.entries.iter().next_back()
+    }
+
+    /// Returns an iterator over all the entries, in [`Timestamp`] order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&Timestamp, &T)> {
+        self.entries.iter()
+    }
+
+    /// Returns an iterator over the entries with a [`Timestamp`] strictly greater than `since`,
+    /// in [`Timestamp`] order.
+    ///
+    /// Typically, `since` is the latest [`Timestamp`] already known by a peer to synchronize with.
+    pub fn range_since(
+        &self,
+        since: &Timestamp,
+    ) -> impl DoubleEndedIterator<Item = (&Timestamp, &T)> {
+        self.entries
+            .range((Bound::Excluded(since), Bound::Unbounded))
+    }
+
+    /// Remove all the entries with a [`Timestamp`] strictly lower than `before`,
+    /// returning the number of removed entries.
+    pub fn prune_before(&mut self, before: &Timestamp) -> usize {
+        let kept = self.entries.split_off(before);
+        let removed = self.entries.len();
+        self.entries = kept;
+        removed
+    }
+}
+
+impl<T> Default for TimestampedLog<T> {
+    fn default() -> Self {
+        TimestampedLog::new()
+    }
+}
+
+impl<T> Extend<(Timestamp, T)> for TimestampedLog<T> {
+    fn extend<I: IntoIterator<Item = (Timestamp, T)>>(&mut self, iter: I) {
+        self.entries.extend(iter)
+    }
+}
+
+impl<T> FromIterator<(Timestamp, T)> for TimestampedLog<T> {
+    fn from_iter<I: IntoIterator<Item = (Timestamp, T)>>(iter: I) -> Self {
+        TimestampedLog {
+            entries: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<T> IntoIterator for TimestampedLog<T> {
+    type Item = (Timestamp, T);
+    type IntoIter = alloc::collections::btree_map::IntoIter<Timestamp, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<T: Serialize> Serialize for TimestampedLog<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.entries.iter())
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for TimestampedLog<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::<(Timestamp, T)>::deserialize(deserializer)
+            .map(|entries| entries.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use core::convert::TryFrom;
+
+    #[test]
+    fn timestamped_log() {
+        let id = ID::try_from([0x01]).unwrap();
+        let ts = |t: u64| Timestamp::new(NTP64(t), id);
+        let mut log: TimestampedLog<u32> = vec![(ts(30), 3), (ts(10), 1), (ts(20), 2)]
+            .into_iter()
+            .collect();
+        assert_eq!(log.insert(ts(20), 22), Some(2));
+        assert_eq!(log.latest(), Some((&ts(30), &3)));
+        let since: Vec<_> = log.range_since(&ts(10)).map(|(_, v)| *v).collect();
+        assert_eq!(since, [22, 3]);
+        assert_eq!(log.range_since(&ts(30)).count(), 0);
+
+        let json = serde_json::to_string(&log).unwrap();
+        assert!(json.starts_with(r#"[[{"time":10,"#));
+        assert_eq!(
+            serde_json::from_str::<TimestampedLog<u32>>(&json).unwrap(),
+            log
+        );
+
+        assert_eq!(log.prune_before(&ts(25)), 2);
+        assert_eq!(log.into_iter().collect::<Vec<_>>(), [(ts(30), 3)]);
+    }
+}
@@ -0,0 +1,97 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::HLC;
+use core::fmt;
+use tracing_subscriber::fmt::{format::Writer, time::FormatTime};
+
+/// A `tracing-subscriber` timer formatting the time of each event as a new [`crate::Timestamp`] of an [`HLC`],
+/// so the log lines of all the hosts of a distributed system carry comparable HLC timestamps
+/// instead of the raw wall clock of each host.
+///
+/// By default, the [`crate::Timestamp`]s are formatted as `"<ntp64_time_decimal>/<hlc_id_hexadecimal>"`:
+/// as their times have the same number of digits (for several centuries), sorting the log lines as text
+/// sorts them by [`crate::Timestamp`]. With [`UhlcTimer::with_rfc3339()`], they are formatted in a human-readable form
+/// (see [`crate::Timestamp`]'s [`fmt::Display`]).
+///
+/// Each event generates a new [`crate::Timestamp`]: sharing the [`HLC`] with the application (e.g. updating it
+/// with the incoming [`crate::Timestamp`]s) makes the log lines causally ordered with its messages.
+///
+/// # Examples
+///
+/// ```
+/// use uhlc::{UhlcTimer, HLC};
+///
+/// let hlc = HLC::default();
+/// let subscriber = tracing_subscriber::fmt()
+///     .with_timer(UhlcTimer::new(&hlc))
+///     .finish();
+/// ```
+#[derive(Debug, Clone)]
+pub struct UhlcTimer {
+    hlc: HLC,
+    rfc3339: bool,
+}
+
+impl UhlcTimer {
+    /// Create a timer formatting the new [`crate::Timestamp`]s of `hlc` (or of its clones).
+    pub fn new(hlc: &HLC) -> UhlcTimer {
+        UhlcTimer {
+            hlc: hlc.clone(),
+            rfc3339: false,
+        }
+    }
+
+    /// Format the [`crate::Timestamp`]s with their time in RFC3339 format
+    /// (e.g. `"2024-07-01T15:32:06.860479000Z/33"`), losing their logical counter.
+    pub fn with_rfc3339(mut self) -> UhlcTimer {
+        self.rfc3339 = true;
+        self
+    }
+}
+
+impl FormatTime for UhlcTimer {
+    fn format_time(&self, w: &mut Writer<'_>) -> fmt::Result {
+        let timestamp = self.hlc.new_timestamp();
+        if self.rfc3339 {
+            write!(w, "{timestamp:#}")
+        } else {
+            write!(w, "{timestamp}")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use alloc::string::String;
+    use tracing_subscriber::fmt::{format::Writer, time::FormatTime};
+
+    #[test]
+    fn uhlc_timer() {
+        let hlc = HLC::default();
+        let timer = UhlcTimer::new(&hlc);
+        let format = |timer: &UhlcTimer| {
+            let mut s = String::new();
+            timer.format_time(&mut Writer::new(&mut s)).unwrap();
+            s
+        };
+        let first: Timestamp = format(&timer).parse().unwrap();
+        let second: Timestamp = format(&timer).parse().unwrap();
+        assert!(first < second);
+        assert_eq!(first.get_id(), hlc.get_id());
+        // the timer shares the HLC
+        assert!(hlc.new_timestamp() > second);
+
+        let human = format(&timer.with_rfc3339());
+        assert!(human.ends_with(&format!("Z/{}", hlc.get_id())));
+        assert!(Timestamp::parse_rfc3339(&human).is_ok());
+    }
+}
@@ -0,0 +1,68 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::NTP64;
+
+/// A physical clock relying on the virtual time of a [turmoil](https://docs.rs/turmoil) simulation.
+///
+/// It returns a NTP64 relative to std::time::UNIX_EPOCH, as the simulation's epoch
+/// (see `turmoil::Builder::epoch()`) plus the virtual time elapsed since the start of the simulation.
+/// This virtual time is driven by the paused tokio runtimes of the simulation,
+/// making the [`crate::Timestamp`]s reproducible from one simulation run to another.
+///
+/// # Examples
+///
+/// ```
+/// use uhlc::{turmoil_clock, HLCBuilder};
+///
+/// let mut sim = turmoil::Builder::new().build();
+/// sim.client("node", async {
+///     let hlc = HLCBuilder::new().with_clock(turmoil_clock).build();
+///     println!("{}", hlc.new_timestamp());
+///     Ok(())
+/// });
+/// sim.run().unwrap();
+/// ```
+///
+/// # Panics
+///
+/// If called outside of a simulated host.
+#[inline]
+pub fn turmoil_clock() -> NTP64 {
+    NTP64::from(
+        turmoil::since_epoch().expect("turmoil_clock() must be called within a turmoil simulation"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn simulated_time() {
+        let epoch = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let mut sim = turmoil::Builder::new().epoch(epoch).build();
+        sim.client("node", async move {
+            let hlc = HLCBuilder::new().with_clock(turmoil_clock).build();
+            let ts1 = hlc.new_timestamp();
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            let ts2 = hlc.new_timestamp();
+            assert!(ts1.get_time().to_system_time() >= epoch);
+            assert_eq!(
+                ts2.get_diff_duration(&ts1).as_secs(),
+                10,
+                "the HLC should follow the virtual time"
+            );
+            Ok(())
+        });
+        sim.run().unwrap();
+    }
+}
@@ -0,0 +1,194 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{Timestamp, NTP64};
+use core::{fmt, time::Duration};
+use humantime::format_rfc3339_nanos;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// An offset from UTC, in minutes, used to display [`NTP64`] and [`Timestamp`] in a local time
+/// (see [`NTP64::to_string_rfc3339_with_offset()`] and [`Timestamp::to_string_rfc3339_with_offset()`]).
+///
+/// It is displayed as in RFC3339: `"Z"` for UTC, `"+HH:MM"` or `"-HH:MM"` otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct UtcOffset {
+    minutes: i16,
+}
+
+impl UtcOffset {
+    /// The UTC offset (i.e. no offset).
+    pub const UTC: UtcOffset = UtcOffset { minutes: 0 };
+
+    // The maximal absolute offset: 23:59
+    const MAX_MINUTES: i16 = 24 * 60 - 1;
+
+    /// Create a [`UtcOffset`] from a number of minutes (negative for west of UTC).
+    ///
+    /// Returns `None` if the absolute offset is greater than 23:59.
+    pub fn from_minutes(minutes: i16) -> Option<UtcOffset> {
+        if (-Self::MAX_MINUTES..=Self::MAX_MINUTES).contains(&minutes) {
+            Some(UtcOffset { minutes })
+        } else {
+            None
+        }
+    }
+
+    /// Create a [`UtcOffset`] from a number of hours and minutes (both negative for west of UTC).
+    ///
+    /// Returns `None` if `hours` and `minutes` have different signs, if `minutes` is not within -59..=59,
+    /// or if the absolute offset is greater than 23:59.
+    pub fn from_hm(hours: i8, minutes: i8) -> Option<UtcOffset> {
+        if !(-59..=59).contains(&minutes)
+            || (hours > 0 && minutes < 0)
+            || (hours < 0 && minutes > 0)
+        {
+            return None;
+        }
+        UtcOffset::from_minutes(hours as i16 * 60 + minutes as i16)
+    }
+
+    /// Returns this offset as a number of minutes (negative for west of UTC).
+    pub fn as_minutes(&self) -> i16 {
+        self.minutes
+    }
+
+    // Split a RFC3339 time representation ending with a "+HH:MM" or "-HH:MM" offset
+    // into the local time (without offset) and the offset.
+    pub(crate) fn split_rfc3339(s: &str) -> Option<(&str, UtcOffset)> {
+        let i = s.len().checked_sub(6)?;
+        if !s.is_char_boundary(i) || !s[i..].is_ascii() {
+            return None;
+        }
+        let (local, soffset) = s.split_at(i);
+        let bytes = soffset.as_bytes();
+        let sign: i16 = match bytes[0] {
+            b'+' => 1,
+            b'-' => -1,
+            _ => return None,
+        };
+        if bytes[3] != b':'
+            || !soffset[1..3]
+                .bytes()
+                .chain(soffset[4..].bytes())
+                .all(|c| c.is_ascii_digit())
+        {
+            return None;
+        }
+        // unwrap: the hours and minutes are made of 2 digits
+        let hours: i16 = soffset[1..3].parse().unwrap();
+        let minutes: i16 = soffset[4..].parse().unwrap();
+        if minutes > 59 {
+            return None;
+        }
+        UtcOffset::from_minutes(sign * (hours * 60 + minutes)).map(|offset| (local, offset))
+    }
+
+    // Convert a local time in this offset to UTC
+    pub(crate) fn to_utc(self, local: SystemTime) -> SystemTime {
+        let shift = Duration::from_secs(u64::from(self.minutes.unsigned_abs()) * 60);
+        if self.minutes < 0 {
+            local + shift
+        } else {
+            local - shift
+        }
+    }
+}
+
+impl fmt::Display for UtcOffset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.minutes == 0 {
+            return f.write_str("Z");
+        }
+        let sign = if self.minutes < 0 { '-' } else { '+' };
+        let minutes = self.minutes.unsigned_abs();
+        write!(f, "{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+    }
+}
+
+impl NTP64 {
+    /// Convert to a RFC3339 time representation with nanoseconds precision, in the local time of `offset`
+    /// (making the assumption that this NTP64 is relative to [`UNIX_EPOCH`]).
+    /// e.g.: `"2024-07-01T15:51:12.129693000+02:00"`
+    ///
+    /// Notice that the canonical representation (see [`NTP64::to_string_rfc3339_lossy()`]) remains in UTC,
+    /// and should be preferred for machine use.
+    ///
+    /// # Examples
+    /// ```
+    /// use uhlc::{UtcOffset, NTP64};
+    ///
+    /// let t = NTP64(7386690599959157260);
+    /// let offset = UtcOffset::from_hm(-5, -30).unwrap();
+    /// assert_eq!(t.to_string_rfc3339_with_offset(offset), "2024-07-01T10:02:06.860479000-05:30");
+    /// ```
+    pub fn to_string_rfc3339_with_offset(&self, offset: UtcOffset) -> String {
+        let shift = Duration::from_secs(u64::from(offset.minutes.unsigned_abs()) * 60);
+        let local = if offset.minutes < 0 {
+            self.to_duration().saturating_sub(shift)
+        } else {
+            self.to_duration() + shift
+        };
+        let mut s = format_rfc3339_nanos(UNIX_EPOCH + local).to_string();
+        // replace the trailing 'Z'
+        s.pop();
+        s.push_str(&offset.to_string());
+        s
+    }
+}
+
+impl Timestamp {
+    /// Convert to a RFC3339 time representation with nanoseconds precision, in the local time of `offset`.
+    /// e.g.: `"2024-07-01T15:51:12.129693000+02:00/33"`
+    ///
+    /// See [`NTP64::to_string_rfc3339_with_offset()`].
+    pub fn to_string_rfc3339_with_offset(&self, offset: UtcOffset) -> String {
+        format!(
+            "{}/{}",
+            self.get_time().to_string_rfc3339_with_offset(offset),
+            self.get_id()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use core::convert::TryFrom;
+
+    #[test]
+    fn rfc3339_with_offset() {
+        let t = Timestamp::new(NTP64(7386690599959157260), ID::try_from([0x33]).unwrap());
+        assert_eq!(
+            t.to_string_rfc3339_with_offset(UtcOffset::UTC),
+            t.to_string_rfc3339_lossy()
+        );
+        assert_eq!(
+            t.to_string_rfc3339_with_offset(UtcOffset::from_hm(2, 0).unwrap()),
+            "2024-07-01T17:32:06.860479000+02:00/33"
+        );
+        assert_eq!(
+            t.to_string_rfc3339_with_offset(UtcOffset::from_minutes(-23 * 60 - 59).unwrap()),
+            "2024-06-30T15:33:06.860479000-23:59/33"
+        );
+        // the local times are parsed back to the same UTC time
+        let utc = Timestamp::parse_rfc3339(&t.to_string_rfc3339_lossy()).unwrap();
+        for offset in [(9, 30), (-3, 0), (0, -45)] {
+            let offset = UtcOffset::from_hm(offset.0, offset.1).unwrap();
+            let local = t.to_string_rfc3339_with_offset(offset);
+            assert_eq!(Timestamp::parse_rfc3339(&local).unwrap(), utc);
+        }
+        assert!(NTP64::parse_rfc3339("2024-07-01T15:32:06+24:00").is_err());
+        assert!(NTP64::parse_rfc3339("2024-07-01T15:32:06€:00").is_err());
+
+        assert!(UtcOffset::from_minutes(24 * 60).is_none());
+        assert!(UtcOffset::from_hm(1, -30).is_none());
+        assert!(UtcOffset::from_hm(0, 60).is_none());
+    }
+}
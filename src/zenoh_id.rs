@@ -0,0 +1,133 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+use super::{IdError, ID};
+use core::fmt;
+
+/// An identifier with the shape of zenoh's `ZenohId`: up to [`ID::MAX_SIZE`] bytes, le-encoded.
+///
+/// Implementing this trait (without a dependency between uhlc and zenoh) provides conversions
+/// with [`ID`], and the same size and hexadecimal representation as [`ID`]
+/// (see [`ID::write_hex()`]), so both can't drift apart.
+///
+/// # Examples
+///
+/// ```
+/// use std::fmt;
+/// use uhlc::{ZenohIdLike, ID};
+///
+/// struct ZenohId([u8; 16]);
+///
+/// impl From<[u8; 16]> for ZenohId {
+///     fn from(bytes: [u8; 16]) -> Self {
+///         ZenohId(bytes)
+///     }
+/// }
+///
+/// impl ZenohIdLike for ZenohId {
+///     fn to_le_bytes(&self) -> [u8; 16] {
+///         self.0
+///     }
+/// }
+///
+/// impl fmt::Display for ZenohId {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         self.write_hex(f)
+///     }
+/// }
+///
+/// let id = ID::rand();
+/// let zid: ZenohId = id.to_zenoh_id();
+/// assert_eq!(zid.to_string(), id.to_string());
+/// assert_eq!(zid.size(), id.size());
+/// assert_eq!(ID::from_zenoh_id(&zid), Ok(id));
+/// ```
+pub trait ZenohIdLike: From<[u8; ID::MAX_SIZE]> {
+    /// The le-encoded bytes of this identifier.
+    fn to_le_bytes(&self) -> [u8; ID::MAX_SIZE];
+
+    /// The size of this identifier in bytes, as [`ID::size()`].
+    fn size(&self) -> usize {
+        ID::MAX_SIZE - (u128::from_le_bytes(self.to_le_bytes()).leading_zeros() as usize / 8)
+    }
+
+    /// Write this identifier in hexadecimal, as [`ID::write_hex()`]
+    /// (a zero identifier is written as `"0"`).
+    fn write_hex<W: fmt::Write + ?Sized>(&self, w: &mut W) -> fmt::Result {
+        match ID::from_zenoh_id(self) {
+            Ok(id) => id.write_hex(w),
+            Err(_) => w.write_str("0"),
+        }
+    }
+}
+
+impl ID {
+    /// Create an [`ID`] from a [`ZenohIdLike`] identifier, with the same bytes.
+    ///
+    /// Returns [`IdError::Zero`] if its bytes are all zeros.
+    #[inline]
+    pub fn from_zenoh_id<Z: ZenohIdLike>(zid: &Z) -> Result<ID, IdError> {
+        ID::from_bytes(&zid.to_le_bytes())
+    }
+
+    /// Convert this [`ID`] into a [`ZenohIdLike`] identifier, with the same bytes.
+    #[inline]
+    pub fn to_zenoh_id<Z: ZenohIdLike>(&self) -> Z {
+        Z::from(self.to_le_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use alloc::string::{String, ToString};
+    use core::convert::TryFrom;
+
+    struct Zid([u8; 16]);
+
+    impl From<[u8; 16]> for Zid {
+        fn from(bytes: [u8; 16]) -> Self {
+            Zid(bytes)
+        }
+    }
+
+    impl ZenohIdLike for Zid {
+        fn to_le_bytes(&self) -> [u8; 16] {
+            self.0
+        }
+    }
+
+    #[test]
+    fn zenoh_id_representation() {
+        // the representations shared with zenoh, that must never change
+        for (bytes, size, hex) in [
+            (&[0x01][..], 1, "1"),
+            (&[0x00, 0x01], 2, "100"),
+            (&[0x1a, 0x2b, 0x3c], 3, "3c2b1a"),
+            (&[0xab, 0x0c], 2, "cab"),
+            (&[0xff; 16], 16, "ffffffffffffffffffffffffffffffff"),
+        ] {
+            let id = ID::try_from(bytes).unwrap();
+            let zid: Zid = id.to_zenoh_id();
+            assert_eq!(&zid.0[..bytes.len()], bytes);
+            assert_eq!(zid.size(), size);
+            assert_eq!(id.size(), size);
+            let mut s = String::new();
+            zid.write_hex(&mut s).unwrap();
+            assert_eq!(s, hex);
+            assert_eq!(id.to_string(), hex);
+            assert_eq!(ID::from_zenoh_id(&zid), Ok(id));
+        }
+
+        let zero = Zid([0; 16]);
+        assert_eq!(zero.size(), 0);
+        assert_eq!(ID::from_zenoh_id(&zero), Err(IdError::Zero));
+    }
+}
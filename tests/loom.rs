@@ -0,0 +1,78 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+//! Model of the concurrent accesses to the lock-free latest time of an HLC.
+//!
+//! Run with: `RUSTFLAGS="--cfg uhlc_loom" cargo test --release --test loom`
+#![cfg(uhlc_loom)]
+
+use loom::thread;
+use uhlc::*;
+
+fn model_clock() -> NTP64 {
+    NTP64(10 << 32)
+}
+
+#[test]
+fn new_timestamp_vs_update() {
+    loom::model(|| {
+        let hlc = HLCBuilder::new().with_clock(model_clock).build();
+        let peer = Timestamp::new(NTP64((10 << 32) + 0x20), ID::rand());
+
+        let issuer = hlc.clone();
+        let t1 = thread::spawn(move || vec![issuer.new_timestamp(), issuer.new_timestamp()]);
+        let updater = hlc.clone();
+        let t2 = thread::spawn(move || {
+            updater.update_with_timestamp(&peer).unwrap();
+            vec![updater.new_timestamp()]
+        });
+        let mut stamps = t1.join().unwrap();
+        let after_update = t2.join().unwrap();
+
+        // monotonicity per thread, and causality with the update
+        assert!(stamps[0] < stamps[1]);
+        assert!(after_update[0] > peer);
+
+        // uniqueness across threads, and the next timestamp is greater than all of them
+        stamps.extend(after_update);
+        let last = hlc.new_timestamp();
+        stamps.sort();
+        stamps.dedup();
+        assert_eq!(stamps.len(), 3);
+        assert!(stamps.iter().all(|ts| *ts < last));
+        assert!(last > peer);
+    });
+}
+
+#[test]
+fn concurrent_updates() {
+    loom::model(|| {
+        let hlc = HLCBuilder::new().with_clock(model_clock).build();
+        let peers: Vec<Timestamp> = (1..=2)
+            .map(|i| Timestamp::new(NTP64((10 << 32) + (i << 8)), ID::rand()))
+            .collect();
+
+        let handles: Vec<_> = peers
+            .iter()
+            .map(|peer| {
+                let (hlc, peer) = (hlc.clone(), *peer);
+                thread::spawn(move || {
+                    hlc.update_with_timestamp(&peer).unwrap();
+                    hlc.new_timestamp()
+                })
+            })
+            .collect();
+        for (handle, peer) in handles.into_iter().zip(&peers) {
+            assert!(handle.join().unwrap() > *peer);
+        }
+        // no update is lost
+        assert!(peers.iter().all(|peer| hlc.new_timestamp() > *peer));
+    });
+}